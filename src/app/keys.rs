@@ -148,11 +148,14 @@ impl App {
                 Tab::Status => match self.state.status.submenu {
                     Submenu::Processes => self.state.status.submenu = Submenu::Benchmarks,
                     Submenu::P2pool => self.state.status.submenu = Submenu::Processes,
-                    Submenu::Benchmarks => self.state.status.submenu = Submenu::P2pool,
+                    Submenu::Workers => self.state.status.submenu = Submenu::P2pool,
+                    Submenu::Charts => self.state.status.submenu = Submenu::Workers,
+                    Submenu::Benchmarks => self.state.status.submenu = Submenu::Charts,
                 },
                 Tab::Gupax => flip!(self.state.gupax.simple),
                 Tab::P2pool => flip!(self.state.p2pool.simple),
                 Tab::Xmrig => flip!(self.state.xmrig.simple),
+                Tab::Xvb => flip!(self.state.xvb.simple),
                 _ => (),
             };
         // Change Submenu RIGHT
@@ -160,12 +163,15 @@ impl App {
             match self.tab {
                 Tab::Status => match self.state.status.submenu {
                     Submenu::Processes => self.state.status.submenu = Submenu::P2pool,
-                    Submenu::P2pool => self.state.status.submenu = Submenu::Benchmarks,
+                    Submenu::P2pool => self.state.status.submenu = Submenu::Workers,
+                    Submenu::Workers => self.state.status.submenu = Submenu::Charts,
+                    Submenu::Charts => self.state.status.submenu = Submenu::Benchmarks,
                     Submenu::Benchmarks => self.state.status.submenu = Submenu::Processes,
                 },
                 Tab::Gupax => flip!(self.state.gupax.simple),
                 Tab::P2pool => flip!(self.state.p2pool.simple),
                 Tab::Xmrig => flip!(self.state.xmrig.simple),
+                Tab::Xvb => flip!(self.state.xvb.simple),
                 _ => (),
             };
         }
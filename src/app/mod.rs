@@ -3,21 +3,25 @@ use crate::cli::Cli;
 use crate::components::gupax::FileWindow;
 use crate::components::node::Ping;
 use crate::components::node::RemoteNode;
-use crate::components::node::REMOTE_NODES;
 use crate::components::update::Update;
 use crate::disk::consts::NODE_TOML;
 use crate::disk::consts::POOL_TOML;
 use crate::disk::consts::STATE_TOML;
 use crate::disk::get_gupax_data_path;
 use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+use crate::disk::history::{unix_now, History};
 use crate::disk::node::Node;
 use crate::disk::pool::Pool;
 use crate::disk::state::State;
 use crate::errors::ErrorButtons;
 use crate::errors::ErrorFerris;
 use crate::errors::ErrorState;
+use crate::helper::http_api::HttpApi;
+use crate::helper::metrics::Metrics;
+use crate::helper::node_health::NodeHealth;
 use crate::helper::p2pool::ImgP2pool;
 use crate::helper::p2pool::PubP2poolApi;
+use crate::helper::supervisor::Supervisor;
 use crate::helper::xrig::xmrig::ImgXmrig;
 use crate::helper::xrig::xmrig::PubXmrigApi;
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
@@ -121,12 +125,14 @@ pub struct App {
     pub sudo: Arc<Mutex<SudoState>>, // This is just a dummy struct on [Windows].
     // State from [--flags]
     pub no_startup: bool,
+    pub daemon: bool, // Running headless with [gupaxx daemon]
     // Gupax-P2Pool API
     // Gupax's P2Pool API (e.g: ~/.local/share/gupax/p2pool/)
     // This is a file-based API that contains data for permanent stats.
     // The below struct holds everything needed for it, the paths, the
     // actual stats, and all the functions needed to mutate them.
     pub gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>,
+    pub history: Arc<Mutex<History>>,
     // Static stuff
     pub benchmarks: Vec<Benchmark>,     // XMRig CPU benchmarks
     pub pid: sysinfo::Pid,              // Gupax's PID
@@ -288,7 +294,9 @@ impl App {
             resizing: false,
             alpha: 0,
             no_startup: false,
+            daemon: false,
             gupax_p2pool_api: arc_mut!(GupaxP2poolApi::new()),
+            history: arc_mut!(History::new()),
             pub_sys,
             benchmarks,
             pid,
@@ -475,6 +483,26 @@ impl App {
         drop(gupax_p2pool_api);
         lock!(app.helper).gupax_p2pool_api = Arc::clone(&app.gupax_p2pool_api);
 
+        //----------------------------------------------------------------------------------------------------
+        // Read [History] disk files, losing it is not worth stopping Gupaxx.
+        info!("App Init | Reading History files...");
+        let history_path = crate::disk::get_history_path(&app.os_data_path);
+        let mut history = lock!(app.history);
+        history.fill_paths(&history_path);
+        match History::create_all_files(&history_path)
+            .and_then(|_| history.read_all_files())
+            .and_then(|_| history.compact(unix_now()))
+        {
+            Ok(_) => info!(
+                "History ... Samples: {} | XvB decisions: {}",
+                history.samples.len(),
+                history.xvb.len()
+            ),
+            Err(e) => warn!("History ... FAIL ... {}", e),
+        }
+        drop(history);
+        lock!(app.helper).history = Arc::clone(&app.history);
+
         //----------------------------------------------------------------------------------------------------
         let mut og = lock!(app.og); // Lock [og]
                                     // Handle max threads
@@ -570,8 +598,8 @@ impl App {
         info!("App Init | Setting saved [Tab]...");
         app.tab = app.state.gupax.tab;
 
-        // Set saved Hero mode to runtime.
-        app.xvb_api.lock().unwrap().stats_priv.runtime_hero_mode = app.state.xvb.hero;
+        // Set saved XvB mode to runtime.
+        app.xvb_api.lock().unwrap().stats_priv.runtime_mode = app.state.xvb.mode;
 
         // Check if [P2pool.node] exists
         info!("App Init | Checking if saved remote node still exists...");
@@ -584,6 +612,39 @@ impl App {
         Helper::spawn_helper(&app.helper, sysinfo, app.pid, app.max_threads);
         info!("Helper ... OK");
 
+        // Spawn the local HTTP API.
+        if app.state.gupax.http_api {
+            HttpApi::new(
+                &app.helper,
+                &app.og,
+                &app.sudo,
+                &app.ping,
+                &app.node_path,
+                &app.state.gupax.http_api_token,
+            )
+            .spawn(&app.state.gupax);
+        }
+
+        // Spawn the process supervisor, it does nothing unless enabled for a process.
+        Supervisor::new(
+            &app.helper,
+            &app.og,
+            &app.sudo,
+            &app.ping,
+            &app.node_path,
+            &app.state_path,
+            &app.exe,
+        )
+        .spawn();
+
+        // Spawn the P2Pool node health monitor, it does nothing unless [failover] is enabled.
+        NodeHealth::new(&app.helper, &app.og, &app.node_path).spawn();
+
+        // Spawn the Prometheus exporter.
+        if app.state.gupax.metrics {
+            Metrics::new(&app.helper).spawn(&app.state.gupax);
+        }
+
         // Check for privilege. Should be Admin on [Windows] and NOT root on Unix.
         info!("App Init | Checking for privilege level...");
         #[cfg(target_os = "windows")]
@@ -615,65 +676,7 @@ impl App {
     #[cold]
     #[inline(never)]
     pub fn gather_backup_hosts(&self) -> Option<Vec<Node>> {
-        if !self.state.p2pool.backup_host {
-            return None;
-        }
-
-        // INVARIANT:
-        // We must ensure all nodes are capable of
-        // sending/receiving valid JSON-RPC requests.
-        //
-        // This is done during the `Ping` phase, meaning
-        // all the nodes listed in our `self.ping` should
-        // have ping data. We can use this data to filter
-        // out "dead" nodes.
-        //
-        // The user must have at least pinged once so that
-        // we actually have this data to work off of, else,
-        // this "backup host" feature will return here
-        // with 0 extra nodes as we can't be sure that any
-        // of them are actually online.
-        //
-        // Realistically, most of them are, but we can't be sure,
-        // and checking here without explicitly asking the user
-        // to connect to nodes is a no-go (also, non-async environment).
-        if !lock!(self.ping).pinged {
-            warn!("Backup hosts ... simple node backup: no ping data available, returning None");
-            return None;
-        }
-
-        if self.state.p2pool.simple {
-            let mut vec = Vec::with_capacity(REMOTE_NODES.len());
-
-            // Locking during this entire loop should be fine,
-            // only a few nodes to iter through.
-            for pinged_node in lock!(self.ping).nodes.iter() {
-                // Continue if this node is not green/yellow.
-                if pinged_node.ms > crate::components::node::RED_NODE_PING {
-                    continue;
-                }
-
-                let (ip, rpc, zmq) = RemoteNode::get_ip_rpc_zmq(pinged_node.ip);
-
-                let node = Node {
-                    ip: ip.into(),
-                    rpc: rpc.into(),
-                    zmq: zmq.into(),
-                };
-
-                vec.push(node);
-            }
-
-            if vec.is_empty() {
-                warn!("Backup hosts ... simple node backup: no viable nodes found");
-                None
-            } else {
-                info!("Backup hosts ... simple node backup list: {vec:#?}");
-                Some(vec)
-            }
-        } else {
-            Some(self.node_vec.iter().map(|(_, node)| node.clone()).collect())
-        }
+        lock!(self.ping).backup_hosts(&self.state.p2pool, &self.node_vec)
     }
 }
 //---------------------------------------------------------------------------------------------------- [Tab] Enum + Impl
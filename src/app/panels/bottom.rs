@@ -5,6 +5,7 @@ use crate::disk::node::Node;
 use crate::disk::pool::Pool;
 use crate::disk::state::{Gupax, State};
 use crate::disk::status::Submenu;
+use crate::helper::args::{override_error, Binary};
 use crate::helper::{Helper, ProcessSignal, ProcessState};
 use crate::utils::constants::*;
 use crate::utils::errors::{ErrorButtons, ErrorFerris};
@@ -128,14 +129,17 @@ impl crate::app::App {
                                 wants_input,
                             );
                         }
-                        Tab::Xvb => self.xvb_run_actions(
-                            ui,
-                            height,
-                            xvb_is_waiting,
-                            xvb_is_alive,
-                            key,
-                            wants_input,
-                        ),
+                        Tab::Xvb => {
+                            self.xvb_submenu(ui, size);
+                            self.xvb_run_actions(
+                                ui,
+                                height,
+                                xvb_is_waiting,
+                                xvb_is_alive,
+                                key,
+                                wants_input,
+                            );
+                        }
                         Tab::About => {}
                     }
                 });
@@ -224,6 +228,28 @@ impl crate::app::App {
                 self.state.status.submenu = Submenu::Benchmarks;
             }
             ui.separator();
+            if ui
+                .add_sized(
+                    size,
+                    SelectableLabel::new(self.state.status.submenu == Submenu::Charts, "Charts"),
+                )
+                .on_hover_text(STATUS_SUBMENU_CHARTS)
+                .clicked()
+            {
+                self.state.status.submenu = Submenu::Charts;
+            }
+            ui.separator();
+            if ui
+                .add_sized(
+                    size,
+                    SelectableLabel::new(self.state.status.submenu == Submenu::Workers, "Workers"),
+                )
+                .on_hover_text(STATUS_SUBMENU_WORKERS)
+                .clicked()
+            {
+                self.state.status.submenu = Submenu::Workers;
+            }
+            ui.separator();
             if ui
                 .add_sized(
                     size,
@@ -374,6 +400,13 @@ impl crate::app::App {
                 ) {
                     ui_enabled = false;
                     text = format!("Error: {}", P2POOL_PATH_NOT_VALID);
+                } else if let Some(e) = override_error(
+                    Binary::P2pool,
+                    self.state.p2pool.simple,
+                    &self.state.p2pool.arguments,
+                ) {
+                    ui_enabled = false;
+                    text = format!("Error: {}", e);
                 }
                 ui.set_enabled(ui_enabled);
                 let color = if ui_enabled { GREEN } else { RED };
@@ -450,6 +483,30 @@ impl crate::app::App {
             }
         });
     }
+    fn xvb_submenu(&mut self, ui: &mut Ui, size: Vec2) {
+        ui.group(|ui| {
+            let width = size.x / 1.5;
+            let size = vec2(width, size.y);
+            if ui
+                .add_sized(
+                    size,
+                    SelectableLabel::new(!self.state.xvb.simple, "Advanced"),
+                )
+                .on_hover_text(XVB_ADVANCED)
+                .clicked()
+            {
+                self.state.xvb.simple = false;
+            }
+            ui.separator();
+            if ui
+                .add_sized(size, SelectableLabel::new(self.state.xvb.simple, "Simple"))
+                .on_hover_text(XVB_SIMPLE)
+                .clicked()
+            {
+                self.state.xvb.simple = true;
+            }
+        });
+    }
     fn xmrig_run_actions(
         &mut self,
         ui: &mut Ui,
@@ -525,6 +582,13 @@ impl crate::app::App {
                 {
                     ui_enabled = false;
                     text = format!("Error: {}", XMRIG_PATH_NOT_VALID);
+                } else if let Some(e) = override_error(
+                    Binary::Xmrig,
+                    self.state.xmrig.simple,
+                    &self.state.xmrig.arguments,
+                ) {
+                    ui_enabled = false;
+                    text = format!("Error: {}", e);
                 }
                 ui.set_enabled(ui_enabled);
                 let color = if ui_enabled { GREEN } else { RED };
@@ -618,6 +682,13 @@ impl crate::app::App {
                 ) {
                     ui_enabled = false;
                     text = format!("Error: {}", XMRIG_PROXY_PATH_NOT_VALID);
+                } else if let Some(e) = override_error(
+                    Binary::XmrigProxy,
+                    self.state.xmrig_proxy.simple,
+                    &self.state.xmrig_proxy.arguments,
+                ) {
+                    ui_enabled = false;
+                    text = format!("Error: {}", e);
                 }
                 ui.set_enabled(ui_enabled);
                 let color = if ui_enabled { GREEN } else { RED };
@@ -2,9 +2,10 @@ use crate::app::panels::middle::*;
 use crate::app::ErrorState;
 use crate::app::Restart;
 use crate::components::gupax::*;
-use crate::components::update::Update;
+use crate::components::update::{Update, UpdateChannel};
 use crate::disk::state::*;
 use crate::macros::lock2;
+use crate::regex::REGEXES;
 use log::debug;
 use std::path::Path;
 use std::sync::Arc;
@@ -52,13 +53,35 @@ impl Gupax {
                     #[cfg(not(feature = "distro"))]
                     ui.set_enabled(!updating && *lock!(restart) == Restart::No);
                     #[cfg(not(feature = "distro"))]
-                    if ui
-                        .add_sized([width, button], Button::new("Check for updates"))
-                        .on_hover_text(GUPAX_UPDATE)
-                        .clicked()
-                    {
-                        Update::spawn_thread(og, self, state_path, update, error_state, restart);
-                    }
+                    ui.horizontal(|ui| {
+                        let width = (width - SPACE * 2.0) / 4.0;
+                        if ui
+                            .add_sized([width * 3.0, button], Button::new("Check for updates"))
+                            .on_hover_text(GUPAX_UPDATE)
+                            .clicked()
+                        {
+                            Update::spawn_thread(
+                                og,
+                                self,
+                                state_path,
+                                update,
+                                error_state,
+                                restart,
+                            );
+                        }
+                        ui.separator();
+                        let backup = lock2!(og, version).backup.clone();
+                        ui.add_enabled_ui(!backup.is_empty(), |ui| {
+                            if ui
+                                .add_sized([width, button], Button::new("Roll back"))
+                                .on_hover_text(format!("{} [{}]", GUPAX_ROLLBACK, backup))
+                                .on_disabled_hover_text(GUPAX_NO_ROLLBACK)
+                                .clicked()
+                            {
+                                Self::rollback(og, state_path, update, restart);
+                            }
+                        });
+                    });
                 });
                 ui.vertical(|ui| {
                     ui.set_enabled(updating);
@@ -74,12 +97,52 @@ impl Gupax {
                     }
                     ui.add_sized(size, ProgressBar::new(lock2!(update, prog).round() / 100.0));
                 });
+                // Release notes, read before applying the update.
+                let release = lock2!(update, release).clone();
+                if let Some(release) = release {
+                    ui.separator();
+                    ui.add_sized(
+                        [width, height / 3.0],
+                        Label::new(
+                            RichText::new(format!("Gupaxx {} release notes", release.tag_name))
+                                .underline()
+                                .color(LIGHT_GRAY),
+                        ),
+                    );
+                    egui::ScrollArea::vertical()
+                        .id_source("release_notes")
+                        .max_height(height * 2.0)
+                        .show(ui, |ui| {
+                            ui.set_width(width);
+                            ui.label(release.body.as_deref().unwrap_or("No release notes"));
+                        });
+                    if lock2!(update, apply).is_none() {
+                        ui.horizontal(|ui| {
+                            let width = (width - SPACE * 2.0) / 2.0;
+                            if ui
+                                .add_sized([width, button], Button::new("Apply"))
+                                .on_hover_text(GUPAX_UPDATE_APPLY)
+                                .clicked()
+                            {
+                                *lock2!(update, apply) = Some(true);
+                            }
+                            ui.separator();
+                            if ui
+                                .add_sized([width, button], Button::new("Cancel"))
+                                .on_hover_text(GUPAX_UPDATE_CANCEL)
+                                .clicked()
+                            {
+                                *lock2!(update, apply) = Some(false);
+                            }
+                        });
+                    }
+                }
             });
 
             debug!("Gupaxx Tab | Rendering bool buttons");
             ui.horizontal(|ui| {
                 ui.group(|ui| {
-                    let width = (size.x - SPACE * 17.0) / 8.0;
+                    let width = (size.x - SPACE * 19.0) / 9.0;
                     let height = if self.simple {
                         size.y / 10.0
                     } else {
@@ -119,6 +182,12 @@ impl Gupax {
                         Checkbox::new(&mut self.save_before_quit, "Save on quit"),
                     )
                     .on_hover_text(GUPAX_SAVE_BEFORE_QUIT);
+                    ui.separator();
+                    ui.add_sized(
+                        size,
+                        Checkbox::new(&mut self.auto_rollback, "Auto-Rollback"),
+                    )
+                    .on_hover_text(GUPAX_AUTO_ROLLBACK);
                 });
             });
 
@@ -255,6 +324,56 @@ impl Gupax {
                     .on_hover_text(GUPAX_PATH_XMRIG_PROXY);
                 });
             });
+
+            debug!("Gupaxx Tab | Rendering update channel and pinned versions");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(
+                        RichText::new("Update Channel/Pinned Versions")
+                            .underline()
+                            .color(LIGHT_GRAY),
+                    ),
+                );
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let width = (ui.available_width() / 2.0) - SPACE;
+                    for (channel, hover) in [
+                        (UpdateChannel::Stable, GUPAX_CHANNEL_STABLE),
+                        (UpdateChannel::PreRelease, GUPAX_CHANNEL_PRERELEASE),
+                    ] {
+                        if ui
+                            .add_sized(
+                                [width, height],
+                                SelectableLabel::new(
+                                    self.update_channel == channel,
+                                    channel.to_string(),
+                                ),
+                            )
+                            .on_hover_text(hover)
+                            .clicked()
+                        {
+                            self.update_channel = channel;
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let width = (ui.available_width() / 4.0) - text_edit - SPACE * 2.0;
+                    for (name, pin, hover) in [
+                        ("Gupaxx", &mut self.pinned_gupax, GUPAX_PIN_GUPAX),
+                        ("P2Pool", &mut self.pinned_p2pool, GUPAX_PIN_P2POOL),
+                        ("XMRig", &mut self.pinned_xmrig, GUPAX_PIN_XMRIG),
+                        ("XMRig-Proxy", &mut self.pinned_xp, GUPAX_PIN_XMRIG_PROXY),
+                    ] {
+                        ui.add_sized([text_edit, height], Label::new(name));
+                        ui.add_sized(
+                            [width, height],
+                            TextEdit::singleline(pin).hint_text("latest"),
+                        )
+                        .on_hover_text(hover);
+                    }
+                });
+            });
             let mut guard = lock!(file_window);
             if guard.picked_p2pool {
                 self.p2pool_path.clone_from(&guard.p2pool_path);
@@ -464,6 +583,138 @@ impl Gupax {
                     }
                 })
             });
+
+            // Local HTTP API
+            debug!("Gupaxx Tab | Rendering [HTTP API] settings");
+            let height = size.y / 28.0;
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(
+                        RichText::new("Local HTTP API")
+                            .underline()
+                            .color(LIGHT_GRAY),
+                    ),
+                )
+                .on_hover_text(GUPAX_HTTP_API);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let width = size.x / 10.0;
+                    ui.add_sized([width, height], Checkbox::new(&mut self.http_api, "Enable"))
+                        .on_hover_text(GUPAX_HTTP_API);
+                    ui.separator();
+                    let (text, color) = if self.http_api_ip == "localhost"
+                        || REGEXES.ipv4.is_match(&self.http_api_ip)
+                    {
+                        ("IP ✔", GREEN)
+                    } else {
+                        ("IP ❌", RED)
+                    };
+                    ui.add_sized(
+                        [width / 2.0, height],
+                        Label::new(RichText::new(text).color(color)),
+                    );
+                    ui.add_sized(
+                        [width * 1.5, height],
+                        TextEdit::singleline(&mut self.http_api_ip),
+                    )
+                    .on_hover_text(GUPAX_HTTP_API_IP);
+                    self.http_api_ip.truncate(255);
+                    let (text, color) = if REGEXES.port.is_match(&self.http_api_port) {
+                        ("Port ✔", GREEN)
+                    } else {
+                        ("Port ❌", RED)
+                    };
+                    ui.add_sized(
+                        [width / 2.0, height],
+                        Label::new(RichText::new(text).color(color)),
+                    );
+                    ui.add_sized(
+                        [width / 1.5, height],
+                        TextEdit::singleline(&mut self.http_api_port),
+                    )
+                    .on_hover_text(GUPAX_HTTP_API_PORT);
+                    self.http_api_port.truncate(5);
+                    ui.add_sized([width / 2.0, height], Label::new("Token"));
+                    ui.add_sized(
+                        [ui.available_width(), height],
+                        TextEdit::singleline(&mut self.http_api_token),
+                    )
+                    .on_hover_text(GUPAX_HTTP_API_TOKEN);
+                });
+            });
+
+            // Prometheus metrics
+            debug!("Gupaxx Tab | Rendering [Metrics] settings");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(
+                        RichText::new("Prometheus metrics")
+                            .underline()
+                            .color(LIGHT_GRAY),
+                    ),
+                )
+                .on_hover_text(GUPAX_METRICS);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let width = size.x / 10.0;
+                    ui.add_sized([width, height], Checkbox::new(&mut self.metrics, "Enable"))
+                        .on_hover_text(GUPAX_METRICS);
+                    ui.separator();
+                    let (text, color) = if self.metrics_ip == "localhost"
+                        || REGEXES.ipv4.is_match(&self.metrics_ip)
+                    {
+                        ("IP ✔", GREEN)
+                    } else {
+                        ("IP ❌", RED)
+                    };
+                    ui.add_sized(
+                        [width / 2.0, height],
+                        Label::new(RichText::new(text).color(color)),
+                    );
+                    ui.add_sized(
+                        [width * 1.5, height],
+                        TextEdit::singleline(&mut self.metrics_ip),
+                    )
+                    .on_hover_text(GUPAX_METRICS_IP);
+                    self.metrics_ip.truncate(255);
+                    let (text, color) = if REGEXES.port.is_match(&self.metrics_port) {
+                        ("Port ✔", GREEN)
+                    } else {
+                        ("Port ❌", RED)
+                    };
+                    ui.add_sized(
+                        [width / 2.0, height],
+                        Label::new(RichText::new(text).color(color)),
+                    );
+                    ui.add_sized(
+                        [width / 1.5, height],
+                        TextEdit::singleline(&mut self.metrics_port),
+                    )
+                    .on_hover_text(GUPAX_METRICS_PORT);
+                    self.metrics_port.truncate(5);
+                });
+            });
         });
     }
+
+    // Result is shown in place of the update message.
+    fn rollback(
+        og: &Arc<Mutex<State>>,
+        state_path: &Path,
+        update: &Arc<Mutex<Update>>,
+        restart: &Arc<Mutex<Restart>>,
+    ) {
+        let path_gupax = lock!(update).path_gupax.clone();
+        let result = Update::get_backup_dir()
+            .and_then(|dir| Update::rollback(og, state_path, &path_gupax, &dir));
+        *lock2!(update, msg) = match result {
+            Ok(version) => {
+                *lock!(restart) = Restart::Yes;
+                format!("Rolled back to {}\nYou need to restart Gupaxx.", version)
+            }
+            Err(e) => format!("Rollback failed | {}", e),
+        };
+    }
 }
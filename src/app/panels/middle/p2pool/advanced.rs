@@ -291,18 +291,29 @@ impl P2pool {
             });
         });
 
-        debug!("P2Pool Tab | Rendering Backup host button");
+        self.supervisor.show(ui, size.x, height / 2.0);
+
+        debug!("P2Pool Tab | Rendering Backup host/Failover buttons");
         ui.group(|ui| {
-            let width = size.x - SPACE;
+            let width = (size.x / 2.0) - (SPACE * 1.75);
             let height = ui.available_height();
             ui.style_mut().spacing.icon_width = height;
             ui.style_mut().spacing.icon_width_inner = height * 0.9;
-            // [Backup host]
-            ui.add_sized(
-                [width, height],
-                Checkbox::new(&mut self.backup_host, "Backup host"),
-            )
-            .on_hover_text(P2POOL_BACKUP_HOST_ADVANCED);
+            ui.horizontal(|ui| {
+                // [Backup host]
+                ui.add_sized(
+                    [width, height],
+                    Checkbox::new(&mut self.backup_host, "Backup host"),
+                )
+                .on_hover_text(P2POOL_BACKUP_HOST_ADVANCED);
+                ui.separator();
+                // [Failover]
+                ui.add_sized(
+                    [width, height],
+                    Checkbox::new(&mut self.failover, "Failover"),
+                )
+                .on_hover_text(P2POOL_FAILOVER);
+            });
         });
     }
 }
@@ -1,5 +1,6 @@
 use crate::disk::node::Node;
 use crate::disk::state::{P2pool, State};
+use crate::helper::args::{override_error, override_warning, Binary};
 use crate::helper::p2pool::PubP2poolApi;
 use crate::regex::num_lines;
 // Gupax - GUI Uniting P2Pool And XMRig
@@ -127,7 +128,15 @@ impl P2pool {
                     )
                     .on_hover_text(P2POOL_ARGUMENTS);
                     self.arguments.truncate(1024);
-                })
+                });
+                // Bad values are shown before the start, unknown options are only a warning.
+                if let Some(e) = override_error(Binary::P2pool, self.simple, &self.arguments) {
+                    ui.label(RichText::new(format!("Error: {}", e)).color(RED));
+                } else if let Some(e) =
+                    override_warning(Binary::P2pool, self.simple, &self.arguments)
+                {
+                    ui.label(RichText::new(format!("Warning: {}", e)).color(ORANGE));
+                }
             });
             ui.set_enabled(self.arguments.is_empty());
         }
@@ -159,7 +159,7 @@ impl P2pool {
         debug!("P2Pool Tab | Rendering [Auto-*] buttons");
         ui.group(|ui| {
             ui.horizontal(|ui| {
-                let width = (size.x / 3.0) - (SPACE * 1.75);
+                let width = (size.x / 4.0) - (SPACE * 1.75);
                 let size = vec2(width, height);
                 // [Auto-node]
                 ui.add_sized(size, Checkbox::new(&mut self.auto_select, "Auto-select"))
@@ -172,6 +172,10 @@ impl P2pool {
                 // [Backup host]
                 ui.add_sized(size, Checkbox::new(&mut self.backup_host, "Backup host"))
                     .on_hover_text(P2POOL_BACKUP_HOST_SIMPLE);
+                ui.separator();
+                // [Failover]
+                ui.add_sized(size, Checkbox::new(&mut self.failover, "Failover"))
+                    .on_hover_text(P2POOL_FAILOVER);
             })
         });
 
@@ -0,0 +1,161 @@
+use std::sync::{Arc, Mutex};
+
+use egui::{Label, RichText, SelectableLabel, Vec2};
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+
+use crate::{
+    disk::{
+        history::{unix_now, History, Sample},
+        state::Status,
+        status::ChartWindow,
+    },
+    utils::{constants::*, macros::lock},
+};
+
+impl Status {
+    pub fn charts(&mut self, size: Vec2, ui: &mut egui::Ui, history: &Arc<Mutex<History>>) {
+        let height = size.y;
+        let width = size.x;
+        let text = height / 25.0;
+        // Window buttons
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                let width = (width / 3.0) - (SPACE * 4.0);
+                for (window, hover) in [
+                    (ChartWindow::Hour, STATUS_CHARTS_HOUR),
+                    (ChartWindow::Day, STATUS_CHARTS_DAY),
+                    (ChartWindow::Week, STATUS_CHARTS_WEEK),
+                ] {
+                    if ui
+                        .add_sized(
+                            [width, text],
+                            SelectableLabel::new(self.chart_window == window, window.to_string()),
+                        )
+                        .on_hover_text(hover)
+                        .clicked()
+                    {
+                        self.chart_window = window;
+                    }
+                    if window != ChartWindow::Week {
+                        ui.separator();
+                    }
+                }
+            });
+        });
+
+        // Copy what is needed so [History] is not locked while drawing.
+        let now = unix_now();
+        let start = now.saturating_sub(self.chart_window.seconds());
+        let unit = self.chart_window.unit() as f64;
+        // X is the time relative to now, in minutes or hours.
+        let x = move |time: u64| (time as f64 - now as f64) / unit;
+        let (samples, xvb) = {
+            let history = lock!(history);
+            let xvb: Vec<(f64, u32)> = history
+                .xvb_since(start)
+                .iter()
+                .map(|d| (x(d.time), d.donated))
+                .collect();
+            (history.samples_since(start).to_vec(), xvb)
+        };
+        let line = |f: fn(&Sample) -> f64| -> PlotPoints {
+            samples.iter().map(|s| [x(s.time), f(s)]).collect()
+        };
+        let x_label = if self.chart_window == ChartWindow::Hour {
+            "minutes ago"
+        } else {
+            "hours ago"
+        };
+        let x_min = -(self.chart_window.seconds() as f64 / unit);
+        let plot_height = (height - text * 4.0) / 3.0;
+        let plot = |id: &str| {
+            Plot::new(id)
+                .height(plot_height)
+                .legend(Legend::default())
+                .include_x(x_min)
+                .include_x(0.0)
+                .include_y(0.0)
+                .x_axis_label(x_label)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .allow_scroll(false)
+        };
+
+        // Hashrate
+        ui.group(|ui| {
+            ui.add_sized(
+                [width, text / 2.0],
+                Label::new(
+                    RichText::new("Hashrate (kH/s)")
+                        .underline()
+                        .color(LIGHT_GRAY),
+                ),
+            )
+            .on_hover_text(STATUS_CHARTS_HASHRATE);
+            plot("chart_hashrate").show(ui, |plot_ui| {
+                plot_ui.line(Line::new(line(|s| s.xmrig_hashrate as f64 / 1000.0)).name("XMRig"));
+                plot_ui
+                    .line(Line::new(line(|s| s.xp_hashrate as f64 / 1000.0)).name("XMRig-Proxy"));
+                plot_ui.line(
+                    Line::new(line(|s| s.p2pool_hashrate_15m as f64 / 1000.0)).name("P2Pool 15m"),
+                );
+                plot_ui.line(
+                    Line::new(line(|s| s.p2pool_hashrate_1h as f64 / 1000.0)).name("P2Pool 1h"),
+                );
+                plot_ui.line(
+                    Line::new(line(|s| s.p2pool_hashrate_24h as f64 / 1000.0)).name("P2Pool 24h"),
+                );
+            });
+        });
+
+        // Effort
+        ui.group(|ui| {
+            ui.add_sized(
+                [width, text / 2.0],
+                Label::new(
+                    RichText::new("Current effort (%)")
+                        .underline()
+                        .color(LIGHT_GRAY),
+                ),
+            )
+            .on_hover_text(STATUS_CHARTS_EFFORT);
+            plot("chart_effort").show(ui, |plot_ui| {
+                plot_ui.line(Line::new(line(|s| s.current_effort as f64)).name("Effort"));
+            });
+        });
+
+        // XvB/P2Pool split, one stacked bar per run of the algorithm.
+        ui.group(|ui| {
+            ui.add_sized(
+                [width, text / 2.0],
+                Label::new(
+                    RichText::new("XvB/P2Pool time split (%)")
+                        .underline()
+                        .color(LIGHT_GRAY),
+                ),
+            )
+            .on_hover_text(STATUS_CHARTS_SPLIT);
+            let bar_width = XVB_TIME_ALGO as f64 / unit * 0.9;
+            let percent = |donated: u32| donated as f64 / XVB_TIME_ALGO as f64 * 100.0;
+            let xvb_bars = BarChart::new(
+                xvb.iter()
+                    .map(|(x, donated)| Bar::new(*x, percent(*donated)).width(bar_width))
+                    .collect(),
+            )
+            .name("XvB")
+            .color(GREEN);
+            let p2pool_bars = BarChart::new(
+                xvb.iter()
+                    .map(|(x, donated)| Bar::new(*x, 100.0 - percent(*donated)).width(bar_width))
+                    .collect(),
+            )
+            .name("P2Pool")
+            .color(LIGHT_GRAY)
+            .stack_on(&[&xvb_bars]);
+            plot("chart_split").include_y(100.0).show(ui, |plot_ui| {
+                plot_ui.bar_chart(xvb_bars);
+                plot_ui.bar_chart(p2pool_bars);
+            });
+        });
+    }
+}
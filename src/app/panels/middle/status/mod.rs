@@ -19,7 +19,7 @@ use egui::Vec2;
 
 use crate::{
     app::Benchmark,
-    disk::{gupax_p2pool_api::GupaxP2poolApi, state::Status, status::*},
+    disk::{gupax_p2pool_api::GupaxP2poolApi, history::History, state::Status, status::*},
     helper::{
         p2pool::{ImgP2pool, PubP2poolApi},
         xrig::{
@@ -33,8 +33,10 @@ use crate::{
 use std::sync::{Arc, Mutex};
 
 mod benchmarks;
+mod charts;
 mod p2pool;
 mod processes;
+mod workers;
 
 impl Status {
     #[inline(always)] // called once
@@ -54,6 +56,7 @@ impl Status {
         xvb_alive: bool,
         max_threads: usize,
         gupax_p2pool_api: &Arc<Mutex<GupaxP2poolApi>>,
+        history: &Arc<Mutex<History>>,
         benchmarks: &[Benchmark],
         size: Vec2,
         _ctx: &egui::Context,
@@ -80,6 +83,12 @@ impl Status {
         //---------------------------------------------------------------------------------------------------- [P2Pool]
         } else if self.submenu == Submenu::P2pool {
             self.p2pool(size, ui, gupax_p2pool_api, p2pool_alive, p2pool_api);
+        //---------------------------------------------------------------------------------------------------- [Workers]
+        } else if self.submenu == Submenu::Workers {
+            self.workers(size, ui, p2pool_alive, p2pool_api);
+        //---------------------------------------------------------------------------------------------------- [Charts]
+        } else if self.submenu == Submenu::Charts {
+            self.charts(size, ui, history);
         //---------------------------------------------------------------------------------------------------- [Benchmarks]
         } else if self.submenu == Submenu::Benchmarks {
             self.benchmarks(size, ui, benchmarks, xmrig_alive, xmrig_api)
@@ -60,6 +60,7 @@ impl Status {
                     xmrig_alive,
                     xmrig_api,
                     xmrig_img,
+                    xvb_api,
                     max_threads,
                 );
                 //[XMRig-Proxy]
@@ -351,6 +352,7 @@ fn xmrig(
     xmrig_alive: bool,
     xmrig_api: &Arc<Mutex<PubXmrigApi>>,
     xmrig_img: &Arc<Mutex<ImgXmrig>>,
+    xvb_api: &Arc<Mutex<PubXvbApi>>,
     max_threads: usize,
 ) {
     ui.group(|ui| {
@@ -418,10 +420,15 @@ fn xmrig(
                 Label::new(RichText::new("Threads").underline().color(BONE)),
             )
             .on_hover_text(STATUS_XMRIG_THREADS);
-            ui.add_sized(
-                size,
-                Label::new(format!("{}/{}", &lock!(xmrig_img).threads, max_threads)),
-            );
+            // with the thread split of XvB, a second XMRig mines on XvB with a part of the threads.
+            let threads = match &lock!(xvb_api).thread_split {
+                Some(split) => format!(
+                    "{}/{} on P2Pool\n{}/{} on XvB ({:.0} H/s)",
+                    split.split.p2pool, max_threads, split.split.xvb, max_threads, split.hashrate
+                ),
+                None => format!("{}/{}", &lock!(xmrig_img).threads, max_threads),
+            };
+            ui.add_sized(size, Label::new(threads));
             drop(api);
         })
         // })
@@ -0,0 +1,172 @@
+use std::sync::{Arc, Mutex};
+
+use egui::{Label, RichText, ScrollArea, SelectableLabel, Slider, Vec2};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{
+    disk::{state::Status, status::WorkerSort},
+    helper::p2pool::{P2poolWorker, PubP2poolApi},
+    utils::{
+        constants::*,
+        human::{HumanNumber, HumanTime},
+        macros::lock,
+    },
+};
+
+impl Status {
+    pub fn workers(
+        &mut self,
+        size: Vec2,
+        ui: &mut egui::Ui,
+        p2pool_alive: bool,
+        p2pool_api: &Arc<Mutex<PubP2poolApi>>,
+    ) {
+        let height = size.y;
+        let width = size.x;
+        let text = height / 25.0;
+        let mut workers = lock!(p2pool_api).workers.clone();
+        self.sort_workers(&mut workers);
+        let min = self.worker_min_hashrate;
+        let low = |w: &P2poolWorker| !w.missing && min > 0 && w.hashrate < min;
+        let missing = workers.iter().filter(|w| w.missing).count();
+        let lows = workers.iter().filter(|w| low(w)).count();
+
+        // Summary + minimum hashrate
+        ui.group(|ui| {
+            ui.set_enabled(p2pool_alive);
+            ui.horizontal(|ui| {
+                let width = (width / 4.0) - (SPACE * 3.0);
+                for (label, color) in [
+                    (
+                        format!("Connected: {}", workers.len() - missing),
+                        LIGHT_GRAY,
+                    ),
+                    (format!("Missing: {}", missing), RED),
+                    (format!("Low: {}", lows), YELLOW),
+                ] {
+                    ui.add_sized(
+                        [width, text],
+                        Label::new(RichText::new(label).underline().color(color)),
+                    );
+                    ui.separator();
+                }
+                ui.spacing_mut().slider_width = width - SPACE * 6.0;
+                ui.add(
+                    Slider::new(&mut self.worker_min_hashrate, 0..=100_000)
+                        .logarithmic(true)
+                        .suffix(" H/s"),
+                )
+                .on_hover_text(STATUS_WORKERS_MIN_HASHRATE);
+            });
+        });
+
+        // Table, the headers sort it.
+        let column = (width - SPACE * 12.0) / 6.0;
+        ui.group(|ui| {
+            ui.set_enabled(p2pool_alive);
+            ScrollArea::horizontal().show(ui, |ui| {
+                TableBuilder::new(ui)
+                    .columns(Column::auto(), 6)
+                    .header(text * 1.5, |mut header| {
+                        header.col(|ui| {
+                            ui.add_sized([column, text], Label::new("Status"));
+                        });
+                        for sort in [
+                            WorkerSort::Name,
+                            WorkerSort::Address,
+                            WorkerSort::Hashrate,
+                            WorkerSort::Difficulty,
+                            WorkerSort::Uptime,
+                        ] {
+                            header.col(|ui| {
+                                let selected = self.worker_sort == sort;
+                                let label = match (selected, self.worker_sort_descending) {
+                                    (true, true) => format!("{} ⬇", sort),
+                                    (true, false) => format!("{} ⬆", sort),
+                                    _ => sort.to_string(),
+                                };
+                                if ui
+                                    .add_sized(
+                                        [column, text],
+                                        SelectableLabel::new(selected, label),
+                                    )
+                                    .on_hover_text(STATUS_WORKERS_SORT)
+                                    .clicked()
+                                {
+                                    if selected {
+                                        self.worker_sort_descending = !self.worker_sort_descending;
+                                    } else {
+                                        self.worker_sort = sort;
+                                    }
+                                }
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(text, workers.len(), |mut row| {
+                            let worker = &workers[row.index()];
+                            row.col(|ui| {
+                                let (status, color, hover) = if worker.missing {
+                                    ("Missing", RED, STATUS_WORKERS_MISSING)
+                                } else if low(worker) {
+                                    ("Low", YELLOW, STATUS_WORKERS_LOW)
+                                } else {
+                                    ("OK", GREEN, STATUS_WORKERS_OK)
+                                };
+                                ui.add_sized(
+                                    [column, text],
+                                    Label::new(RichText::new(status).color(color)),
+                                )
+                                .on_hover_text(hover);
+                            });
+                            row.col(|ui| {
+                                ui.add_sized([column, text], Label::new(worker.name.as_str()));
+                            });
+                            row.col(|ui| {
+                                ui.add_sized([column, text], Label::new(worker.address.as_str()));
+                            });
+                            row.col(|ui| {
+                                ui.add_sized(
+                                    [column, text],
+                                    Label::new(format!(
+                                        "{} H/s",
+                                        HumanNumber::from_u64(worker.hashrate)
+                                    )),
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.add_sized(
+                                    [column, text],
+                                    Label::new(HumanNumber::from_u64(worker.difficulty).as_str()),
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.add_sized(
+                                    [column, text],
+                                    Label::new(
+                                        HumanTime::into_human(std::time::Duration::from_secs(
+                                            worker.uptime,
+                                        ))
+                                        .to_string(),
+                                    ),
+                                );
+                            });
+                        });
+                    });
+            });
+        });
+    }
+
+    fn sort_workers(&self, workers: &mut [P2poolWorker]) {
+        match self.worker_sort {
+            WorkerSort::Name => workers.sort_by(|a, b| a.name.cmp(&b.name)),
+            WorkerSort::Address => workers.sort_by(|a, b| a.address.cmp(&b.address)),
+            WorkerSort::Hashrate => workers.sort_by_key(|w| w.hashrate),
+            WorkerSort::Difficulty => workers.sort_by_key(|w| w.difficulty),
+            WorkerSort::Uptime => workers.sort_by_key(|w| w.uptime),
+        }
+        if self.worker_sort_descending {
+            workers.reverse();
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use crate::constants::*;
+use crate::disk::state::SupervisorPolicy;
+use egui::{Checkbox, Label, Slider, TextStyle::*, Ui};
+use log::*;
+
+impl SupervisorPolicy {
+    // Shared by the [P2Pool], [XMRig] and [XMRig-Proxy] advanced tabs.
+    pub(super) fn show(&mut self, ui: &mut Ui, width: f32, height: f32) {
+        debug!("Supervisor | Rendering [Auto-restart] elements");
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.style_mut().spacing.icon_width = height;
+                ui.style_mut().spacing.icon_width_inner = height * 0.9;
+                ui.style_mut().spacing.interact_size.y = height;
+                ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
+                let text = (width / 10.0) - SPACE;
+                ui.add_sized(
+                    [text * 1.5, height],
+                    Checkbox::new(&mut self.enabled, "Auto-restart"),
+                )
+                .on_hover_text(SUPERVISOR_ENABLED);
+                ui.add_enabled_ui(self.enabled, |ui| {
+                    ui.style_mut().spacing.slider_width = text * 1.2;
+                    ui.separator();
+                    ui.add(Label::new("Restarts:"));
+                    ui.add(Slider::new(&mut self.max_restarts, 1..=20))
+                        .on_hover_text(SUPERVISOR_MAX_RESTARTS);
+                    ui.separator();
+                    ui.add(Label::new("Backoff:"));
+                    ui.add(Slider::new(&mut self.backoff, 1..=300).suffix("s"))
+                        .on_hover_text(SUPERVISOR_BACKOFF);
+                    ui.separator();
+                    ui.add(Label::new("Window:"));
+                    ui.add(Slider::new(&mut self.crash_loop_window, 60..=3600).suffix("s"))
+                        .on_hover_text(SUPERVISOR_CRASH_LOOP_WINDOW);
+                });
+            });
+        });
+    }
+}
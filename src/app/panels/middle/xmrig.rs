@@ -17,6 +17,7 @@
 
 use crate::disk::pool::Pool;
 use crate::disk::state::Xmrig;
+use crate::helper::args::{override_error, override_warning, Binary};
 use crate::helper::xrig::xmrig::PubXmrigApi;
 use crate::helper::Process;
 use crate::regex::{num_lines, REGEXES};
@@ -116,7 +117,15 @@ impl Xmrig {
                     )
                     .on_hover_text(XMRIG_ARGUMENTS);
                     self.arguments.truncate(1024);
-                })
+                });
+                // Bad values are shown before the start, unknown options are only a warning.
+                if let Some(e) = override_error(Binary::Xmrig, self.simple, &self.arguments) {
+                    ui.label(RichText::new(format!("Error: {}", e)).color(RED));
+                } else if let Some(e) =
+                    override_warning(Binary::Xmrig, self.simple, &self.arguments)
+                {
+                    ui.label(RichText::new(format!("Warning: {}", e)).color(ORANGE));
+                }
             });
             ui.set_enabled(self.arguments.is_empty());
             //---------------------------------------------------------------------------------------------------- Address
@@ -482,6 +491,7 @@ impl Xmrig {
                     });
                 });
             });
+            self.supervisor.show(ui, size.x, text_edit);
         }
     }
 }
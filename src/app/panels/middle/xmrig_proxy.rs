@@ -6,15 +6,17 @@ use log::{debug, info};
 
 use crate::disk::pool::Pool;
 use crate::disk::state::XmrigProxy;
+use crate::helper::args::{override_error, override_warning, Binary};
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
 use crate::helper::Process;
 use crate::regex::{num_lines, REGEXES};
 use crate::utils::constants::DARK_GRAY;
 use crate::utils::macros::lock;
 use crate::{
-    GREEN, LIGHT_GRAY, LIST_ADD, LIST_CLEAR, LIST_DELETE, LIST_SAVE, RED, SPACE, XMRIG_API_IP,
-    XMRIG_API_PORT, XMRIG_IP, XMRIG_KEEPALIVE, XMRIG_NAME, XMRIG_PORT, XMRIG_PROXY_ARGUMENTS,
-    XMRIG_PROXY_INPUT, XMRIG_PROXY_REDIRECT, XMRIG_PROXY_URL, XMRIG_RIG, XMRIG_TLS,
+    GREEN, LIGHT_GRAY, LIST_ADD, LIST_CLEAR, LIST_DELETE, LIST_SAVE, ORANGE, RED, SPACE,
+    XMRIG_API_IP, XMRIG_API_PORT, XMRIG_IP, XMRIG_KEEPALIVE, XMRIG_NAME, XMRIG_PORT,
+    XMRIG_PROXY_ARGUMENTS, XMRIG_PROXY_INPUT, XMRIG_PROXY_REDIRECT, XMRIG_PROXY_URL, XMRIG_RIG,
+    XMRIG_TLS,
 };
 
 impl XmrigProxy {
@@ -106,7 +108,15 @@ impl XmrigProxy {
                     )
                     .on_hover_text(XMRIG_PROXY_ARGUMENTS);
                     self.arguments.truncate(1024);
-                })
+                });
+                // Bad values are shown before the start, unknown options are only a warning.
+                if let Some(e) = override_error(Binary::XmrigProxy, self.simple, &self.arguments) {
+                    ui.label(RichText::new(format!("Error: {}", e)).color(RED));
+                } else if let Some(e) =
+                    override_warning(Binary::XmrigProxy, self.simple, &self.arguments)
+                {
+                    ui.label(RichText::new(format!("Warning: {}", e)).color(ORANGE));
+                }
             });
             ui.set_enabled(self.arguments.is_empty());
             ui.add_space(space_h);
@@ -423,6 +433,7 @@ impl XmrigProxy {
                     });
                 });
             });
+            self.supervisor.show(ui, size.x, text_edit);
         }
     }
 }
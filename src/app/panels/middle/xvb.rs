@@ -1,16 +1,25 @@
 use std::sync::{Arc, Mutex};
 
 use egui::TextStyle::{self, Name};
-use egui::{vec2, Image, RichText, TextEdit, Ui, Vec2};
+use egui::{vec2, ComboBox, Image, Label, RichText, ScrollArea, Slider, TextEdit, Ui, Vec2};
+use egui_extras::{Column, TableBuilder};
 use log::debug;
 use readable::num::Float;
 use readable::up::Uptime;
 
+use crate::disk::history::History;
+use crate::helper::xvb::algorithm::XvbSplit;
+use crate::helper::xvb::rounds::{count_rounds, XvbRound};
+use crate::helper::xvb::strategy::XvbMode;
 use crate::helper::xvb::PubXvbApi;
 use crate::regex::num_lines;
 use crate::utils::constants::{
-    GREEN, LIGHT_GRAY, ORANGE, RED, XVB_DONATED_1H_FIELD, XVB_DONATED_24H_FIELD, XVB_FAILURE_FIELD,
-    XVB_HELP, XVB_HERO_SELECT, XVB_ROUND_TYPE_FIELD, XVB_TOKEN_FIELD, XVB_TOKEN_LEN, XVB_URL_RULES,
+    GREEN, LIGHT_GRAY, ORANGE, RED, XVB_DEFAULT_SELECT, XVB_DONATED_1H_FIELD,
+    XVB_DONATED_24H_FIELD, XVB_FAILURE_FIELD, XVB_HELP, XVB_HERO_SELECT, XVB_MANUAL_OUTSIDE_HR,
+    XVB_MANUAL_ROUND, XVB_MANUAL_SPLIT, XVB_PROBABILISTIC_SELECT, XVB_REMOTE_RIGS,
+    XVB_ROUND_DONOR_MEGA_MIN_HR, XVB_ROUND_HISTORY, XVB_ROUND_TYPE_FIELD,
+    XVB_SHARE_CONFIDENCE_SELECT, XVB_STRATUM_SELECT, XVB_STRATUM_SWITCHER, XVB_SWITCHER_BIND,
+    XVB_THREAD_SPLIT, XVB_TIME_ALGO, XVB_TOKEN_FIELD, XVB_TOKEN_LEN, XVB_URL_RULES,
     XVB_WINNER_FIELD,
 };
 use crate::utils::macros::lock;
@@ -23,6 +32,7 @@ use crate::{
 
 impl crate::disk::state::Xvb {
     #[inline(always)] // called once
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         size: Vec2,
@@ -30,6 +40,7 @@ impl crate::disk::state::Xvb {
         _ctx: &egui::Context,
         ui: &mut egui::Ui,
         api: &Arc<Mutex<PubXvbApi>>,
+        history: &Arc<Mutex<History>>,
         private_stats: bool,
     ) {
         let website_height = size.y / 10.0;
@@ -114,10 +125,19 @@ impl crate::disk::state::Xvb {
     ui.style_mut().spacing.icon_width_inner = width / 45.0;
     ui.style_mut().spacing.icon_width = width / 35.0;
     ui.style_mut().spacing.icon_spacing = space_h;
-                       if ui.checkbox(&mut self.hero, "Hero Mode").on_hover_text(XVB_HERO_SELECT).clicked() {
-                // also change hero mode of runtime.
-                lock!(api).stats_priv.runtime_hero_mode = self.hero;
+            for (mode, hover) in [
+                (XvbMode::Default, XVB_DEFAULT_SELECT),
+                (XvbMode::Hero, XVB_HERO_SELECT),
+                (XvbMode::Probabilistic, XVB_PROBABILISTIC_SELECT),
+            ] {
+                if ui.selectable_label(self.mode == mode, mode.to_string()).on_hover_text(hover).clicked() {
+                    self.mode = mode;
+                    // also change mode of runtime.
+                    lock!(api).stats_priv.runtime_mode = mode;
+                }
             }
+            ui.checkbox(&mut self.stratum_hashrate, "Stratum Hashrate")
+                .on_hover_text(XVB_STRATUM_SELECT);
 
 // need to warn the user if no address is set in p2pool tab
         if !Regexes::addr_ok(address) {
@@ -130,6 +150,117 @@ impl crate::disk::state::Xvb {
                 });
         }
         });
+        // advanced settings, manual overrides of the algorithm
+        if !self.simple {
+            debug!("XvB Tab | Rendering [Overrides]");
+            ui.add_space(space_h);
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.manual_outside_hr, "Outside HR")
+                        .on_hover_text(XVB_MANUAL_OUTSIDE_HR);
+                    ui.add_enabled(
+                        self.manual_outside_hr,
+                        Slider::new(&mut self.outside_hr, 0..=XVB_ROUND_DONOR_MEGA_MIN_HR)
+                            .logarithmic(true)
+                            .suffix(" H/s"),
+                    )
+                    .on_hover_text(XVB_MANUAL_OUTSIDE_HR);
+                    ui.separator();
+                    ui.checkbox(&mut self.manual_split, "Split")
+                        .on_hover_text(XVB_MANUAL_SPLIT);
+                    ui.add_enabled_ui(self.manual_split, |ui| {
+                        for unit in [XvbSplit::Percent, XvbSplit::Seconds] {
+                            if ui
+                                .selectable_label(self.split_unit == unit, unit.to_string())
+                                .on_hover_text(XVB_MANUAL_SPLIT)
+                                .clicked()
+                                && self.split_unit != unit
+                            {
+                                // keep the same split when changing unit.
+                                let seconds = self.split_unit.seconds(self.split);
+                                self.split = match unit {
+                                    XvbSplit::Percent => seconds * 100 / XVB_TIME_ALGO,
+                                    XvbSplit::Seconds => seconds,
+                                };
+                                self.split_unit = unit;
+                            }
+                        }
+                        let (max, suffix) = match self.split_unit {
+                            XvbSplit::Percent => (100, "%"),
+                            XvbSplit::Seconds => (XVB_TIME_ALGO, "s"),
+                        };
+                        ui.add(Slider::new(&mut self.split, 0..=max).suffix(suffix))
+                            .on_hover_text(XVB_MANUAL_SPLIT);
+                    });
+                    ui.separator();
+                    ui.checkbox(&mut self.manual_round, "Round cap")
+                        .on_hover_text(XVB_MANUAL_ROUND);
+                    ui.add_enabled_ui(self.manual_round, |ui| {
+                        ComboBox::from_id_source("xvb_round_cap")
+                            .selected_text(self.round_cap.to_string())
+                            .show_ui(ui, |ui| {
+                                for round in [
+                                    XvbRound::Vip,
+                                    XvbRound::Donor,
+                                    XvbRound::DonorVip,
+                                    XvbRound::DonorWhale,
+                                    XvbRound::DonorMega,
+                                ] {
+                                    let text = round.to_string();
+                                    ui.selectable_value(&mut self.round_cap, round, text);
+                                }
+                            })
+                            .response
+                            .on_hover_text(XVB_MANUAL_ROUND);
+                    });
+                    ui.separator();
+                    ui.add_enabled_ui(self.mode == XvbMode::Probabilistic, |ui| {
+                        ui.label("Share confidence");
+                        ui.add(Slider::new(&mut self.share_confidence, 50..=99).suffix("%"))
+                            .on_hover_text(XVB_SHARE_CONFIDENCE_SELECT)
+                            .on_disabled_hover_text(XVB_SHARE_CONFIDENCE_SELECT);
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.stratum_switcher, "Stratum switcher")
+                        .on_hover_text(XVB_STRATUM_SWITCHER);
+                    ui.add_enabled(
+                        self.stratum_switcher,
+                        TextEdit::singleline(&mut self.switcher_bind)
+                            .hint_text(XVB_SWITCHER_BIND)
+                            .desired_width(width / 8.0),
+                    )
+                    .on_hover_text(XVB_STRATUM_SWITCHER);
+                    ui.separator();
+                    ui.checkbox(&mut self.thread_split, "Thread split")
+                        .on_hover_text(XVB_THREAD_SPLIT);
+                    if let Some(switcher) = &lock!(api).switcher {
+                        ui.label(format!(
+                            "Listening on {}, {} miners connected",
+                            switcher.address(),
+                            switcher.miners()
+                        ));
+                    }
+                });
+                // remote rigs are only set in the state file.
+                let rigs = lock!(api)
+                    .rigs
+                    .iter()
+                    .map(|rig| match rig.error {
+                        Some(_) => format!("{} unreachable", rig.config.name),
+                        None => format!(
+                            "{} {} kH/s",
+                            rig.config.name,
+                            Float::from_3(rig.hashrate as f64 / 1000.0)
+                        ),
+                    })
+                    .collect::<Vec<_>>();
+                if !rigs.is_empty() {
+                    ui.label(format!("Remote rigs: {}", rigs.join(", ")))
+                        .on_hover_text(XVB_REMOTE_RIGS);
+                }
+            });
+        }
         // private stats
         ui.add_space(space_h);
         // ui.add_enabled_ui(private_stats, |ui| {
@@ -245,6 +376,87 @@ impl crate::disk::state::Xvb {
                 // currently mining on
             });
         });
+        // round history, with the totals first
+        if !self.simple {
+            debug!("XvB Tab | Rendering [Round history]");
+            ui.add_space(space_h);
+            let history = lock!(history);
+            let rounds = &history.rounds;
+            ui.group(|ui| {
+                let totals = count_rounds(rounds.iter().map(|r| &r.round))
+                    .into_iter()
+                    .map(|(round, count)| {
+                        let name = round.map_or("None".to_string(), |r| r.to_string());
+                        format!("{}: {}", name, count)
+                    })
+                    .collect::<Vec<_>>();
+                let wins = rounds.iter().filter(|r| r.won).count();
+                ui.label(format!(
+                    "{} rounds logged, {} won | {}",
+                    rounds.len(),
+                    wins,
+                    totals.join(", ")
+                ))
+                .on_hover_text(XVB_ROUND_HISTORY);
+                let text = ui.text_style_height(&TextStyle::Body);
+                let column = (width - SPACE * 16.0) / 8.0;
+                ScrollArea::both().max_height(height / 6.0).show(ui, |ui| {
+                    TableBuilder::new(ui)
+                        .columns(Column::auto(), 8)
+                        .header(text * 1.5, |mut header| {
+                            for name in [
+                                "End",
+                                XVB_ROUND_TYPE_FIELD,
+                                XVB_DONATED_1H_FIELD,
+                                XVB_DONATED_24H_FIELD,
+                                "Time donated",
+                                XVB_WINNER_FIELD,
+                                "Winner",
+                                "Rolls",
+                            ] {
+                                header.col(|ui| {
+                                    ui.add_sized([column, text], Label::new(name));
+                                });
+                            }
+                        })
+                        .body(|body| {
+                            // most recent first
+                            body.rows(text, rounds.len(), |mut row| {
+                                let record = &rounds[rounds.len() - 1 - row.index()];
+                                let end = chrono::DateTime::from_timestamp(record.time as i64, 0)
+                                    .map_or("???".to_string(), |t| {
+                                        t.with_timezone(&chrono::Local)
+                                            .format("%Y-%m-%d %H:%M")
+                                            .to_string()
+                                    });
+                                let cells = [
+                                    end,
+                                    record
+                                        .round
+                                        .as_ref()
+                                        .map_or("None".to_string(), |r| r.to_string()),
+                                    format!("{} kH/s", Float::from_3(record.donor_1h as f64)),
+                                    format!("{} kH/s", Float::from_3(record.donor_24h as f64)),
+                                    Uptime::from(record.donated).to_string(),
+                                    if record.won { "Yes" } else { "No" }.to_string(),
+                                    record.winner.clone(),
+                                    format!("{} / {}", record.roll_winner, record.roll_round),
+                                ];
+                                for (i, cell) in cells.into_iter().enumerate() {
+                                    row.col(|ui| {
+                                        let label = if i == 5 && record.won {
+                                            Label::new(RichText::new(cell).color(GREEN))
+                                        } else {
+                                            Label::new(cell)
+                                        };
+                                        ui.add_sized([column, text], label);
+                                    });
+                                }
+                            });
+                        });
+                });
+            });
+        }
         // Rules link help
         ui.horizontal_centered(|ui| {
             // can't have horizontal and vertical centering work together so fix by this.
@@ -1,16 +1,24 @@
+use std::path::PathBuf;
 use std::process::exit;
 
+use anyhow::anyhow;
 use clap::crate_authors;
 use clap::crate_description;
 use clap::crate_name;
 use clap::crate_version;
 use clap::Parser;
 use clap::Subcommand;
+use env_logger::Target;
 use log::debug;
 use log::info;
 use log::warn;
 
 use crate::app::App;
+use crate::components::update::Update;
+use crate::disk::state::State;
+use crate::helper::xvb::simulate::{parse_timeline, report, simulate, synthetic_timeline};
+use crate::helper::xvb::strategy::XvbMode;
+use crate::macros::arc_mut;
 use crate::miscs::print_disk_file;
 use crate::miscs::print_gupax_p2pool_api;
 use crate::resets::reset;
@@ -54,6 +62,90 @@ pub enum GupaxxData {
         name = "no-startup"
     )]
     Nostartup,
+    #[command(about = "Put back the binaries replaced by the last update")]
+    Rollback,
+    #[command(
+        about = "Simulate the decisions of the XvB algorithm on a timeline, without mining",
+        name = "xvb-simulate"
+    )]
+    XvbSimulate {
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Replay this timeline instead of a synthetic one: one JSON object per ten minutes cycle and per line, with hashrate (H/s), difficulty, shares, and optionally donor_1h/donor_24h (kH/s)"
+        )]
+        timeline: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value_t = 10000.0,
+            help = "Local hashrate in H/s of the synthetic timeline"
+        )]
+        hashrate: f32,
+        #[arg(
+            long,
+            default_value_t = 100000000,
+            help = "Sidechain difficulty of the synthetic timeline"
+        )]
+        difficulty: u64,
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Shares in the PPLNS window of the synthetic timeline"
+        )]
+        shares: u32,
+        #[arg(
+            long,
+            default_value_t = 144,
+            help = "Number of ten minutes cycles of the synthetic timeline"
+        )]
+        cycles: usize,
+        #[arg(
+            long,
+            value_enum,
+            help = "Mode of the algorithm, the one of the [XvB] tab if not set"
+        )]
+        mode: Option<XvbMode>,
+        #[arg(
+            long,
+            help = "Also print what the algorithm would write in the console"
+        )]
+        verbose: bool,
+    },
+    #[command(
+        about = "Run Gupaxx without the GUI, starting the processes enabled for auto-start (stop with SIGTERM)"
+    )]
+    Daemon {
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Append the logs to this file instead of STDOUT"
+        )]
+        log_file: Option<PathBuf>,
+    },
+}
+
+impl Cli {
+    // Where the logger should write.
+    // The GUI keeps logging to STDERR, the daemon to STDOUT or the given file.
+    pub fn log_target(&self) -> Target {
+        match &self.info {
+            Some(GupaxxData::Daemon {
+                log_file: Some(path),
+            }) => match std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+            {
+                Ok(file) => Target::Pipe(Box::new(file)),
+                Err(e) => {
+                    eprintln!("Could not open log file [{}]: {}", path.display(), e);
+                    exit(1);
+                }
+            },
+            Some(GupaxxData::Daemon { log_file: None }) => Target::Stdout,
+            _ => Target::Stderr,
+        }
+    }
 }
 // #[cold]
 // #[inline(never)]
@@ -126,7 +218,68 @@ pub fn parse_args<S: Into<String>>(mut app: App, args: Cli, panic: S) -> App {
                 &app.pool_path,
                 &app.gupax_p2pool_api_path,
             ),
+            GupaxxData::Rollback => {
+                let result = State::get(&app.state_path)
+                    .map_err(|e| anyhow!("{}", e))
+                    .and_then(|state| {
+                        Update::rollback(
+                            &arc_mut!(state),
+                            &app.state_path,
+                            &app.exe,
+                            &Update::get_backup_dir()?,
+                        )
+                    });
+                match result {
+                    Ok(version) => {
+                        println!("\nRollback to {} ... OK", version);
+                        exit(0)
+                    }
+                    Err(e) => {
+                        eprintln!("\nRollback ... FAIL: {}", e);
+                        exit(1)
+                    }
+                }
+            }
+            GupaxxData::XvbSimulate {
+                timeline,
+                hashrate,
+                difficulty,
+                shares,
+                cycles,
+                mode,
+                verbose,
+            } => {
+                let state = State::get(&app.state_path).unwrap_or_else(|e| {
+                    warn!(
+                        "Could not read the state, using the default settings: {}",
+                        e
+                    );
+                    State::default()
+                });
+                let timeline = match timeline {
+                    Some(path) => match std::fs::read_to_string(&path)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|s| parse_timeline(&s))
+                    {
+                        Ok(timeline) => timeline,
+                        Err(e) => {
+                            eprintln!("\nTimeline [{}] ... FAIL: {}", path.display(), e);
+                            exit(1)
+                        }
+                    },
+                    None => synthetic_timeline(hashrate, difficulty, shares, cycles),
+                };
+                let simulated = simulate(
+                    &timeline,
+                    mode.unwrap_or(state.xvb.mode),
+                    &state.xvb,
+                    state.p2pool.mini,
+                );
+                print!("{}", report(&simulated, verbose));
+                exit(0)
+            }
             GupaxxData::Nostartup => app.no_startup = true,
+            GupaxxData::Daemon { .. } => app.daemon = true,
         }
     }
     app
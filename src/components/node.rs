@@ -16,11 +16,14 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::components::update::get_user_agent;
+use crate::disk::node::Node;
+use crate::disk::state::P2pool;
 use crate::{constants::*, macros::*};
 use egui::Color32;
 use log::*;
 use rand::{thread_rng, Rng};
 use reqwest::{Client, RequestBuilder};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -286,6 +289,7 @@ pub struct GetInfo<'a> {
 pub struct GetInfoResult {
     pub mainnet: bool,
     pub synchronized: bool,
+    pub height: u64,
 }
 
 //---------------------------------------------------------------------------------------------------- Ping data
@@ -319,6 +323,93 @@ impl Ping {
         }
     }
 
+    // Nodes given to P2Pool as backup hosts: the green/yellow nodes of the last ping
+    // in simple mode, the manual node list in advanced mode.
+    #[cold]
+    #[inline(never)]
+    pub fn backup_hosts(&self, p2pool: &P2pool, node_vec: &[(String, Node)]) -> Option<Vec<Node>> {
+        if !p2pool.backup_host {
+            return None;
+        }
+
+        // INVARIANT:
+        // We must ensure all nodes are capable of
+        // sending/receiving valid JSON-RPC requests.
+        //
+        // This is done during the `Ping` phase, meaning
+        // all the nodes listed in our `self.nodes` should
+        // have ping data. We can use this data to filter
+        // out "dead" nodes.
+        //
+        // The user must have at least pinged once so that
+        // we actually have this data to work off of, else,
+        // this "backup host" feature will return here
+        // with 0 extra nodes as we can't be sure that any
+        // of them are actually online.
+        //
+        // Realistically, most of them are, but we can't be sure,
+        // and checking here without explicitly asking the user
+        // to connect to nodes is a no-go (also, non-async environment).
+        if !self.pinged {
+            warn!("Backup hosts ... simple node backup: no ping data available, returning None");
+            return None;
+        }
+
+        if p2pool.simple {
+            let mut vec = Vec::with_capacity(REMOTE_NODES.len());
+
+            // Locking during this entire loop should be fine,
+            // only a few nodes to iter through.
+            for pinged_node in self.nodes.iter() {
+                // Continue if this node is not green/yellow.
+                if pinged_node.ms > RED_NODE_PING {
+                    continue;
+                }
+
+                let (ip, rpc, zmq) = RemoteNode::get_ip_rpc_zmq(pinged_node.ip);
+
+                let node = Node {
+                    ip: ip.into(),
+                    rpc: rpc.into(),
+                    zmq: zmq.into(),
+                };
+
+                vec.push(node);
+            }
+
+            if vec.is_empty() {
+                warn!("Backup hosts ... simple node backup: no viable nodes found");
+                None
+            } else {
+                info!("Backup hosts ... simple node backup list: {vec:#?}");
+                Some(vec)
+            }
+        } else {
+            Some(node_vec.iter().map(|(_, node)| node.clone()).collect())
+        }
+    }
+
+    // [backup_hosts] for the threads without the node list of the GUI,
+    // the manual node list is read from its saved file.
+    pub fn saved_backup_hosts(
+        ping: &Mutex<Self>,
+        p2pool: &P2pool,
+        node_path: &Path,
+    ) -> Option<Vec<Node>> {
+        let node_vec = if p2pool.simple {
+            vec![]
+        } else {
+            match Node::get(&node_path.to_path_buf()) {
+                Ok(vec) => vec,
+                Err(e) => {
+                    warn!("Backup hosts ... could not read the node list: {}", e);
+                    vec![]
+                }
+            }
+        };
+        lock!(ping).backup_hosts(p2pool, &node_vec)
+    }
+
     //---------------------------------------------------------------------------------------------------- Main Ping function
     #[cold]
     #[inline(never)]
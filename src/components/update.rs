@@ -27,18 +27,24 @@
 use crate::{
     app::Restart,
     constants::GUPAX_VERSION,
-    disk::{state::State, *},
+    disk::{
+        state::{Gupax, State},
+        *,
+    },
+    helper::ProcessName,
     macros::*,
     miscs::get_exe_dir,
     utils::errors::{ErrorButtons, ErrorFerris, ErrorState},
 };
 use anyhow::{anyhow, Error};
 use log::*;
+use pgp::{Deserializable, SignedPublicKey, StandaloneSignature};
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 use reqwest::header::{LOCATION, USER_AGENT};
 use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use walkdir::WalkDir;
@@ -55,7 +61,18 @@ use zip::ZipArchive;
 // Example: https://github.com/hinto-janai/gupax/releases/download/v0.0.1/gupax-v0.0.1-linux-standalone-x64.tar.gz
 //
 
-const GUPAX_METADATA: &str = "https://api.github.com/repos/Cyrix126/gupaxx/releases/latest";
+const GUPAX_METADATA: &str = "https://api.github.com/repos/Cyrix126/gupaxx/releases";
+const GUPAX_RELEASES: &str = "https://github.com/Cyrix126/gupaxx/releases/download/";
+
+// Every release contains the SHA256 of its archives in [SHA256SUMS],
+// signed with the key below in [SHA256SUMS.asc] (armored detached signature).
+const SHA256SUMS: &str = "SHA256SUMS";
+const SHA256SUMS_SIG: &str = "SHA256SUMS.asc";
+const GUPAX_PUBLIC_KEY: &str = include_str!("../../pgp/cyrix126.asc");
+// Directory next to Gupaxx keeping the binaries replaced by the last update.
+const BACKUP_DIR: &str = "gupaxx_backup";
+// File of the backup keeping the versions of P2Pool and XMRig replaced.
+const BACKUP_VERSIONS: &str = "versions.toml";
 
 cfg_if::cfg_if! {
      if #[cfg(target_family = "unix")] {
@@ -123,7 +140,10 @@ const MSG_HTTPS: &str = "Creating HTTPS client";
 const MSG_METADATA: &str = "Fetching package metadata";
 const MSG_COMPARE: &str = "Compare package versions";
 const MSG_UP_TO_DATE: &str = "All packages already up-to-date";
+const MSG_CONFIRM: &str = "Waiting for confirmation to update to";
+const MSG_CANCEL: &str = "Update cancelled";
 const MSG_DOWNLOAD: &str = "Downloading packages";
+const MSG_VERIFY: &str = "Verifying packages";
 const MSG_EXTRACT: &str = "Extracting packages";
 const MSG_UPGRADE: &str = "Upgrading packages";
 pub const MSG_FAILED: &str = "Update failed";
@@ -132,7 +152,9 @@ pub const MSG_FAILED_HELP: &str =
 const INIT: &str = "------------------- Init -------------------";
 const METADATA: &str = "----------------- Metadata -----------------";
 const COMPARE: &str = "----------------- Compare ------------------";
+const CONFIRM: &str = "----------------- Confirm ------------------";
 const DOWNLOAD: &str = "----------------- Download -----------------";
+const VERIFY: &str = "------------------ Verify ------------------";
 const EXTRACT: &str = "----------------- Extract ------------------";
 const UPGRADE: &str = "----------------- Upgrade ------------------";
 
@@ -191,18 +213,44 @@ pub fn check_xp_path(path: &str) -> bool {
 // 30% | Download Metadata (x3)
 // 5%  | Compare Versions (x3)
 // 30% | Download Archive (x3)
+// 0%  | Verify signature and checksum
 // 5%  | Extract (x3)
 // 5%  | Upgrade (x3)
 
 #[derive(Clone)]
 pub struct Update {
-    pub path_gupax: String,         // Full path to current gupax
-    pub path_p2pool: String,        // Full path to current p2pool
-    pub path_xmrig: String,         // Full path to current xmrig
-    pub path_xp: String,            // Full path to current xmrig
-    pub updating: Arc<Mutex<bool>>, // Is an update in progress?
-    pub prog: Arc<Mutex<f32>>,      // Holds the 0-100% progress bar number
-    pub msg: Arc<Mutex<String>>,    // Message to display on [Gupax] tab while updating
+    pub path_gupax: String,                   // Full path to current gupax
+    pub path_p2pool: String,                  // Full path to current p2pool
+    pub path_xmrig: String,                   // Full path to current xmrig
+    pub path_xp: String,                      // Full path to current xmrig
+    pub updating: Arc<Mutex<bool>>,           // Is an update in progress?
+    pub prog: Arc<Mutex<f32>>,                // Holds the 0-100% progress bar number
+    pub msg: Arc<Mutex<String>>,              // Message to display on [Gupax] tab while updating
+    pub release: Arc<Mutex<Option<Release>>>, // Release being applied, its notes are shown on [Gupax] tab
+    pub apply: Arc<Mutex<Option<bool>>>, // Answer of the user to [release], [None] while waiting
+}
+
+// Versions of P2Pool and XMRig before the last update, restored by a rollback.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+struct BackupVersions {
+    p2pool: String,
+    xmrig: String,
+}
+
+// Which releases are followed when no version of Gupaxx is pinned.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub enum UpdateChannel {
+    Stable,
+    PreRelease,
+}
+
+impl std::fmt::Display for UpdateChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Stable => write!(f, "Stable"),
+            Self::PreRelease => write!(f, "Pre-release"),
+        }
+    }
 }
 
 impl Update {
@@ -221,6 +269,8 @@ impl Update {
             updating: arc_mut!(false),
             prog: arc_mut!(0.0),
             msg: arc_mut!(MSG_NONE.to_string()),
+            release: arc_mut!(None),
+            apply: arc_mut!(None),
         }
     }
 
@@ -243,6 +293,12 @@ impl Update {
         Ok(tmp_dir)
     }
 
+    // Directory keeping the binaries replaced by the last update, in a
+    // sub-directory named after the Gupaxx version they belong to.
+    pub fn get_backup_dir() -> Result<PathBuf, anyhow::Error> {
+        Ok(Path::new(&get_exe_dir()?).join(BACKUP_DIR))
+    }
+
     #[cold]
     #[inline(never)]
     // Intermediate function that spawns a new thread
@@ -250,6 +306,8 @@ impl Update {
     // actually contains the code. This is so that everytime
     // an update needs to happen (Gupax tab, auto-update), the
     // code only needs to be edited once, here.
+    // The release notes are shown and the update waits
+    // for the user before downloading, auto-update included.
     pub fn spawn_thread(
         og: &Arc<Mutex<State>>,
         gupax: &crate::disk::state::Gupax,
@@ -358,8 +416,10 @@ impl Update {
     // 1. fill vector with all enums
     // 2. loop over vec, download metadata
     // 3. if current == version, remove from vec
-    // 4. loop over vec, download links
-    // 5. extract, upgrade
+    // 4. wait for the user to confirm after reading the release notes
+    // 5. loop over vec, download links
+    // 6. verify signature and checksum
+    // 7. extract, upgrade
     #[allow(clippy::await_holding_lock)]
     #[tokio::main]
     pub async fn start(
@@ -382,6 +442,7 @@ impl Update {
         // Set progress bar
         *lock2!(update, msg) = MSG_START.to_string();
         *lock2!(update, prog) = 0.0;
+        *lock2!(update, release) = None;
         info!("Update | {}", INIT);
 
         // Get temporary directory
@@ -411,14 +472,15 @@ impl Update {
         // Loop process:
         // reqwest will retry himself
         // Send to async
-        let new_ver = if let Ok(new_ver) =
-            get_metadata(&client, GUPAX_METADATA.to_string(), user_agent).await
-        {
-            new_ver
-        } else {
-            error!("Update | Metadata ... FAIL");
-            return Err(anyhow!("Metadata fetch failed"));
+        let gupax = lock!(og).gupax.clone();
+        let new_release = match get_metadata(&client, GUPAX_METADATA, &gupax, user_agent).await {
+            Ok(release) => release,
+            Err(e) => {
+                error!("Update | Metadata ... FAIL ... {}", e);
+                return Err(anyhow!("Metadata fetch failed"));
+            }
         };
+        let new_ver = new_release.tag_name.clone();
 
         *lock2!(update, prog) += 10.0;
         info!("Update | Gupaxx {} ... OK", new_ver);
@@ -426,6 +488,10 @@ impl Update {
         //---------------------------------------------------------------------------------------------------- Compare
         *lock2!(update, msg) = MSG_COMPARE.to_string();
         info!("Update | {}", COMPARE);
+        // A pinned version is installed even if older than the current one.
+        if !gupax.pinned_gupax.is_empty() {
+            info!("Update | Gupaxx pinned to {}", new_ver);
+        }
         let diff = GUPAX_VERSION != new_ver;
         if diff {
             info!(
@@ -448,6 +514,30 @@ impl Update {
         // Return if 0 (all packages up-to-date)
         // Get amount of packages to divide up the percentage increases
 
+        //---------------------------------------------------------------------------------------------------- Confirm
+        // The release notes are shown on the [Gupax] tab,
+        // the user has to apply or cancel the update.
+        info!("Update | {}", CONFIRM);
+        *lock2!(update, apply) = None;
+        *lock2!(update, release) = Some(new_release);
+        *lock2!(update, msg) = format!("{} {}", MSG_CONFIRM, new_ver);
+        loop {
+            let apply = *lock2!(update, apply);
+            match apply {
+                Some(true) => break,
+                Some(false) => {
+                    info!("Update | Cancelled by the user ... RETURNING");
+                    std::fs::remove_dir_all(&tmp_dir)?;
+                    *lock2!(update, release) = None;
+                    *lock2!(update, prog) = 100.0;
+                    *lock2!(update, msg) = MSG_CANCEL.to_string();
+                    return Ok(());
+                }
+                None => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
+            }
+        }
+        info!("Update | Confirm ... OK");
+
         //---------------------------------------------------------------------------------------------------- Download
         *lock2!(update, msg) = format!("{} Gupaxx", MSG_DOWNLOAD);
         info!("Update | {}", DOWNLOAD);
@@ -468,10 +558,8 @@ impl Update {
         } else {
             "standalone"
         };
-        let link = [
-            "https://github.com/Cyrix126/gupaxx/releases/download/",
-            &version,
-            "/gupaxx-",
+        let archive = [
+            "gupaxx-",
             &version,
             "-",
             OS_TARGET,
@@ -483,6 +571,8 @@ impl Update {
             ARCHIVE_EXT,
         ]
         .concat();
+        let release = [GUPAX_RELEASES, &version, "/"].concat();
+        let link = [&release, archive.as_str()].concat();
         info!("Update | Gupaxx ... {}", link);
         let bytes = if let Ok(bytes) = get_bytes(&client, link, user_agent).await {
             bytes
@@ -494,6 +584,20 @@ impl Update {
         info!("Update | Gupaxx ... OK");
         info!("Update | Download ... OK ... {}%", *lock2!(update, prog));
 
+        //---------------------------------------------------------------------------------------------------- Verify
+        *lock2!(update, msg) = format!("{} Gupaxx", MSG_VERIFY);
+        info!("Update | {}", VERIFY);
+        verify_release(
+            &client,
+            &release,
+            &archive,
+            &bytes,
+            GUPAX_PUBLIC_KEY,
+            user_agent,
+        )
+        .await?;
+        info!("Update | Verify ... OK");
+
         //---------------------------------------------------------------------------------------------------- Extract
         *lock2!(update, msg) = format!("{} Gupaxx", MSG_EXTRACT);
         info!("Update | {}", EXTRACT);
@@ -521,6 +625,20 @@ impl Update {
         // If this bool doesn't get set, something has gone wrong because
         // we _didn't_ find a binary even though we downloaded it.
         let mut found = false;
+        // The current binaries are kept in [gupaxx_backup/GUPAX_VERSION],
+        // only the last version replaced is kept.
+        let backup_root = Self::get_backup_dir()?;
+        if backup_root.exists() {
+            info!("Update | Removing old backup ... {}", backup_root.display());
+            std::fs::remove_dir_all(&backup_root)?;
+        }
+        let backup_dir = backup_root.join(GUPAX_VERSION);
+        std::fs::create_dir_all(&backup_dir)?;
+        let old_versions = BackupVersions {
+            p2pool: lock2!(og, version).p2pool.clone(),
+            xmrig: lock2!(og, version).xmrig.clone(),
+        };
+        let mut untested = vec![];
         for entry in WalkDir::new(tmp_dir.clone()) {
             let entry = entry?.clone();
             // If not a file, continue
@@ -539,7 +657,50 @@ impl Update {
                 _ => continue,
             };
             found = true;
+            let new_version = match name {
+                P2POOL_BINARY | XMRIG_BINARY | XMRIG_PROXY_BINARY => binary_version(entry.path()),
+                _ => None,
+            };
+            // A pinned component is held: only replaced by a bundled version matching
+            // the pin, the pinned version itself is never downloaded.
+            let pin = match name {
+                P2POOL_BINARY => gupax.pinned_p2pool.as_str(),
+                XMRIG_BINARY => gupax.pinned_xmrig.as_str(),
+                XMRIG_PROXY_BINARY => gupax.pinned_xp.as_str(),
+                _ => "",
+            };
+            if !pin.is_empty() {
+                match &new_version {
+                    Some(v) if version_matches(v, pin) => {
+                        info!("Update | [{}] {} matches pinned [{}]", name, v, pin)
+                    }
+                    v => {
+                        info!(
+                            "Update | [{}] {} does not match pinned [{}] ... SKIPPING",
+                            name,
+                            v.as_deref().unwrap_or("unknown version"),
+                            pin
+                        );
+                        continue;
+                    }
+                }
+            }
             let path = Path::new(&path);
+            if path.exists() {
+                let backup = backup_dir.join(name);
+                info!(
+                    "Update | Backup old [{}] -> [{}]",
+                    path.display(),
+                    backup.display()
+                );
+                std::fs::copy(path, backup)?;
+            }
+            match name {
+                P2POOL_BINARY => untested.push(ProcessName::P2pool.to_string()),
+                XMRIG_BINARY => untested.push(ProcessName::Xmrig.to_string()),
+                XMRIG_PROXY_BINARY => untested.push(ProcessName::XmrigProxy.to_string()),
+                _ => (),
+            }
             // Unix can replace running binaries no problem (they're loaded into memory)
             // Windows locks binaries in place, so we must move (rename) current binary
             // into the temp folder, then move the new binary into the old ones spot.
@@ -577,6 +738,13 @@ impl Update {
             }
             // Move downloaded path into old path
             std::fs::rename(entry.path(), path)?;
+            if let Some(v) = new_version {
+                match name {
+                    P2POOL_BINARY => lock2!(og, version).p2pool = format!("v{}", v),
+                    XMRIG_BINARY => lock2!(og, version).xmrig = format!("v{}", v),
+                    _ => (),
+                }
+            }
             // If we're updating Gupax, set the [Restart] state so that the user knows to restart
             *lock!(restart) = Restart::Yes;
             *lock2!(update, prog) += 5.0;
@@ -584,6 +752,13 @@ impl Update {
         if !found {
             return Err(anyhow!("Fatal error: Package binary could not be found"));
         }
+        std::fs::write(
+            backup_dir.join(BACKUP_VERSIONS),
+            toml::to_string(&old_versions)?,
+        )?;
+        // Saved with the new versions by [spawn_thread].
+        lock2!(og, version).backup = GUPAX_VERSION.to_string();
+        lock2!(og, version).untested = untested;
 
         // Remove tmp dir (on Unix)
         #[cfg(target_family = "unix")]
@@ -600,6 +775,83 @@ impl Update {
         *lock2!(update, prog) = 100.0;
         Ok(())
     }
+
+    #[cold]
+    #[inline(never)]
+    // Put the binaries kept by the last update back over the current ones,
+    // then remove the backup and save the state.
+    // Returns the version of Gupaxx rolled back to.
+    pub fn rollback(
+        og: &Arc<Mutex<State>>,
+        state_path: &Path,
+        path_gupax: &str,
+        backup_root: &Path,
+    ) -> Result<String, anyhow::Error> {
+        let version = lock2!(og, version).backup.clone();
+        if version.is_empty() {
+            return Err(anyhow!("No backup to roll back to"));
+        }
+        let backup_dir = backup_root.join(&version);
+        if !backup_dir.is_dir() {
+            return Err(anyhow!(
+                "Backup directory [{}] not found",
+                backup_dir.display()
+            ));
+        }
+        info!("Update | Rolling back to [{}]", version);
+        // Backups made before the versions were recorded keep the current ones.
+        let old_versions = match std::fs::read_to_string(backup_dir.join(BACKUP_VERSIONS)) {
+            Ok(string) => Some(toml::from_str::<BackupVersions>(&string)?),
+            Err(_) => None,
+        };
+        let gupax = lock!(og).gupax.clone();
+        let absolute =
+            |path: &str| into_absolute_path(path.to_string()).map_err(|e| anyhow!("{}", e));
+        for entry in std::fs::read_dir(&backup_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name
+                .to_str()
+                .ok_or_else(|| anyhow!("Backup basename failed"))?;
+            let path = match name {
+                GUPAX_BINARY => PathBuf::from(path_gupax),
+                P2POOL_BINARY => absolute(&gupax.p2pool_path)?,
+                XMRIG_BINARY => absolute(&gupax.xmrig_path)?,
+                XMRIG_PROXY_BINARY => absolute(&gupax.xmrig_proxy_path)?,
+                _ => continue,
+            };
+            // Copied next to the current binary, then renamed over it:
+            // a running binary can be replaced but not written into.
+            let tmp = path.with_file_name(format!("{}.rollback", name));
+            std::fs::copy(entry.path(), &tmp)?;
+            // Windows locks running binaries, move it into a temporary
+            // folder like [start()], it gets cleared at startup.
+            #[cfg(target_os = "windows")]
+            if path.exists() {
+                let tmp_dir = Self::get_tmp_dir()?;
+                std::fs::create_dir(&tmp_dir)?;
+                std::fs::rename(&path, tmp_dir + name)?;
+            }
+            info!(
+                "Update | Restoring [{}] -> [{}]",
+                entry.path().display(),
+                path.display()
+            );
+            std::fs::rename(&tmp, &path)?;
+        }
+        std::fs::remove_dir_all(backup_root)?;
+
+        if let Some(old_versions) = old_versions {
+            lock2!(og, version).p2pool = old_versions.p2pool;
+            lock2!(og, version).xmrig = old_versions.xmrig;
+        }
+        lock2!(og, version).gupax.clone_from(&version);
+        lock2!(og, version).backup = String::new();
+        lock2!(og, version).untested.clear();
+        State::save(&mut lock!(og), &state_path.to_path_buf()).map_err(|e| anyhow!("{}", e))?;
+        info!("Update | Rollback to [{}] ... OK", version);
+        Ok(version)
+    }
 }
 
 //---------------------------------------------------------------------------------------------------- Pkg functions
@@ -626,19 +878,77 @@ fn get_request(
     Ok(client.get(link).header(USER_AGENT, user_agent))
 }
 
+// Where to find the release to update to in the GitHub API at [base]:
+// the pinned version if any, else the latest release of the channel.
+pub fn metadata_link(base: &str, gupax: &Gupax) -> String {
+    let pin = gupax.pinned_gupax.trim();
+    if !pin.is_empty() {
+        let tag = if pin.starts_with('v') {
+            pin.to_string()
+        } else {
+            format!("v{}", pin)
+        };
+        return format!("{}/tags/{}", base, tag);
+    }
+    match gupax.update_channel {
+        UpdateChannel::Stable => format!("{}/latest", base),
+        // Only the list includes pre-releases.
+        UpdateChannel::PreRelease => base.to_string(),
+    }
+}
+
 #[cold]
 #[inline(never)]
 // Get metadata using [Generic hyper::client<C>] & [Request]
 // and change [version, prog] under an Arc<Mutex>
 async fn get_metadata(
     client: &Client,
-    link: String,
+    base: &str,
+    gupax: &Gupax,
     user_agent: &'static str,
-) -> Result<String, Error> {
+) -> Result<Release, Error> {
+    let link = metadata_link(base, gupax);
     let request = get_request(client, link, user_agent)?;
-    let response = request.send().await?;
-    let body = response.json::<TagName>().await?;
-    Ok(body.tag_name)
+    let response = request.send().await?.error_for_status()?;
+    if gupax.pinned_gupax.trim().is_empty() && gupax.update_channel == UpdateChannel::PreRelease {
+        // Newest first, stable and pre-releases mixed.
+        response
+            .json::<Vec<Release>>()
+            .await?
+            .into_iter()
+            .find(|r| !r.draft)
+            .ok_or_else(|| anyhow!("No release found"))
+    } else {
+        Ok(response.json::<Release>().await?)
+    }
+}
+
+// Version printed by [binary --version], to compare with a pinned version.
+fn binary_version(path: &Path) -> Option<String> {
+    let output = std::process::Command::new(path)
+        .arg("--version")
+        .output()
+        .ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+// First word looking like a version, without the leading [v].
+// [P2Pool v4.1 (built with...] -> [4.1], [XMRig 6.21.0] -> [6.21.0]
+pub fn parse_version(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .map(|w| w.trim_start_matches('v'))
+        .find(|w| w.starts_with(|c: char| c.is_ascii_digit()) && w.contains('.'))
+        .map(|w| {
+            w.trim_end_matches(|c: char| !c.is_ascii_digit())
+                .to_string()
+        })
+}
+
+// A pin matches its exact version, or any version under it: [6.21] matches [6.21.0].
+pub fn version_matches(version: &str, pin: &str) -> bool {
+    let pin = pin.trim().trim_start_matches('v');
+    version == pin || version.starts_with(&format!("{}.", pin))
 }
 
 #[cold]
@@ -667,12 +977,345 @@ async fn get_bytes(
         .send()
         .await?;
     }
-    let body = response.bytes().await?;
+    let body = response.error_for_status()?.bytes().await?;
     Ok(body)
 }
 
-// This inherits the value of [tag_name] from GitHub's JSON API
-#[derive(Debug, Serialize, Deserialize)]
-struct TagName {
-    tag_name: String,
+#[cold]
+#[inline(never)]
+// Download [SHA256SUMS] and its signature from [release], then [verify_checksum()].
+// Nothing can be installed if this fails.
+pub async fn verify_release(
+    client: &Client,
+    release: &str,
+    archive: &str,
+    bytes: &[u8],
+    key: &str,
+    user_agent: &'static str,
+) -> Result<(), anyhow::Error> {
+    let sums = get_bytes(client, [release, SHA256SUMS].concat(), user_agent)
+        .await
+        .map_err(|e| anyhow!("Could not download {}: {}", SHA256SUMS, e))?;
+    let sig = get_bytes(client, [release, SHA256SUMS_SIG].concat(), user_agent)
+        .await
+        .map_err(|e| anyhow!("Could not download {}: {}", SHA256SUMS_SIG, e))?;
+    verify_checksum(archive, bytes, &sums, &sig, key)
+}
+
+// Check that [sums] is signed by [key] (or one of its subkeys)
+// and that it contains the SHA256 of [bytes] for [archive].
+pub fn verify_checksum(
+    archive: &str,
+    bytes: &[u8],
+    sums: &[u8],
+    sig: &[u8],
+    key: &str,
+) -> Result<(), anyhow::Error> {
+    let (key, _) =
+        SignedPublicKey::from_string(key).map_err(|e| anyhow!("Invalid public key: {}", e))?;
+    let (sig, _) = StandaloneSignature::from_armor_single(sig)
+        .map_err(|e| anyhow!("Invalid signature of {}: {}", SHA256SUMS, e))?;
+    if sig.verify(&key, sums).is_err()
+        && !key
+            .public_subkeys
+            .iter()
+            .any(|sub| sig.verify(sub, sums).is_ok())
+    {
+        error!("Update | Signature of {} ... FAIL", SHA256SUMS);
+        return Err(anyhow!(
+            "Signature of {} does not match the Gupaxx key",
+            SHA256SUMS
+        ));
+    }
+    info!("Update | Signature of {} ... OK", SHA256SUMS);
+
+    // Format: [<sha256>  <file>], or [<sha256> *<file>] in binary mode.
+    let expected = std::str::from_utf8(sums)?
+        .lines()
+        .find_map(|line| {
+            let (hash, file) = line.split_once(char::is_whitespace)?;
+            (file.trim_start().trim_start_matches('*') == archive).then(|| hash.to_lowercase())
+        })
+        .ok_or_else(|| anyhow!("[{}] is not listed in {}", archive, SHA256SUMS))?;
+    let found = format!("{:x}", Sha256::digest(bytes));
+    if found != expected {
+        error!(
+            "Update | Checksum of [{}] ... FAIL ... expected: [{}], found: [{}]",
+            archive, expected, found
+        );
+        return Err(anyhow!(
+            "Checksum of [{}] does not match {}",
+            archive,
+            SHA256SUMS
+        ));
+    }
+    info!("Update | Checksum of [{}] ... OK", archive);
+    Ok(())
+}
+
+// This inherits the release values from GitHub's JSON API
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub body: Option<String>, // Release notes, in markdown
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+//---------------------------------------------------------------------------------------------------- TESTS
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::{extract::Path as UrlPath, http::StatusCode, routing::get, Router};
+    use pgp::crypto::hash::HashAlgorithm;
+    use pgp::packet::{SignatureConfig, SignatureType, SignatureVersion, Subpacket, SubpacketData};
+    use pgp::types::{KeyTrait, SecretKeyTrait};
+    use pgp::{ArmorOptions, KeyType, SecretKeyParamsBuilder, SignedSecretKey};
+    use std::collections::HashMap;
+
+    const ARCHIVE: &str = "gupaxx-v9.9.9-linux-x64-standalone.tar.gz";
+
+    // A signing key only known by the test.
+    fn test_key() -> SignedSecretKey {
+        SecretKeyParamsBuilder::default()
+            .key_type(KeyType::EdDSA)
+            .can_sign(true)
+            .primary_user_id("Test <test@gupaxx.test>".into())
+            .build()
+            .unwrap()
+            .generate()
+            .unwrap()
+            .sign(String::new)
+            .unwrap()
+    }
+
+    fn detached_signature(key: &SignedSecretKey, data: &[u8]) -> Vec<u8> {
+        let config = SignatureConfig::new_v4(
+            SignatureVersion::V4,
+            SignatureType::Binary,
+            key.algorithm(),
+            HashAlgorithm::SHA2_256,
+            vec![
+                Subpacket::regular(SubpacketData::SignatureCreationTime(chrono::Utc::now())),
+                Subpacket::regular(SubpacketData::Issuer(key.key_id())),
+            ],
+            vec![],
+        );
+        let signature = config.sign(key, String::new, data).unwrap();
+        StandaloneSignature::new(signature)
+            .to_armored_bytes(ArmorOptions::default())
+            .unwrap()
+    }
+
+    // Serves [files] under [/v9.9.9/], like a GitHub release.
+    async fn fake_release(files: HashMap<&'static str, Vec<u8>>) -> String {
+        let router = Router::new().route(
+            "/v9.9.9/:file",
+            get(move |UrlPath(file): UrlPath<String>| async move {
+                match files.get(file.as_str()) {
+                    Some(bytes) => Ok(bytes.clone()),
+                    None => Err(StatusCode::NOT_FOUND),
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}/v9.9.9/", addr)
+    }
+
+    #[tokio::test]
+    async fn verify_release_signature_and_checksum() {
+        let key = test_key();
+        let public = key.public_key().sign(&key, String::new).unwrap();
+        let public = public.to_armored_string(ArmorOptions::default()).unwrap();
+        let archive = b"gupaxx release archive".to_vec();
+        let sums = format!(
+            "{:x}  other-archive.zip\n{:x}  {}\n",
+            Sha256::digest(b"other"),
+            Sha256::digest(&archive),
+            ARCHIVE
+        )
+        .into_bytes();
+        let sig = detached_signature(&key, &sums);
+        let release = fake_release(HashMap::from([
+            (SHA256SUMS, sums.clone()),
+            (SHA256SUMS_SIG, sig.clone()),
+        ]))
+        .await;
+        let client = Client::new();
+        let ua = get_user_agent();
+
+        // Valid.
+        verify_release(&client, &release, ARCHIVE, &archive, &public, ua)
+            .await
+            .unwrap();
+        // Tampered archive.
+        let e = verify_release(&client, &release, ARCHIVE, b"evil", &public, ua)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().starts_with("Checksum of"), "{e}");
+        // Not in the list.
+        let e = verify_release(
+            &client,
+            &release,
+            "gupaxx-v9.9.9-x.zip",
+            &archive,
+            &public,
+            ua,
+        )
+        .await
+        .unwrap_err();
+        assert!(e.to_string().contains("is not listed in"), "{e}");
+        // Signed by someone else than the embedded key.
+        let e = verify_release(&client, &release, ARCHIVE, &archive, GUPAX_PUBLIC_KEY, ua)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().starts_with("Signature of"), "{e}");
+
+        // Tampered [SHA256SUMS], the signature does not match anymore.
+        let mut evil_sums = sums.clone();
+        evil_sums[0] ^= 1;
+        let e = verify_checksum(ARCHIVE, &archive, &evil_sums, &sig, &public).unwrap_err();
+        assert!(e.to_string().starts_with("Signature of"), "{e}");
+
+        // No signature in the release.
+        let release = fake_release(HashMap::from([(SHA256SUMS, sums)])).await;
+        let e = verify_release(&client, &release, ARCHIVE, &archive, &public, ua)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().starts_with("Could not download"), "{e}");
+    }
+
+    #[test]
+    fn embedded_key_is_valid() {
+        SignedPublicKey::from_string(GUPAX_PUBLIC_KEY).unwrap();
+    }
+
+    #[tokio::test]
+    async fn metadata_follows_channel_and_pin() {
+        // Fake GitHub API: a draft, a pre-release and a stable release.
+        fn release(tag: &str, prerelease: bool, draft: bool) -> Release {
+            Release {
+                tag_name: tag.to_string(),
+                body: Some(format!("Notes of {}", tag)),
+                prerelease,
+                draft,
+            }
+        }
+        let list = vec![
+            release("v2.0.0", false, true),
+            release("v1.3.0-rc1", true, false),
+            release("v1.2.0", false, false),
+        ];
+        let router = Router::new()
+            .route("/releases", get(move || async move { axum::Json(list) }))
+            .route(
+                "/releases/latest",
+                get(|| async { axum::Json(release("v1.2.0", false, false)) }),
+            )
+            .route(
+                "/releases/tags/:tag",
+                get(move |UrlPath(tag): UrlPath<String>| async move {
+                    axum::Json(release(&tag, false, false))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/releases", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        let client = Client::new();
+        let ua = get_user_agent();
+
+        let mut gupax = Gupax::default();
+        assert_eq!(metadata_link(&base, &gupax), format!("{}/latest", base));
+        let r = get_metadata(&client, &base, &gupax, ua).await.unwrap();
+        assert_eq!(r.tag_name, "v1.2.0");
+        assert_eq!(r.body.unwrap(), "Notes of v1.2.0");
+
+        // The draft is skipped.
+        gupax.update_channel = UpdateChannel::PreRelease;
+        assert_eq!(metadata_link(&base, &gupax), base);
+        let r = get_metadata(&client, &base, &gupax, ua).await.unwrap();
+        assert_eq!(r.tag_name, "v1.3.0-rc1");
+        assert!(r.prerelease);
+
+        // The pin wins over the channel, with or without [v].
+        gupax.pinned_gupax = "1.1.0".to_string();
+        assert_eq!(
+            metadata_link(&base, &gupax),
+            format!("{}/tags/v1.1.0", base)
+        );
+        let r = get_metadata(&client, &base, &gupax, ua).await.unwrap();
+        assert_eq!(r.tag_name, "v1.1.0");
+    }
+
+    #[test]
+    fn pinned_versions() {
+        let p2pool = "P2Pool v4.1 (built with GCC 13.2.0 on Aug 20 2024)";
+        let xmrig = "XMRig 6.21.0\n built on Nov 12 2023 with GCC 13.2.0";
+        assert_eq!(parse_version(p2pool).unwrap(), "4.1");
+        assert_eq!(parse_version(xmrig).unwrap(), "6.21.0");
+        assert_eq!(parse_version("no version here"), None);
+        assert!(version_matches("6.21.0", "6.21.0"));
+        assert!(version_matches("6.21.0", "v6.21"));
+        assert!(version_matches("4.1", " v4.1 "));
+        assert!(!version_matches("6.21.0", "6.2"));
+        assert!(!version_matches("6.22.0", "6.21"));
+    }
+
+    #[test]
+    fn rollback_restores_backup() {
+        // Not the real Gupaxx directory, to not touch the user's binaries.
+        let dir = std::env::temp_dir().join("gupaxx_test_rollback");
+        let _ = std::fs::remove_dir_all(&dir);
+        let backup_root = dir.join(BACKUP_DIR);
+        let backup = backup_root.join("v1.0.0");
+        std::fs::create_dir_all(&backup).unwrap();
+        let gupax = dir.join(GUPAX_BINARY);
+        let p2pool = dir.join(P2POOL_BINARY);
+        for path in [&gupax, &p2pool] {
+            std::fs::write(path, "new").unwrap();
+            std::fs::write(backup.join(path.file_name().unwrap()), "old").unwrap();
+        }
+        let old_versions = BackupVersions {
+            p2pool: "v3.10".to_string(),
+            xmrig: "v6.21.1".to_string(),
+        };
+        std::fs::write(
+            backup.join(BACKUP_VERSIONS),
+            toml::to_string(&old_versions).unwrap(),
+        )
+        .unwrap();
+        let state_path = dir.join("state.toml");
+        let mut state = State::new();
+        state.gupax.p2pool_path = p2pool.display().to_string();
+        lock!(state.version).p2pool = "v4.1".to_string();
+        lock!(state.version).xmrig = "v6.22.0".to_string();
+        let og = arc_mut!(state);
+
+        // Nothing recorded in the state yet.
+        let e = Update::rollback(&og, &state_path, &gupax.display().to_string(), &backup_root)
+            .unwrap_err();
+        assert_eq!(e.to_string(), "No backup to roll back to");
+
+        lock2!(og, version).backup = "v1.0.0".to_string();
+        lock2!(og, version).untested = vec![ProcessName::P2pool.to_string()];
+        let version =
+            Update::rollback(&og, &state_path, &gupax.display().to_string(), &backup_root).unwrap();
+        assert_eq!(version, "v1.0.0");
+        assert_eq!(std::fs::read_to_string(&gupax).unwrap(), "old");
+        assert_eq!(std::fs::read_to_string(&p2pool).unwrap(), "old");
+        assert!(!backup_root.exists());
+        let saved = State::get(&state_path).unwrap();
+        let saved = lock!(saved.version);
+        assert_eq!(saved.gupax, "v1.0.0");
+        assert_eq!(saved.p2pool, old_versions.p2pool);
+        assert_eq!(saved.xmrig, old_versions.xmrig);
+        assert!(saved.backup.is_empty());
+        assert!(saved.untested.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
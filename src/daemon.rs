@@ -0,0 +1,152 @@
+// Headless mode.
+// [gupaxx daemon] runs the same [App] init and [Helper] as the GUI,
+// but instead of launching [eframe] it just keeps the helper loop alive,
+// forwards the console of each process to the logger and waits for a
+// termination signal to stop the children cleanly.
+
+use crate::app::App;
+use crate::errors::ErrorButtons;
+use crate::helper::{Helper, ProcessName};
+use crate::inits::init_auto;
+use crate::macros::lock;
+use crate::miscs::clean_dir;
+use log::{error, info, warn};
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+// How long to wait for the children to exit after a stop signal.
+const DAEMON_STOP_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[cold]
+#[inline(never)]
+pub fn run(mut app: App) -> ! {
+    info!("Daemon | Starting Gupaxx without GUI...");
+    if app.error_state.error {
+        // Not being admin on Windows is only a warning, everything else would
+        // have stopped the GUI on the error screen, so abort.
+        if app.error_state.buttons == ErrorButtons::WindowsAdmin {
+            warn!("Daemon | {}", app.error_state.msg);
+            app.error_state.reset();
+        } else {
+            error!("Daemon | {}", app.error_state.msg.trim());
+            exit(1);
+        }
+    }
+    init_auto(&mut app);
+
+    // Gupax folder cleanup.
+    match clean_dir() {
+        Ok(_) => info!("Temporary folder cleanup ... OK"),
+        Err(e) => warn!("Could not cleanup [gupax_tmp] folders: {}", e),
+    }
+
+    info!("/*************************************/ Daemon Init ... OK /*************************************/");
+    wait_for_signal(&app);
+
+    info!("Daemon | Termination signal received, stopping processes...");
+    stop_all(&app);
+    if app.state.gupax.save_before_quit {
+        app.save_before_quit();
+    }
+    info!("Daemon | Exit ... OK");
+    exit(0);
+}
+
+#[tokio::main]
+// Forward the processes console every second until SIGTERM/SIGINT (or Ctrl-C on Windows).
+async fn wait_for_signal(app: &App) {
+    let mut consoles = Consoles::default();
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    #[cfg(target_family = "unix")]
+    let mut sigterm = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+    {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Daemon | Could not listen for SIGTERM: {}", e);
+            exit(1);
+        }
+    };
+    loop {
+        #[cfg(target_family = "unix")]
+        let term = sigterm.recv();
+        #[cfg(target_family = "windows")]
+        let term = std::future::pending::<Option<()>>();
+        tokio::select! {
+            _ = interval.tick() => consoles.forward(app),
+            _ = tokio::signal::ctrl_c() => break,
+            _ = term => break,
+        }
+    }
+    // Don't lose what was printed just before the signal.
+    consoles.forward(app);
+}
+
+// Sets the stop signals and waits for the watchdogs to pick them up.
+fn stop_all(app: &App) {
+    if lock!(app.xvb).is_alive() {
+        Helper::stop_xvb(&app.helper);
+    }
+    if lock!(app.xmrig_proxy).is_alive() {
+        Helper::stop_xp(&app.helper);
+    }
+    if lock!(app.xmrig).is_alive() {
+        Helper::stop_xmrig(&app.helper);
+    }
+    if lock!(app.p2pool).is_alive() {
+        Helper::stop_p2pool(&app.helper);
+    }
+    let processes = [&app.xvb, &app.xmrig_proxy, &app.xmrig, &app.p2pool];
+    let now = Instant::now();
+    while processes.iter().any(|p| lock!(p).is_alive()) {
+        if now.elapsed() > DAEMON_STOP_TIMEOUT {
+            warn!(
+                "Daemon | Processes did not stop after {:?}, exiting anyway",
+                DAEMON_STOP_TIMEOUT
+            );
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    info!("Daemon | All processes stopped ... OK");
+}
+
+// How much of each console output has already been sent to the logger.
+#[derive(Default)]
+struct Consoles {
+    p2pool: usize,
+    xmrig: usize,
+    xmrig_proxy: usize,
+    xvb: usize,
+}
+
+impl Consoles {
+    fn forward(&mut self, app: &App) {
+        Self::forward_one(
+            &lock!(app.p2pool_api).output,
+            &mut self.p2pool,
+            ProcessName::P2pool,
+        );
+        Self::forward_one(
+            &lock!(app.xmrig_api).output,
+            &mut self.xmrig,
+            ProcessName::Xmrig,
+        );
+        Self::forward_one(
+            &lock!(app.xmrig_proxy_api).output,
+            &mut self.xmrig_proxy,
+            ProcessName::XmrigProxy,
+        );
+        Self::forward_one(&lock!(app.xvb_api).output, &mut self.xvb, ProcessName::Xvb);
+    }
+
+    fn forward_one(output: &str, sent: &mut usize, name: ProcessName) {
+        // The helper resets the output when it gets too big or when the process restarts.
+        if output.len() < *sent || !output.is_char_boundary(*sent) {
+            *sent = 0;
+        }
+        for line in output[*sent..].lines().filter(|l| !l.trim().is_empty()) {
+            info!("{} | {}", name, line);
+        }
+        *sent = output.len();
+    }
+}
@@ -34,6 +34,30 @@ pub const GUPAX_P2POOL_API_FILE_ARRAY: [&str; 3] = [
     GUPAX_P2POOL_API_XMR,
 ];
 
+// History
+// Lives within the Gupax OS data directory.
+// ~/.local/share/gupaxx/history/
+// ├─ samples // One JSON [Sample] per line: hashrates and P2Pool stats
+// ├─ xvb     // One JSON [XvbDecision] per line: every run of the XvB algorithm
+// ├─ rounds  // One JSON [XvbRoundRecord] per line: every XvB raffle round seen
+#[cfg(target_os = "windows")]
+pub const HISTORY_DIRECTORY: &str = r"history\";
+#[cfg(target_family = "unix")]
+pub const HISTORY_DIRECTORY: &str = "history/";
+pub const HISTORY_SAMPLES: &str = "samples";
+pub const HISTORY_XVB: &str = "xvb";
+pub const HISTORY_XVB_ROUNDS: &str = "rounds";
+pub const HISTORY_FILE_ARRAY: [&str; 3] = [HISTORY_SAMPLES, HISTORY_XVB, HISTORY_XVB_ROUNDS];
+// Seconds between two samples.
+pub const HISTORY_SAMPLE_INTERVAL: u64 = 60;
+// Samples older than this are averaged into one per [HISTORY_DOWNSAMPLE_INTERVAL].
+pub const HISTORY_RAW_RETENTION: u64 = 2 * 86400;
+pub const HISTORY_DOWNSAMPLE_INTERVAL: u64 = 3600;
+// Everything older than this is deleted.
+pub const HISTORY_RETENTION: u64 = 30 * 86400;
+// Seconds between two passes of downsampling/deletion.
+pub const HISTORY_COMPACT_INTERVAL: u64 = 3600;
+
 #[cfg(target_os = "windows")]
 pub const DEFAULT_P2POOL_PATH: &str = r"P2Pool\p2pool.exe";
 #[cfg(target_os = "macos")]
@@ -0,0 +1,275 @@
+use super::*;
+use crate::helper::p2pool::PubP2poolApi;
+use crate::helper::xrig::{xmrig::PubXmrigApi, xmrig_proxy::PubXmrigProxyApi};
+use crate::helper::xvb::rounds::XvbRound;
+use std::collections::BTreeMap;
+//---------------------------------------------------------------------------------------------------- History
+// Persistent time-series of the stats, so they survive a restart.
+// The [Helper] appends a [Sample] every [HISTORY_SAMPLE_INTERVAL] while something is
+// running, the XvB algorithm appends a [XvbDecision] every time it runs
+// and the XvB watchdog appends a [XvbRoundRecord] at the end of every raffle round.
+// All are kept in memory as well, for the [Status] and [XvB] tabs.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    pub samples: Vec<Sample>,        // Ordered by time
+    pub xvb: Vec<XvbDecision>,       // Ordered by time
+    pub rounds: Vec<XvbRoundRecord>, // Ordered by time
+    pub path_samples: PathBuf,       // Path to [samples]
+    pub path_xvb: PathBuf,           // Path to [xvb]
+    pub path_rounds: PathBuf,        // Path to [rounds]
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Sample {
+    pub time: u64, // UNIX timestamp in seconds
+    pub xmrig_hashrate: f32,
+    pub xp_hashrate: f32,
+    pub p2pool_hashrate_15m: u64,
+    pub p2pool_hashrate_1h: u64,
+    pub p2pool_hashrate_24h: u64,
+    pub shares_found: Option<u64>,
+    pub current_effort: f32,
+    pub connections: u32,
+    pub sidechain_shares: u32,
+    pub sidechain_ehr: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct XvbDecision {
+    pub time: u64,             // UNIX timestamp in seconds
+    pub donated: u32,          // Seconds given to XvB for this run
+    pub node: String,          // Where the donated time is mined
+    pub round: Option<String>, // Round type at the time of the decision
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct XvbRoundRecord {
+    pub time: u64,               // UNIX timestamp in seconds of the draw ending the round
+    pub round: Option<XvbRound>, // Round qualified for, [None] without share
+    pub donor_1h: f32,           // Average donated the last hour in kH/s, at the end of the round
+    pub donor_24h: f32, // Average donated the last 24 hours in kH/s, at the end of the round
+    pub donated: u32,   // Seconds given to XvB by the algorithm during the round
+    pub won: bool,
+    pub winner: String, // Winner of the draw, as shortened by XvB
+    pub roll_winner: u64,
+    pub roll_round: u64,
+}
+
+impl Sample {
+    pub fn new(
+        time: u64,
+        xmrig: &PubXmrigApi,
+        xp: &PubXmrigProxyApi,
+        p2pool: &PubP2poolApi,
+    ) -> Self {
+        Self {
+            time,
+            xmrig_hashrate: xmrig.hashrate_raw_1m,
+            xp_hashrate: xp.hashrate_1m,
+            p2pool_hashrate_15m: p2pool.hashrate_15m_u64,
+            p2pool_hashrate_1h: p2pool.user_p2pool_hashrate_u64,
+            p2pool_hashrate_24h: p2pool.hashrate_24h_u64,
+            shares_found: p2pool.shares_found,
+            current_effort: p2pool.current_effort_f32,
+            connections: p2pool.connections_u32,
+            sidechain_shares: p2pool.sidechain_shares,
+            sidechain_ehr: p2pool.sidechain_ehr,
+        }
+    }
+
+    // Mean of every field, [shares_found] is a counter so the last one is kept.
+    fn average(time: u64, samples: &[Sample]) -> Self {
+        let len = samples.len() as f64;
+        let mean = |f: fn(&Sample) -> f64| samples.iter().map(f).sum::<f64>() / len;
+        Self {
+            time,
+            xmrig_hashrate: mean(|s| s.xmrig_hashrate.into()) as f32,
+            xp_hashrate: mean(|s| s.xp_hashrate.into()) as f32,
+            p2pool_hashrate_15m: mean(|s| s.p2pool_hashrate_15m as f64).round() as u64,
+            p2pool_hashrate_1h: mean(|s| s.p2pool_hashrate_1h as f64).round() as u64,
+            p2pool_hashrate_24h: mean(|s| s.p2pool_hashrate_24h as f64).round() as u64,
+            shares_found: samples.iter().rev().find_map(|s| s.shares_found),
+            current_effort: mean(|s| s.current_effort.into()) as f32,
+            connections: mean(|s| s.connections.into()).round() as u32,
+            sidechain_shares: mean(|s| s.sidechain_shares.into()).round() as u32,
+            sidechain_ehr: mean(|s| s.sidechain_ehr.into()) as f32,
+        }
+    }
+}
+
+impl History {
+    //---------------------------------------------------------------------------------------------------- Init
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fill_paths(&mut self, history_dir: &Path) {
+        self.path_samples = history_dir.join(HISTORY_SAMPLES);
+        self.path_xvb = history_dir.join(HISTORY_XVB);
+        self.path_rounds = history_dir.join(HISTORY_XVB_ROUNDS);
+    }
+
+    pub fn create_all_files(history_dir: &Path) -> Result<(), TomlError> {
+        for file in HISTORY_FILE_ARRAY {
+            let path = history_dir.join(file);
+            if path.exists() {
+                info!("History | [{}] already exists, skipping...", path.display());
+                continue;
+            }
+            match fs::File::create(&path) {
+                Ok(_) => info!("History | [{}] create ... OK", path.display()),
+                Err(e) => {
+                    warn!("History | [{}] create ... FAIL: {}", path.display(), e);
+                    return Err(TomlError::Io(e));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_all_files(&mut self) -> Result<(), TomlError> {
+        self.samples = Self::parse(&read_to_string(File::Samples, &self.path_samples)?);
+        self.xvb = Self::parse(&read_to_string(File::XvbDecisions, &self.path_xvb)?);
+        self.rounds = Self::parse(&read_to_string(File::XvbRounds, &self.path_rounds)?);
+        Ok(())
+    }
+
+    // Invalid lines (e.g: cut by a crash while appending) are skipped.
+    fn parse<T: serde::de::DeserializeOwned>(string: &str) -> Vec<T> {
+        string
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| match serde_json::from_str(l) {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    warn!("History | Skipping invalid line [{}]: {}", l, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    //---------------------------------------------------------------------------------------------------- Live
+    pub fn push_sample(&mut self, sample: Sample) -> Result<(), TomlError> {
+        Self::disk_append(&sample, &self.path_samples)?;
+        self.samples.push(sample);
+        Ok(())
+    }
+
+    pub fn push_xvb(&mut self, decision: XvbDecision) -> Result<(), TomlError> {
+        Self::disk_append(&decision, &self.path_xvb)?;
+        self.xvb.push(decision);
+        Ok(())
+    }
+
+    pub fn push_round(&mut self, round: XvbRoundRecord) -> Result<(), TomlError> {
+        Self::disk_append(&round, &self.path_rounds)?;
+        self.rounds.push(round);
+        Ok(())
+    }
+
+    // Samples/decisions in the [start..] range, for the charts.
+    pub fn samples_since(&self, start: u64) -> &[Sample] {
+        let i = self.samples.partition_point(|s| s.time < start);
+        &self.samples[i..]
+    }
+
+    pub fn xvb_since(&self, start: u64) -> &[XvbDecision] {
+        let i = self.xvb.partition_point(|d| d.time < start);
+        &self.xvb[i..]
+    }
+
+    // Downsample and delete old data, then rewrite the files.
+    // Rounds are few and kept forever, they are the participation log.
+    pub fn compact(&mut self, now: u64) -> Result<(), TomlError> {
+        let samples = Self::downsample(&self.samples, now);
+        let retention = now.saturating_sub(HISTORY_RETENTION);
+        let xvb: Vec<XvbDecision> = self
+            .xvb
+            .iter()
+            .filter(|d| d.time >= retention)
+            .cloned()
+            .collect();
+        Self::disk_overwrite(&samples, &self.path_samples)?;
+        Self::disk_overwrite(&xvb, &self.path_xvb)?;
+        info!(
+            "History | Compact ... OK ... samples: [{} -> {}], xvb: [{} -> {}]",
+            self.samples.len(),
+            samples.len(),
+            self.xvb.len(),
+            xvb.len()
+        );
+        self.samples = samples;
+        self.xvb = xvb;
+        Ok(())
+    }
+
+    // Samples older than [HISTORY_RAW_RETENTION] are averaged per [HISTORY_DOWNSAMPLE_INTERVAL],
+    // samples older than [HISTORY_RETENTION] are dropped.
+    pub fn downsample(samples: &[Sample], now: u64) -> Vec<Sample> {
+        let retention = now.saturating_sub(HISTORY_RETENTION);
+        let raw = now.saturating_sub(HISTORY_RAW_RETENTION);
+        let mut buckets: BTreeMap<u64, Vec<Sample>> = BTreeMap::new();
+        let mut recent = vec![];
+        for sample in samples.iter().filter(|s| s.time >= retention) {
+            if sample.time < raw {
+                let bucket = sample.time - sample.time % HISTORY_DOWNSAMPLE_INTERVAL;
+                buckets.entry(bucket).or_default().push(sample.clone());
+            } else {
+                recent.push(sample.clone());
+            }
+        }
+        let mut downsampled: Vec<Sample> = buckets
+            .into_iter()
+            .map(|(time, samples)| Sample::average(time, &samples))
+            .collect();
+        downsampled.append(&mut recent);
+        downsampled
+    }
+
+    //---------------------------------------------------------------------------------------------------- Disk
+    fn disk_append<T: Serialize>(line: &T, path: &PathBuf) -> Result<(), TomlError> {
+        use std::io::Write;
+        let line = match serde_json::to_string(line) {
+            Ok(l) => l,
+            Err(e) => {
+                error!("History | Serialize [{}] ... FAIL: {}", path.display(), e);
+                return Err(TomlError::Parse("history"));
+            }
+        };
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)?;
+        writeln!(file, "{}", line)?;
+        debug!("History | Append [{}] ... OK", path.display());
+        Ok(())
+    }
+
+    // Written to a temporary file first, so a crash can not lose the whole history.
+    fn disk_overwrite<T: Serialize>(lines: &[T], path: &PathBuf) -> Result<(), TomlError> {
+        let mut string = String::new();
+        for line in lines {
+            match serde_json::to_string(line) {
+                Ok(l) => writeln!(string, "{}", l)?,
+                Err(e) => {
+                    error!("History | Serialize [{}] ... FAIL: {}", path.display(), e);
+                    return Err(TomlError::Parse("history"));
+                }
+            }
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, string)?;
+        fs::rename(&tmp, path)?;
+        debug!("History | Overwrite [{}] ... OK", path.display());
+        Ok(())
+    }
+}
+
+// Current UNIX timestamp in seconds.
+pub fn unix_now() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
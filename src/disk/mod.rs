@@ -53,6 +53,7 @@ use self::errors::TomlError;
 pub mod consts;
 pub mod errors;
 pub mod gupax_p2pool_api;
+pub mod history;
 pub mod node;
 pub mod pool;
 pub mod state;
@@ -77,6 +78,9 @@ pub fn get_gupax_data_path() -> Result<PathBuf, TomlError> {
             let mut gupax_p2pool_dir = path.clone();
             gupax_p2pool_dir.push(GUPAX_P2POOL_API_DIRECTORY);
             create_gupax_p2pool_dir(&gupax_p2pool_dir)?;
+            let mut history_dir = path.clone();
+            history_dir.push(HISTORY_DIRECTORY);
+            create_history_dir(&history_dir)?;
             Ok(path)
         }
         None => {
@@ -112,6 +116,12 @@ pub fn get_gupax_p2pool_path(os_data_path: &Path) -> PathBuf {
     gupax_p2pool_dir
 }
 
+pub fn get_history_path(os_data_path: &Path) -> PathBuf {
+    let mut history_dir = os_data_path.to_path_buf();
+    history_dir.push(HISTORY_DIRECTORY);
+    history_dir
+}
+
 pub fn create_gupax_dir(path: &PathBuf) -> Result<(), TomlError> {
     // Create Gupax directory
     match fs::create_dir_all(path) {
@@ -148,6 +158,23 @@ pub fn create_gupax_p2pool_dir(path: &PathBuf) -> Result<(), TomlError> {
     }
 }
 
+pub fn create_history_dir(path: &PathBuf) -> Result<(), TomlError> {
+    match fs::create_dir_all(path) {
+        Ok(_) => {
+            info!("OS | Create History path [{}] ... OK", path.display());
+            Ok(())
+        }
+        Err(e) => {
+            error!(
+                "OS | Create History path [{}] ... FAIL ... {}",
+                path.display(),
+                e
+            );
+            Err(TomlError::Io(e))
+        }
+    }
+}
+
 // Convert a [File] path to a [String]
 pub fn read_to_string(file: File, path: &PathBuf) -> Result<String, TomlError> {
     match fs::read_to_string(path) {
@@ -196,4 +223,9 @@ pub enum File {
     Log,    // log    | Raw log lines of P2Pool payouts received
     Payout, // payout | Single [u64] representing total payouts
     Xmr,    // xmr    | Single [u64] representing total XMR mined in atomic units
+
+    // History
+    Samples,      // samples | JSON lines of [Sample]
+    XvbDecisions, // xvb     | JSON lines of [XvbDecision]
+    XvbRounds,    // rounds  | JSON lines of [XvbRoundRecord]
 }
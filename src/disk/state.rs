@@ -2,7 +2,14 @@ use anyhow::Result;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 
 use super::*;
-use crate::{components::node::RemoteNode, disk::status::*};
+use crate::{
+    components::{node::RemoteNode, update::UpdateChannel},
+    disk::status::*,
+    helper::xvb::{
+        algorithm::XvbSplit, nodes::XvbNodeConfig, rigs::XvbRigConfig, rounds::XvbRound,
+        strategy::XvbMode,
+    },
+};
 //---------------------------------------------------------------------------------------------------- [State] Impl
 impl Default for State {
     fn default() -> Self {
@@ -138,7 +145,7 @@ impl State {
     // leaving behind old keys+values and updating [default] with old valid ones.
     pub fn merge(old: &str) -> Result<Self, TomlError> {
         let default = toml::ser::to_string(&Self::new()).unwrap();
-        let new: Self = match Figment::from(Toml::string(&default))
+        let mut new: Self = match Figment::from(Toml::string(&default))
             .merge(Toml::string(old))
             .extract()
         {
@@ -151,8 +158,24 @@ impl State {
                 return Err(TomlError::Merge(err));
             }
         };
+        // [xvb.hero] was replaced by [xvb.mode], keep the hero mode of old states.
+        if Self::old_hero(old) {
+            info!("State | Old [xvb.hero] found, selecting the hero mode");
+            new.xvb.mode = XvbMode::Hero;
+        }
         Ok(new)
     }
+
+    // Returns true if the old TOML enables [xvb.hero] and has no [xvb.mode] yet.
+    fn old_hero(old: &str) -> bool {
+        let Ok(table) = old.parse::<toml::Table>() else {
+            return false;
+        };
+        let Some(xvb) = table.get("xvb") else {
+            return false;
+        };
+        xvb.get("mode").is_none() && xvb.get("hero").and_then(|hero| hero.as_bool()) == Some(true)
+    }
 }
 //---------------------------------------------------------------------------------------------------- [State] Struct
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -174,6 +197,10 @@ pub struct Status {
     pub manual_hash: bool,
     pub hashrate: f64,
     pub hash_metric: Hash,
+    pub chart_window: ChartWindow,
+    pub worker_sort: WorkerSort,
+    pub worker_sort_descending: bool,
+    pub worker_min_hashrate: u64, // Workers under this H/s are flagged, 0 to disable
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
@@ -187,6 +214,7 @@ pub struct Gupax {
     //	pub auto_monero: bool,
     pub ask_before_quit: bool,
     pub save_before_quit: bool,
+    pub auto_rollback: bool,
     pub p2pool_path: String,
     pub xmrig_path: String,
     pub xmrig_proxy_path: String,
@@ -199,6 +227,18 @@ pub struct Gupax {
     pub tab: Tab,
     pub ratio: Ratio,
     pub bundled: bool,
+    pub update_channel: UpdateChannel,
+    pub pinned_gupax: String,  // Version to update to, empty if not pinned
+    pub pinned_p2pool: String, // Versions held by updates, empty if not pinned
+    pub pinned_xmrig: String,
+    pub pinned_xp: String,
+    pub http_api: bool,
+    pub http_api_ip: String,
+    pub http_api_port: String,
+    pub http_api_token: String,
+    pub metrics: bool,
+    pub metrics_ip: String,
+    pub metrics_port: String,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
@@ -208,6 +248,7 @@ pub struct P2pool {
     pub auto_ping: bool,
     pub auto_select: bool,
     pub backup_host: bool,
+    pub failover: bool,
     pub out_peers: u16,
     pub in_peers: u16,
     pub log_level: u8,
@@ -223,6 +264,7 @@ pub struct P2pool {
     pub selected_ip: String,
     pub selected_rpc: String,
     pub selected_zmq: String,
+    pub supervisor: SupervisorPolicy,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
@@ -248,6 +290,7 @@ pub struct Xmrig {
     pub selected_ip: String,
     pub selected_port: String,
     pub token: String,
+    pub supervisor: SupervisorPolicy,
 }
 
 // present for future.
@@ -274,6 +317,7 @@ pub struct XmrigProxy {
     pub selected_port: String,
     pub token: String,
     pub redirect_local_xmrig: bool,
+    pub supervisor: SupervisorPolicy,
 }
 
 impl Default for XmrigProxy {
@@ -304,14 +348,41 @@ impl Default for XmrigProxy {
             api_port: "18089".to_string(),
             tls: false,
             keepalive: false,
+            supervisor: SupervisorPolicy::default(),
         }
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, Default)]
+// Restart policy of a process applied by the [Supervisor] when it crashes.
+// Restarts are delayed by [backoff] seconds, doubled after each restart.
+// The count is reset once the process stayed alive longer than [crash_loop_window] seconds.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct SupervisorPolicy {
+    pub enabled: bool,
+    pub max_restarts: u8,
+    pub backoff: u64,
+    pub crash_loop_window: u64,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub struct Xvb {
+    pub simple: bool,
     pub token: String,
-    pub hero: bool,
+    pub mode: XvbMode,           // Strategy deciding the split of each cycle
+    pub stratum_hashrate: bool, // Use the hashrate of the P2Pool stratum instead of the sidechain estimate
+    pub manual_outside_hr: bool, // Use [outside_hr] instead of the estimated outside HR
+    pub outside_hr: u32,        // H/s
+    pub manual_split: bool,     // Donate a fixed [split] every cycle, the estimates are not used
+    pub split_unit: XvbSplit,
+    pub split: u32,
+    pub manual_round: bool, // Never aim for a round higher than [round_cap]
+    pub round_cap: XvbRound,
+    pub share_confidence: u8, // Probability in % to keep a share in the PPLNS window, for the probabilistic mode
+    pub nodes: Vec<XvbNodeConfig>, // XvB nodes to choose from, the fastest is used
+    pub stratum_switcher: bool, // Switch the miners with the stratum switcher instead of the HTTP API of XMRig
+    pub switcher_bind: String,  // Address the stratum switcher listens on
+    pub rigs: Vec<XvbRigConfig>, // Remote XMRig controlled with their HTTP API, in addition to the local miners
+    pub thread_split: bool, // Mine on XvB with a part of the threads for the whole cycle instead of a part of the cycle
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -319,6 +390,8 @@ pub struct Version {
     pub gupax: String,
     pub p2pool: String,
     pub xmrig: String,
+    pub backup: String, // Version of Gupaxx kept by the last update, empty if none
+    pub untested: Vec<String>, // Processes updated by the last update that did not start yet
 }
 
 //---------------------------------------------------------------------------------------------------- [State] Defaults
@@ -331,6 +404,10 @@ impl Default for Status {
             manual_hash: false,
             hashrate: 1.0,
             hash_metric: Hash::default(),
+            chart_window: ChartWindow::default(),
+            worker_sort: WorkerSort::default(),
+            worker_sort_descending: true,
+            worker_min_hashrate: 0,
         }
     }
 }
@@ -346,6 +423,7 @@ impl Default for Gupax {
             auto_xvb: false,
             ask_before_quit: true,
             save_before_quit: true,
+            auto_rollback: false,
             p2pool_path: DEFAULT_P2POOL_PATH.to_string(),
             xmrig_path: DEFAULT_XMRIG_PATH.to_string(),
             xmrig_proxy_path: DEFAULT_XMRIG_PROXY_PATH.to_string(),
@@ -361,6 +439,22 @@ impl Default for Gupax {
             bundled: true,
             #[cfg(not(feature = "bundle"))]
             bundled: false,
+            update_channel: UpdateChannel::Stable,
+            pinned_gupax: String::new(),
+            pinned_p2pool: String::new(),
+            pinned_xmrig: String::new(),
+            pinned_xp: String::new(),
+            http_api: false,
+            http_api_ip: "127.0.0.1".to_string(),
+            http_api_port: "18090".to_string(),
+            http_api_token: thread_rng()
+                .sample_iter(Alphanumeric)
+                .take(16)
+                .map(char::from)
+                .collect(),
+            metrics: false,
+            metrics_ip: "127.0.0.1".to_string(),
+            metrics_port: "18091".to_string(),
         }
     }
 }
@@ -373,6 +467,7 @@ impl Default for P2pool {
             auto_ping: true,
             auto_select: true,
             backup_host: true,
+            failover: false,
             out_peers: 10,
             in_peers: 10,
             log_level: 3,
@@ -388,6 +483,7 @@ impl Default for P2pool {
             selected_ip: "localhost".to_string(),
             selected_rpc: "18081".to_string(),
             selected_zmq: "18083".to_string(),
+            supervisor: SupervisorPolicy::default(),
         }
     }
 }
@@ -430,6 +526,42 @@ impl Default for Xmrig {
                 .take(16)
                 .map(char::from)
                 .collect(),
+            supervisor: SupervisorPolicy::default(),
+        }
+    }
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_restarts: 5,
+            backoff: 5,
+            crash_loop_window: 600,
+        }
+    }
+}
+
+impl Default for Xvb {
+    fn default() -> Self {
+        Self {
+            simple: true,
+            token: String::new(),
+            mode: XvbMode::default(),
+            stratum_hashrate: false,
+            manual_outside_hr: false,
+            outside_hr: 0,
+            manual_split: false,
+            split_unit: XvbSplit::default(),
+            split: 50,
+            manual_round: false,
+            round_cap: XvbRound::DonorVip,
+            share_confidence: 95,
+            nodes: XvbNodeConfig::defaults(),
+            stratum_switcher: false,
+            switcher_bind: XVB_SWITCHER_BIND.to_string(),
+            rigs: vec![],
+            thread_split: false,
         }
     }
 }
@@ -440,6 +572,8 @@ impl Default for Version {
             gupax: GUPAX_VERSION.to_string(),
             p2pool: P2POOL_VERSION.to_string(),
             xmrig: XMRIG_VERSION.to_string(),
+            backup: String::new(),
+            untested: vec![],
         }
     }
 }
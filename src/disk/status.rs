@@ -4,6 +4,8 @@ use super::*;
 pub enum Submenu {
     Processes,
     P2pool,
+    Workers,
+    Charts,
     Benchmarks,
 }
 
@@ -51,6 +53,71 @@ impl Display for PayoutView {
     }
 }
 
+//---------------------------------------------------------------------------------------------------- [ChartWindow] enum for [Status/Charts] tab
+// How far back the charts go.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub enum ChartWindow {
+    Hour,
+    Day,
+    Week,
+}
+
+impl ChartWindow {
+    pub fn seconds(&self) -> u64 {
+        match self {
+            Self::Hour => 3600,
+            Self::Day => 86400,
+            Self::Week => 7 * 86400,
+        }
+    }
+    // Unit of the X axis in seconds, minutes for the last hour, hours otherwise.
+    pub fn unit(&self) -> u64 {
+        match self {
+            Self::Hour => 60,
+            Self::Day | Self::Week => 3600,
+        }
+    }
+}
+
+impl Default for ChartWindow {
+    fn default() -> Self {
+        Self::Day
+    }
+}
+
+impl Display for ChartWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Hour => write!(f, "1 hour"),
+            Self::Day => write!(f, "24 hours"),
+            Self::Week => write!(f, "7 days"),
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- [WorkerSort] enum for [Status/Workers] tab
+// The column the worker table is sorted by.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub enum WorkerSort {
+    Name,
+    Address,
+    Hashrate,
+    Difficulty,
+    Uptime,
+}
+
+impl Default for WorkerSort {
+    fn default() -> Self {
+        Self::Hashrate
+    }
+}
+
+impl Display for WorkerSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 //---------------------------------------------------------------------------------------------------- [Hash] enum for [Status/P2Pool]
 #[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
 #[allow(clippy::enum_variant_names)]
@@ -35,6 +35,7 @@ mod test {
             auto_xp = false
 			ask_before_quit = true
 			save_before_quit = true
+			auto_rollback = false
 			p2pool_path = "p2pool/p2pool"
 			xmrig_path = "xmrig/xmrig"
 			xmrig_proxy_path = "xmrig-proxy/xmrig-proxy"
@@ -47,6 +48,18 @@ mod test {
 			tab = "About"
 			ratio = "Width"
 			bundled = false
+			update_channel = "PreRelease"
+			pinned_gupax = ""
+			pinned_p2pool = ""
+			pinned_xmrig = "6.21"
+			pinned_xp = ""
+			http_api = false
+			http_api_ip = "127.0.0.1"
+			http_api_port = "18090"
+			http_api_token = "testtoken"
+			metrics = false
+			metrics_ip = "127.0.0.1"
+			metrics_port = "18091"
 
 			[status]
 			submenu = "P2pool"
@@ -55,6 +68,10 @@ mod test {
 			manual_hash = false
 			hashrate = 1241.23
 			hash_metric = "Hash"
+			chart_window = "Week"
+			worker_sort = "Name"
+			worker_sort_descending = false
+			worker_min_hashrate = 1000
 
 			[p2pool]
 			simple = true
@@ -62,6 +79,7 @@ mod test {
 			auto_ping = true
 			auto_select = true
 			backup_host = true
+			failover = true
 			out_peers = 10
 			in_peers = 450
 			log_level = 3
@@ -78,6 +96,12 @@ mod test {
 			selected_rpc = "18089"
 			selected_zmq = "18083"
 
+			[p2pool.supervisor]
+			enabled = true
+			max_restarts = 3
+			backoff = 10
+			crash_loop_window = 300
+
 			[xmrig]
 			simple = true
 			pause = 0
@@ -101,6 +125,12 @@ mod test {
 			selected_port = "3333"
             token = "testtoken"
 
+			[xmrig.supervisor]
+			enabled = false
+			max_restarts = 5
+			backoff = 5
+			crash_loop_window = 600
+
             [xmrig_proxy]
             simple = true
             arguments = ""
@@ -124,17 +154,70 @@ mod test {
 			selected_port = "3333"
             redirect_local_xmrig = true
 
+			[xmrig_proxy.supervisor]
+			enabled = false
+			max_restarts = 5
+			backoff = 5
+			crash_loop_window = 600
+
             [xvb]
+            simple = false
             token = ""
-            hero = false
+            mode = "Probabilistic"
+            stratum_hashrate = true
+            manual_outside_hr = true
+            outside_hr = 12000
+            manual_split = false
+            split_unit = "Seconds"
+            split = 120
+            manual_round = true
+            round_cap = "DonorVip"
+            share_confidence = 90
             node = "Europe"
+            stratum_switcher = true
+            switcher_bind = "127.0.0.1:3344"
+            thread_split = true
+
+            [[xvb.nodes]]
+            name = "European"
+            host = "eu.xmrvsbeast.com"
+            port = 4247
+            tls = true
+            keepalive = true
+
+            [[xvb.nodes]]
+            name = "Custom"
+            host = "xvb.example.com"
+            port = 3333
+            tls = false
+            keepalive = false
+
+            [[xvb.rigs]]
+            name = "Garage"
+            api = "192.168.1.20:18088"
+            token = "secret"
+            p2pool = "192.168.1.10:3333"
+
+            [[xvb.rigs]]
+            name = "Office"
+            api = "192.168.1.21:18088"
 
 			[version]
 			gupax = "v1.3.0"
 			p2pool = "v2.5"
 			xmrig = "v6.18.0"
+			backup = "v1.2.0"
+			untested = ["P2Pool"]
 		"#;
         let state = State::from_str(state).unwrap();
+        assert_eq!(state.xvb.nodes.len(), 2);
+        assert_eq!(state.xvb.nodes[1].host, "xvb.example.com");
+        assert!(state.xvb.stratum_switcher);
+        assert_eq!(state.xvb.switcher_bind, "127.0.0.1:3344");
+        assert_eq!(state.xvb.rigs.len(), 2);
+        assert!(state.xvb.thread_split);
+        assert_eq!(state.xvb.rigs[0].p2pool, "192.168.1.10:3333");
+        assert!(state.xvb.rigs[1].token.is_empty());
         State::to_string(&state).unwrap();
     }
 
@@ -268,6 +351,32 @@ mod test {
         assert!(merged_state.contains("backup_host = true"));
     }
 
+    // Old states had [xvb.hero] instead of [xvb.mode].
+    #[test]
+    fn merge_state_hero() {
+        use crate::helper::xvb::strategy::XvbMode;
+        let old_state = r#"
+            [xvb]
+            simple = true
+            token = "1234"
+            hero = true
+            manual_outside_hr = false
+        "#;
+        let path =
+            std::env::temp_dir().join(format!("gupaxx_state_hero_{}.toml", std::process::id()));
+        std::fs::write(&path, old_state).unwrap();
+        let state = State::get(&path).unwrap();
+        assert_eq!(state.xvb.mode, XvbMode::Hero);
+        assert_eq!(state.xvb.token, "1234");
+        // the migrated state is saved, without the old key
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(saved.contains(r#"mode = "Hero""#));
+        assert!(!saved.contains("hero = true"));
+        let state = State::merge(&old_state.replace("hero = true", "hero = false")).unwrap();
+        assert_eq!(state.xvb.mode, XvbMode::Default);
+    }
+
     #[test]
     fn create_and_serde_gupax_p2pool_api() {
         use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
@@ -304,6 +413,101 @@ mod test {
             .contains("2022-01-27 01:30:23.1377 | 0.000000000001 XMR | Block 2,642,816"));
     }
 
+    #[test]
+    fn create_and_compact_history() {
+        use crate::disk::consts::*;
+        use crate::disk::history::{History, Sample, XvbDecision, XvbRoundRecord};
+        use crate::helper::xvb::rounds::XvbRound;
+
+        // Not the real data path, to not touch the user's history.
+        let path = std::env::temp_dir().join("gupaxx_test_history");
+        let _ = std::fs::remove_dir_all(&path);
+        crate::disk::create_history_dir(&path).unwrap();
+        History::create_all_files(&path).unwrap();
+        let mut history = History::new();
+        history.fill_paths(&path);
+
+        // 3 samples in the same old hour, 1 too old to be kept, 1 recent.
+        let now = 100 * 86400;
+        let hour = now - HISTORY_RAW_RETENTION - 10 * 3600;
+        let sample = |time, hr| Sample {
+            time,
+            xmrig_hashrate: hr,
+            p2pool_hashrate_1h: hr as u64,
+            shares_found: Some(time),
+            ..Default::default()
+        };
+        for s in [
+            sample(now - HISTORY_RETENTION - 1, 1.0),
+            sample(hour, 1000.0),
+            sample(hour + 60, 2000.0),
+            sample(hour + 120, 3000.0),
+            sample(now - 60, 4000.0),
+        ] {
+            history.push_sample(s).unwrap();
+        }
+        history
+            .push_xvb(XvbDecision {
+                time: now - 600,
+                donated: 120,
+                node: "XvB Europe".to_string(),
+                round: Some("VIP Donor".to_string()),
+            })
+            .unwrap();
+        let round = XvbRoundRecord {
+            time: 10,
+            round: Some(XvbRound::DonorVip),
+            donor_1h: 12.5,
+            donor_24h: 11.0,
+            donated: 1200,
+            won: true,
+            winner: "4A5Dwt2q...5rYFWnw".to_string(),
+            roll_winner: 17,
+            roll_round: 98,
+        };
+        history.push_round(round.clone()).unwrap();
+        // A line cut by a crash must not prevent reading the rest.
+        std::fs::write(
+            &history.path_samples,
+            std::fs::read_to_string(&history.path_samples).unwrap() + "{\"time\":12",
+        )
+        .unwrap();
+
+        // Read back
+        let mut read = History::new();
+        read.fill_paths(&path);
+        read.read_all_files().unwrap();
+        assert_eq!(read.samples, history.samples);
+        assert_eq!(read.xvb, history.xvb);
+        assert_eq!(read.rounds, vec![round.clone()]);
+
+        // Compact
+        read.compact(now).unwrap();
+        assert_eq!(read.samples.len(), 2);
+        assert_eq!(
+            read.samples[0].time,
+            hour - hour % HISTORY_DOWNSAMPLE_INTERVAL
+        );
+        assert_eq!(read.samples[0].xmrig_hashrate, 2000.0);
+        assert_eq!(read.samples[0].p2pool_hashrate_1h, 2000);
+        assert_eq!(read.samples[0].shares_found, Some(hour + 120));
+        assert_eq!(read.samples[1], sample(now - 60, 4000.0));
+        assert_eq!(read.samples_since(now - 3600).len(), 1);
+        assert_eq!(read.xvb_since(now - 3600).len(), 1);
+        assert!(read.xvb_since(now).is_empty());
+        assert_eq!(read.xvb.len(), 1);
+
+        // Compacting again changes nothing, and the files match.
+        let compacted = read.samples.clone();
+        read.compact(now).unwrap();
+        assert_eq!(read.samples, compacted);
+        read.read_all_files().unwrap();
+        assert_eq!(read.samples, compacted);
+        // Rounds are never dropped.
+        assert_eq!(read.rounds, vec![round]);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
     #[test]
     fn convert_hash() {
         use crate::disk::status::Hash;
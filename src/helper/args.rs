@@ -0,0 +1,536 @@
+// Typed model of the command line of P2Pool, XMRig and XMRig-Proxy.
+// The arguments overriding the advanced settings are checked against the options known for each binary
+// before the start, and the arguments built by Gupaxx in the simple and advanced modes go through the same model.
+
+use std::fmt::{self, Display};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binary {
+    P2pool,
+    Xmrig,
+    XmrigProxy,
+}
+impl Display for Binary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::P2pool => write!(f, "P2Pool"),
+            Self::Xmrig => write!(f, "XMRig"),
+            Self::XmrigProxy => write!(f, "XMRig-Proxy"),
+        }
+    }
+}
+impl Binary {
+    fn specs(self) -> &'static [Spec] {
+        match self {
+            Self::P2pool => P2POOL_SPECS,
+            Self::Xmrig => XMRIG_SPECS,
+            Self::XmrigProxy => XP_SPECS,
+        }
+    }
+    // Spec of an option given by its long or short name.
+    fn spec(self, name: &str) -> Option<&'static Spec> {
+        self.specs()
+            .iter()
+            .find(|s| s.name == name || s.short == Some(name))
+    }
+}
+
+// What follows an option on the command line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Value {
+    Flag,
+    Text,
+    Port,
+    Number(u32, u32), // inclusive range
+    Pair,             // two values, like [--merge-mine <host> <wallet>]
+    Optional,         // a flag or [--option=value]
+}
+impl Value {
+    fn count(self) -> usize {
+        match self {
+            Self::Flag | Self::Optional => 0,
+            Self::Text | Self::Port | Self::Number(..) => 1,
+            Self::Pair => 2,
+        }
+    }
+    fn is_valid(self, value: &str) -> bool {
+        match self {
+            Self::Port => value.parse::<u16>().is_ok_and(|p| p != 0),
+            Self::Number(min, max) => value.parse::<u32>().is_ok_and(|n| n >= min && n <= max),
+            Self::Flag | Self::Text | Self::Pair | Self::Optional => true,
+        }
+    }
+}
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Port => write!(f, "a port between 1 and 65535"),
+            Self::Number(min, max) => write!(f, "a number between {} and {}", min, max),
+            Self::Flag | Self::Text | Self::Pair | Self::Optional => write!(f, "a value"),
+        }
+    }
+}
+
+struct Spec {
+    name: &'static str,
+    short: Option<&'static str>,
+    value: Value,
+    repeat: bool, // options of a pool or a node, given once for each of them
+}
+impl Spec {
+    const fn new(name: &'static str, value: Value) -> Self {
+        Self {
+            name,
+            short: None,
+            value,
+            repeat: false,
+        }
+    }
+    const fn short(self, short: &'static str) -> Self {
+        Self {
+            short: Some(short),
+            ..self
+        }
+    }
+    const fn repeat(self) -> Self {
+        Self {
+            repeat: true,
+            ..self
+        }
+    }
+}
+const fn flag(name: &'static str) -> Spec {
+    Spec::new(name, Value::Flag)
+}
+const fn text(name: &'static str) -> Spec {
+    Spec::new(name, Value::Text)
+}
+const fn port(name: &'static str) -> Spec {
+    Spec::new(name, Value::Port)
+}
+const fn number(name: &'static str, min: u32, max: u32) -> Spec {
+    Spec::new(name, Value::Number(min, max))
+}
+
+// [p2pool --help], a node is given by [--host] followed by its own ports and login.
+const P2POOL_SPECS: &[Spec] = &[
+    text("--wallet"),
+    text("--host").repeat(),
+    port("--rpc-port").repeat(),
+    port("--zmq-port").repeat(),
+    text("--rpc-login").repeat(),
+    flag("--rpc-ssl").repeat(),
+    text("--rpc-ssl-fingerprint").repeat(),
+    text("--stratum"),
+    text("--p2p"),
+    text("--addpeers"),
+    flag("--light-mode"),
+    number("--loglevel", 0, 6),
+    text("--config"),
+    text("--data-dir"),
+    text("--sidechain-config"),
+    text("--data-api"),
+    flag("--local-api"),
+    flag("--stratum-api"),
+    flag("--no-cache"),
+    flag("--no-color"),
+    flag("--no-randomx"),
+    number("--out-peers", 10, 450),
+    number("--in-peers", 10, 450),
+    number("--start-mining", 1, u16::MAX as u32),
+    flag("--mini"),
+    flag("--nano"),
+    flag("--no-autodiff"),
+    text("--socks5"),
+    flag("--no-dns"),
+    port("--p2p-external-port"),
+    flag("--no-upnp"),
+    flag("--upnp-stratum"),
+    Spec::new("--merge-mine", Value::Pair).repeat(),
+    text("--onion-address"),
+    flag("--no-clearnet-p2p"),
+    flag("--version"),
+    flag("--help"),
+];
+
+// [xmrig --help], a pool is given by [--url] followed by its own options.
+const XMRIG_SPECS: &[Spec] = &[
+    // Network
+    text("--url").short("-o").repeat(),
+    text("--algo").short("-a").repeat(),
+    text("--coin").repeat(),
+    text("--user").short("-u").repeat(),
+    text("--pass").short("-p").repeat(),
+    text("--userpass").short("-O").repeat(),
+    text("--proxy").short("-x").repeat(),
+    flag("--keepalive").short("-k").repeat(),
+    flag("--nicehash").repeat(),
+    text("--rig-id").repeat(),
+    flag("--tls").repeat(),
+    text("--tls-fingerprint").repeat(),
+    flag("--daemon").repeat(),
+    number("--daemon-poll-interval", 0, u32::MAX).repeat(),
+    number("--daemon-job-timeout", 0, u32::MAX).repeat(),
+    text("--self-select").repeat(),
+    flag("--submit-to-origin").repeat(),
+    flag("--dns-ipv6"),
+    number("--dns-ttl", 0, u32::MAX),
+    number("--retries", 0, u32::MAX).short("-r"),
+    number("--retry-pause", 0, u32::MAX).short("-R"),
+    text("--user-agent"),
+    number("--donate-level", 0, 100),
+    number("--donate-over-proxy", 0, 2),
+    // CPU
+    flag("--no-cpu"),
+    number("--threads", 1, u16::MAX as u32).short("-t"),
+    number("--av", 0, u32::MAX).short("-v"),
+    text("--cpu-affinity"),
+    number("--cpu-priority", 0, 5),
+    number("--cpu-max-threads-hint", 1, 100),
+    text("--cpu-memory-pool"),
+    flag("--cpu-no-yield"),
+    flag("--no-huge-pages"),
+    number("--hugepage-size", 1, u32::MAX),
+    flag("--huge-pages-jit"),
+    text("--asm"),
+    text("--argon2-impl"),
+    text("--randomx-init"),
+    flag("--randomx-no-numa"),
+    text("--randomx-mode"),
+    flag("--randomx-1gb-pages"),
+    Spec::new("--randomx-wrmsr", Value::Optional),
+    flag("--randomx-no-rdmsr"),
+    flag("--randomx-cache-qos"),
+    // API
+    text("--api-worker-id"),
+    text("--api-id"),
+    text("--http-host"),
+    port("--http-port"),
+    text("--http-access-token"),
+    flag("--http-no-restricted"),
+    // OpenCL and CUDA
+    flag("--opencl"),
+    text("--opencl-devices"),
+    text("--opencl-platform"),
+    text("--opencl-loader"),
+    flag("--opencl-no-cache"),
+    flag("--print-platforms"),
+    flag("--cuda"),
+    text("--cuda-loader"),
+    text("--cuda-devices"),
+    number("--cuda-bfactor-hint", 0, 12),
+    number("--cuda-bsleep-hint", 0, u32::MAX),
+    flag("--no-nvml"),
+    // Logging
+    flag("--syslog").short("-S"),
+    text("--log-file").short("-l"),
+    number("--print-time", 0, u32::MAX),
+    number("--health-print-time", 0, u32::MAX),
+    flag("--no-color"),
+    flag("--verbose"),
+    // Misc
+    text("--config").short("-c"),
+    flag("--background").short("-B"),
+    text("--title"),
+    flag("--no-title"),
+    flag("--pause-on-battery"),
+    number("--pause-on-active", 0, u32::MAX),
+    flag("--stress"),
+    text("--bench"),
+    text("--seed"),
+    text("--hash"),
+    flag("--dmi"),
+    flag("--no-dmi"),
+    flag("--export-topology"),
+    flag("--dry-run"),
+    flag("--help").short("-h"),
+    flag("--version").short("-V"),
+];
+
+// [xmrig-proxy --help], pools are given like XMRig.
+const XP_SPECS: &[Spec] = &[
+    // Network
+    text("--url").short("-o").repeat(),
+    text("--algo").short("-a").repeat(),
+    text("--coin").repeat(),
+    text("--user").short("-u").repeat(),
+    text("--pass").short("-p").repeat(),
+    text("--userpass").short("-O").repeat(),
+    flag("--keepalive").short("-k").repeat(),
+    text("--rig-id").repeat(),
+    flag("--tls").repeat(),
+    text("--tls-fingerprint").repeat(),
+    flag("--daemon").repeat(),
+    number("--daemon-poll-interval", 0, u32::MAX).repeat(),
+    flag("--dns-ipv6"),
+    number("--dns-ttl", 0, u32::MAX),
+    number("--retries", 0, u32::MAX).short("-r"),
+    number("--retry-pause", 0, u32::MAX).short("-R"),
+    text("--user-agent"),
+    number("--donate-level", 0, 100),
+    // Proxy
+    text("--bind").short("-b").repeat(),
+    text("--mode").short("-m"),
+    number("--custom-diff", 0, u32::MAX),
+    flag("--custom-diff-stats"),
+    number("--reuse-timeout", 0, u32::MAX),
+    text("--login-file"),
+    text("--access-password"),
+    flag("--no-workers"),
+    // TLS
+    text("--tls-bind").repeat(),
+    text("--tls-cert"),
+    text("--tls-cert-key"),
+    text("--tls-dhparam"),
+    text("--tls-protocols"),
+    text("--tls-ciphers"),
+    text("--tls-ciphersuites"),
+    // API
+    text("--api-worker-id"),
+    text("--api-id"),
+    text("--http-host"),
+    port("--http-port"),
+    text("--http-access-token"),
+    flag("--http-no-restricted"),
+    // Logging
+    text("--log-file").short("-l"),
+    text("--access-log-file").short("-A"),
+    flag("--syslog").short("-S"),
+    flag("--no-color"),
+    flag("--verbose"),
+    // Misc
+    text("--config").short("-c"),
+    flag("--background").short("-B"),
+    flag("--help").short("-h"),
+    flag("--version").short("-V"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgError {
+    Unknown(Binary, String),
+    Duplicate(String),
+    MissingValue(String),
+    UnexpectedValue(String), // a value given to a flag, or a value without an option
+    InvalidValue(String, String, String), // option, value, what was expected
+}
+impl ArgError {
+    // An unknown option may come from a newer binary, it is passed as it is with its values.
+    pub fn is_blocking(&self) -> bool {
+        !matches!(self, Self::Unknown(..))
+    }
+}
+impl Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ArgError::*;
+        match self {
+            Unknown(binary, name) => write!(f, "{} is not an option of {}", name, binary),
+            Duplicate(name) => write!(f, "{} is given more than once", name),
+            MissingValue(name) => write!(f, "{} needs a value", name),
+            UnexpectedValue(value) => write!(f, "{} is not expected here", value),
+            InvalidValue(name, value, expected) => write!(
+                f,
+                "{} is not a valid value for {}, expected {}",
+                value, name, expected
+            ),
+        }
+    }
+}
+
+// One option and its values. Options are stored with their long name,
+// anything unknown kept by [Args::parse_lossy] is stored as it was typed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Arg {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Args {
+    binary: Binary,
+    list: Vec<Arg>,
+}
+impl Args {
+    pub fn new(binary: Binary) -> Self {
+        Self {
+            binary,
+            list: vec![],
+        }
+    }
+    // Arguments typed by the user, the first error found is returned.
+    #[cfg(test)]
+    pub fn parse(binary: Binary, input: &str) -> Result<Self, ArgError> {
+        match Self::parse_all(binary, input) {
+            (args, errors) if errors.is_empty() => Ok(args),
+            (_, mut errors) => Err(errors.swap_remove(0)),
+        }
+    }
+    // Like [parse], but what is not understood is passed as it is to the binary.
+    // Used at the start, the arguments were already checked by the UI.
+    pub fn parse_lossy(binary: Binary, input: &str) -> Self {
+        Self::parse_all(binary, input).0
+    }
+    fn parse_all(binary: Binary, input: &str) -> (Self, Vec<ArgError>) {
+        let mut args = Self::new(binary);
+        let mut errors = vec![];
+        let mut tokens = input.split_whitespace().peekable();
+        while let Some(token) = tokens.next() {
+            // [--option=value] is accepted for every binary, the value is given separately.
+            let (name, inline) = match token.split_once('=') {
+                Some((name, value)) if name.starts_with('-') => (name, Some(value)),
+                _ => (token, None),
+            };
+            let Some(spec) = binary.spec(name) else {
+                let mut values = vec![];
+                if token.starts_with('-') {
+                    errors.push(ArgError::Unknown(binary, name.to_string()));
+                    // what follows an unknown option until the next option is its value.
+                    while let Some(value) = tokens.next_if(|t| !t.starts_with('-')) {
+                        values.push(value.to_string());
+                    }
+                } else {
+                    errors.push(ArgError::UnexpectedValue(token.to_string()));
+                }
+                args.list.push(Arg {
+                    name: token.to_string(),
+                    values,
+                });
+                continue;
+            };
+            if !spec.repeat && args.has(spec.name) {
+                errors.push(ArgError::Duplicate(spec.name.to_string()));
+            }
+            let mut values: Vec<String> = vec![];
+            if let Some(value) = inline {
+                if spec.value == Value::Flag {
+                    errors.push(ArgError::UnexpectedValue(token.to_string()));
+                } else {
+                    values.push(value.to_string());
+                }
+            }
+            // the value is missing if an option follows.
+            while values.len() < spec.value.count() {
+                match tokens.next_if(|t| !is_option(binary, t)) {
+                    Some(value) => values.push(value.to_string()),
+                    None => {
+                        errors.push(ArgError::MissingValue(spec.name.to_string()));
+                        break;
+                    }
+                }
+            }
+            if let Some(value) = values.iter().find(|v| !spec.value.is_valid(v)) {
+                errors.push(ArgError::InvalidValue(
+                    spec.name.to_string(),
+                    value.to_string(),
+                    spec.value.to_string(),
+                ));
+            }
+            args.list.push(Arg {
+                name: spec.name.to_string(),
+                values,
+            });
+        }
+        (args, errors)
+    }
+    pub fn flag(&mut self, name: &str) -> &mut Self {
+        self.push_values(name, vec![])
+    }
+    pub fn push(&mut self, name: &str, value: impl Display) -> &mut Self {
+        self.push_values(name, vec![value.to_string()])
+    }
+    fn push_values(&mut self, name: &str, values: Vec<String>) -> &mut Self {
+        debug_assert!(
+            self.binary.spec(name).is_some_and(|s| s.name == name),
+            "{} is not a long option of {}",
+            name,
+            self.binary
+        );
+        self.list.push(Arg {
+            name: name.to_string(),
+            values,
+        });
+        self
+    }
+    // Replace every occurrence of the option by this one.
+    pub fn set(&mut self, name: &str, value: impl Display) -> &mut Self {
+        self.list.retain(|a| a.name != name);
+        self.push(name, value)
+    }
+    pub fn set_flag(&mut self, name: &str) -> &mut Self {
+        if !self.has(name) {
+            self.flag(name);
+        }
+        self
+    }
+    pub fn has(&self, name: &str) -> bool {
+        self.list.iter().any(|a| a.name == name)
+    }
+    // First value of the option, the pool or node given first for the repeated ones.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.list
+            .iter()
+            .find(|a| a.name == name)
+            .and_then(|a| a.values.first().map(String::as_str))
+    }
+    fn tokens(&self) -> Vec<String> {
+        let mut tokens = Vec::with_capacity(self.list.len() * 2);
+        for arg in &self.list {
+            let optional = self
+                .binary
+                .spec(&arg.name)
+                .is_some_and(|s| s.value == Value::Optional);
+            match arg.values.first() {
+                Some(value) if optional => tokens.push(format!("{}={}", arg.name, value)),
+                _ => {
+                    tokens.push(arg.name.clone());
+                    tokens.extend(arg.values.iter().cloned());
+                }
+            }
+        }
+        tokens
+    }
+    // Command line given to the binary, none of them understands [localhost].
+    pub fn into_argv(self) -> Vec<String> {
+        self.tokens()
+            .into_iter()
+            .map(|t| {
+                if t == "localhost" {
+                    "127.0.0.1".to_string()
+                } else {
+                    t
+                }
+            })
+            .collect()
+    }
+}
+// The arguments as the user would type them.
+impl Display for Args {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.tokens().join(" "))
+    }
+}
+
+fn is_option(binary: Binary, token: &str) -> bool {
+    let name = token.split_once('=').map_or(token, |(name, _)| name);
+    binary.spec(name).is_some()
+}
+
+// Error of the arguments overriding the advanced settings, the start is refused.
+pub fn override_error(binary: Binary, simple: bool, arguments: &str) -> Option<ArgError> {
+    override_errors(binary, simple, arguments).find(ArgError::is_blocking)
+}
+// Options not known by Gupaxx, shown as a warning but given to the binary anyway.
+pub fn override_warning(binary: Binary, simple: bool, arguments: &str) -> Option<ArgError> {
+    override_errors(binary, simple, arguments).find(|e| !e.is_blocking())
+}
+fn override_errors(
+    binary: Binary,
+    simple: bool,
+    arguments: &str,
+) -> impl Iterator<Item = ArgError> {
+    let errors = if simple || arguments.is_empty() {
+        vec![]
+    } else {
+        Args::parse_all(binary, arguments).1
+    };
+    errors.into_iter()
+}
@@ -0,0 +1,451 @@
+// Local HTTP API.
+// Optional server (enabled in the [Gupaxx] tab) exposing what the [Status] tab shows,
+// read from the [gui_api_*] of the [Helper], as JSON:
+//
+//     GET  /api/status                          everything below in one object
+//     GET  /api/(p2pool|xmrig|xmrig_proxy|xvb)  a single process
+//     POST /api/(p2pool|xmrig|xmrig_proxy|xvb)/(start|stop|restart)
+//
+// POST requests need the header [Authorization: Bearer <token>] with the token
+// set in the [Gupaxx] tab, the same way XMRig handles its [--http-access-token].
+// Processes are started with the last saved settings, P2Pool with the backup hosts
+// of the last ping or saved node list. On Unix, XMRig can only be (re)started if
+// [sudo] does not ask for a password.
+
+use crate::components::node::Ping;
+use crate::components::update::{check_p2pool_path, check_xmrig_path, check_xp_path};
+use crate::disk::state::{Gupax, State};
+use crate::helper::xvb::strategy::XvbMode;
+use crate::helper::{Helper, Process, ProcessSignal};
+use crate::regex::Regexes;
+use crate::utils::sudo::SudoState;
+use crate::{constants::*, macros::*};
+use axum::extract::{Path, State as ApiState};
+use axum::http::{header::AUTHORIZATION, HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use log::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Clone)]
+pub struct HttpApi {
+    helper: Arc<Mutex<Helper>>,
+    state: Arc<Mutex<State>>, // The saved state [og], used to start processes.
+    sudo: Arc<Mutex<SudoState>>,
+    ping: Arc<Mutex<Ping>>,
+    node_path: PathBuf, // The saved manual node list, for the backup hosts of P2Pool.
+    token: Arc<str>,
+}
+
+impl HttpApi {
+    pub fn new(
+        helper: &Arc<Mutex<Helper>>,
+        state: &Arc<Mutex<State>>,
+        sudo: &Arc<Mutex<SudoState>>,
+        ping: &Arc<Mutex<Ping>>,
+        node_path: &std::path::Path,
+        token: &str,
+    ) -> Self {
+        Self {
+            helper: Arc::clone(helper),
+            state: Arc::clone(state),
+            sudo: Arc::clone(sudo),
+            ping: Arc::clone(ping),
+            node_path: node_path.to_path_buf(),
+            token: Arc::from(token),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Spawns the server in its own thread, listening on the address set in [Gupax].
+    pub fn spawn(self, gupax: &Gupax) {
+        let addr = format!("{}:{}", gupax.http_api_ip, gupax.http_api_port);
+        info!("HTTP API | Spawning server on [{}]...", addr);
+        thread::spawn(move || self.serve(addr));
+    }
+
+    #[tokio::main]
+    async fn serve(self, addr: String) {
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                error!("HTTP API | Could not bind [{}] ... FAIL ... {}", addr, e);
+                return;
+            }
+        };
+        info!("HTTP API | Listening on [{}] ... OK", addr);
+        if let Err(e) = axum::serve(listener, self.router()).await {
+            error!("HTTP API | Server stopped ... FAIL ... {}", e);
+        }
+    }
+
+    pub(crate) fn router(self) -> Router {
+        Router::new()
+            .route("/api/status", get(get_status))
+            .route("/api/:process", get(get_process))
+            .route("/api/:process/:action", post(post_signal))
+            .with_state(self)
+    }
+
+    // Check the [Authorization] header against our token.
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        if self.token.is_empty() {
+            return false;
+        }
+        match headers.get(AUTHORIZATION).and_then(|h| h.to_str().ok()) {
+            Some(auth) => auth
+                .strip_prefix("Bearer ")
+                .is_some_and(|token| same_token(token, &self.token)),
+            None => false,
+        }
+    }
+}
+
+// Compare the hashes of the tokens without stopping at the first difference,
+// so the time taken tells nothing about the token, not even its length.
+pub(crate) fn same_token(a: &str, b: &str) -> bool {
+    let (a, b) = (Sha256::digest(a), Sha256::digest(b));
+    a.iter()
+        .zip(b.iter())
+        .fold(0, |diff, (x, y)| diff | (x ^ y))
+        == 0
+}
+
+//---------------------------------------------------------------------------------------------------- JSON
+#[derive(Debug, Serialize)]
+pub struct ApiStatus {
+    pub gupaxx: GupaxxStatus,
+    pub p2pool: P2poolStatus,
+    pub xmrig: XmrigStatus,
+    pub xmrig_proxy: XmrigProxyStatus,
+    pub xvb: XvbStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GupaxxStatus {
+    pub version: &'static str,
+    pub uptime: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct P2poolStatus {
+    pub state: String,
+    pub uptime: u64,
+    pub hashrate_15m: u64,
+    pub hashrate_1h: u64,
+    pub hashrate_24h: u64,
+    pub shares_found: Option<u64>,
+    pub average_effort: f32,
+    pub current_effort: f32,
+    pub connections: u32,
+    pub sidechain_shares: u32,
+    pub sidechain_ehr: f32,
+    pub payouts: u128,
+    pub xmr: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct XmrigStatus {
+    pub state: String,
+    pub uptime: u64,
+    pub hashrate_10s: f32,
+    pub hashrate_1m: f32,
+    pub hashrate_15m: f32,
+    pub accepted: u128,
+    pub rejected: u128,
+    pub node: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct XmrigProxyStatus {
+    pub state: String,
+    pub uptime: u64,
+    pub hashrate_1m: f32,
+    pub hashrate_10m: f32,
+    pub hashrate_1h: f32,
+    pub hashrate_12h: f32,
+    pub hashrate_24h: f32,
+    pub accepted: u32,
+    pub rejected: u32,
+    pub node: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct XvbStatus {
+    pub state: String,
+    pub current_node: Option<String>,
+    pub round_type: Option<String>,
+    pub donor_1hr_avg: f32,
+    pub donor_24hr_avg: f32,
+    pub hero_mode: bool,
+    pub mode: String,
+    // Seconds before the algorithm switches to the other side.
+    pub time_switch_node: u32,
+    // Seconds given to XvB by the last decision of the algorithm.
+    pub time_donated: u32,
+    pub msg_indicator: String,
+    pub fails: u8,
+}
+
+impl ApiStatus {
+    // Takes a snapshot of the [gui_api_*] of the [Helper].
+    // Every [Arc] is cloned first so only one lock is held at a time.
+    pub fn from_helper(helper: &Arc<Mutex<Helper>>) -> Self {
+        let h = lock!(helper);
+        let uptime = h.instant.elapsed().as_secs();
+        let (p2pool, xmrig, xmrig_proxy, xvb) = (
+            Arc::clone(&h.p2pool),
+            Arc::clone(&h.xmrig),
+            Arc::clone(&h.xmrig_proxy),
+            Arc::clone(&h.xvb),
+        );
+        let (api_p2pool, api_xmrig, api_xp, api_xvb) = (
+            Arc::clone(&h.gui_api_p2pool),
+            Arc::clone(&h.gui_api_xmrig),
+            Arc::clone(&h.gui_api_xp),
+            Arc::clone(&h.gui_api_xvb),
+        );
+        drop(h);
+        let p2pool = {
+            let state = lock!(p2pool).state.to_string();
+            let api = lock!(api_p2pool);
+            P2poolStatus {
+                state,
+                uptime: api.uptime.as_secs(),
+                hashrate_15m: api.hashrate_15m_u64,
+                hashrate_1h: api.user_p2pool_hashrate_u64,
+                hashrate_24h: api.hashrate_24h_u64,
+                shares_found: api.shares_found,
+                average_effort: api.average_effort_f32,
+                current_effort: api.current_effort_f32,
+                connections: api.connections_u32,
+                sidechain_shares: api.sidechain_shares,
+                sidechain_ehr: api.sidechain_ehr,
+                payouts: api.payouts,
+                xmr: api.xmr,
+            }
+        };
+        let xmrig = {
+            let state = lock!(xmrig).state.to_string();
+            let api = lock!(api_xmrig);
+            XmrigStatus {
+                state,
+                uptime: api.uptime.as_secs(),
+                hashrate_10s: api.hashrate_raw,
+                hashrate_1m: api.hashrate_raw_1m,
+                hashrate_15m: api.hashrate_raw_15m,
+                accepted: api.accepted_raw,
+                rejected: api.rejected_raw,
+                node: api.node.clone(),
+            }
+        };
+        let xmrig_proxy = {
+            let state = lock!(xmrig_proxy).state.to_string();
+            let api = lock!(api_xp);
+            XmrigProxyStatus {
+                state,
+                uptime: api.uptime.as_secs(),
+                hashrate_1m: api.hashrate_1m,
+                hashrate_10m: api.hashrate_10m,
+                hashrate_1h: api.hashrate_1h,
+                hashrate_12h: api.hashrate_12h,
+                hashrate_24h: api.hashrate_24h,
+                accepted: api.accepted,
+                rejected: api.rejected,
+                node: api.node.clone(),
+            }
+        };
+        let xvb = {
+            let state = lock!(xvb).state.to_string();
+            let api = lock!(api_xvb);
+            XvbStatus {
+                state,
+                current_node: api.current_node.as_ref().map(|n| n.to_string()),
+                round_type: api
+                    .stats_priv
+                    .round_participate
+                    .as_ref()
+                    .map(|r| r.to_string()),
+                donor_1hr_avg: api.stats_priv.donor_1hr_avg,
+                donor_24hr_avg: api.stats_priv.donor_24hr_avg,
+                hero_mode: api.stats_priv.runtime_mode == XvbMode::Hero,
+                mode: api.stats_priv.runtime_mode.to_string(),
+                time_switch_node: api.stats_priv.time_switch_node,
+                time_donated: api.time_donated,
+                msg_indicator: api.stats_priv.msg_indicator.clone(),
+                fails: api.stats_priv.fails,
+            }
+        };
+        Self {
+            gupaxx: GupaxxStatus {
+                version: GUPAX_VERSION,
+                uptime,
+            },
+            p2pool,
+            xmrig,
+            xmrig_proxy,
+            xvb,
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Handlers
+async fn get_status(ApiState(api): ApiState<HttpApi>) -> Json<ApiStatus> {
+    Json(ApiStatus::from_helper(&api.helper))
+}
+
+async fn get_process(
+    ApiState(api): ApiState<HttpApi>,
+    Path(process): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let status = ApiStatus::from_helper(&api.helper);
+    let value = match process.as_str() {
+        "p2pool" => serde_json::to_value(status.p2pool),
+        "xmrig" => serde_json::to_value(status.xmrig),
+        "xmrig_proxy" => serde_json::to_value(status.xmrig_proxy),
+        "xvb" => serde_json::to_value(status.xvb),
+        _ => return Err(StatusCode::NOT_FOUND),
+    };
+    value
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn post_signal(
+    ApiState(api): ApiState<HttpApi>,
+    Path((process, action)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> (StatusCode, String) {
+    if !api.authorized(&headers) {
+        warn!("HTTP API | Unauthorized request: [{}/{}]", process, action);
+        return (StatusCode::UNAUTHORIZED, "Invalid token".to_string());
+    }
+    let signal = match action.as_str() {
+        "start" => ProcessSignal::Start,
+        "stop" => ProcessSignal::Stop,
+        "restart" => ProcessSignal::Restart,
+        _ => return (StatusCode::NOT_FOUND, format!("Unknown action: {}", action)),
+    };
+    let h = lock!(api.helper);
+    let target = match process.as_str() {
+        "p2pool" => Arc::clone(&h.p2pool),
+        "xmrig" => Arc::clone(&h.xmrig),
+        "xmrig_proxy" => Arc::clone(&h.xmrig_proxy),
+        "xvb" => Arc::clone(&h.xvb),
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("Unknown process: {}", process),
+            )
+        }
+    };
+    drop(h);
+    info!("HTTP API | Received [{}/{}]", process, action);
+    match api.send_signal(&process, &target, signal) {
+        Ok(()) => (StatusCode::OK, format!("{} {} ... OK", process, action)),
+        Err((code, msg)) => {
+            warn!("HTTP API | [{}/{}] ... FAIL ... {}", process, action, msg);
+            (code, msg.to_string())
+        }
+    }
+}
+
+impl HttpApi {
+    // Does what the buttons of the bottom panel do, with the saved state.
+    fn send_signal(
+        &self,
+        name: &str,
+        process: &Arc<Mutex<Process>>,
+        signal: ProcessSignal,
+    ) -> Result<(), (StatusCode, &'static str)> {
+        let (alive, waiting) = {
+            let p = lock!(process);
+            (p.is_alive(), p.is_waiting())
+        };
+        if waiting {
+            return Err((StatusCode::CONFLICT, "Process is busy, try again later"));
+        }
+        match signal {
+            ProcessSignal::Start if alive => {
+                return Err((StatusCode::CONFLICT, "Process is already running"))
+            }
+            ProcessSignal::Stop | ProcessSignal::Restart if !alive => {
+                return Err((StatusCode::CONFLICT, "Process is not running"))
+            }
+            _ => (),
+        }
+        let mut state = lock!(self.state);
+        let _ = state.update_absolute_path();
+        let state = state.clone();
+        let helper = &self.helper;
+        match (name, &signal) {
+            ("p2pool", ProcessSignal::Stop) => Helper::stop_p2pool(helper),
+            ("xmrig", ProcessSignal::Stop) => Helper::stop_xmrig(helper),
+            ("xmrig_proxy", ProcessSignal::Stop) => Helper::stop_xp(helper),
+            ("xvb", ProcessSignal::Stop) => Helper::stop_xvb(helper),
+            ("p2pool", _) => {
+                if !Regexes::addr_ok(&state.p2pool.address) {
+                    return Err((StatusCode::BAD_REQUEST, P2POOL_ADDRESS));
+                } else if !check_p2pool_path(&state.gupax.p2pool_path) {
+                    return Err((StatusCode::BAD_REQUEST, P2POOL_PATH_NOT_VALID));
+                }
+                let path = &state.gupax.absolute_p2pool_path;
+                let backup_hosts =
+                    Ping::saved_backup_hosts(&self.ping, &state.p2pool, &self.node_path);
+                if signal == ProcessSignal::Restart {
+                    Helper::restart_p2pool(helper, &state.p2pool, path, backup_hosts);
+                } else {
+                    Helper::start_p2pool(helper, &state.p2pool, path, backup_hosts);
+                }
+            }
+            ("xmrig", _) => {
+                if !check_xmrig_path(&state.gupax.xmrig_path) {
+                    return Err((StatusCode::BAD_REQUEST, XMRIG_PATH_NOT_VALID));
+                }
+                let (path, sudo) = (&state.gupax.absolute_xmrig_path, Arc::clone(&self.sudo));
+                if signal == ProcessSignal::Restart {
+                    Helper::restart_xmrig(helper, &state.xmrig, path, sudo);
+                } else {
+                    Helper::start_xmrig(helper, &state.xmrig, path, sudo);
+                }
+            }
+            ("xmrig_proxy", _) => {
+                if !check_xp_path(&state.gupax.xmrig_proxy_path) {
+                    return Err((StatusCode::BAD_REQUEST, XMRIG_PROXY_PATH_NOT_VALID));
+                }
+                let path = &state.gupax.absolute_xp_path;
+                if signal == ProcessSignal::Restart {
+                    Helper::restart_xp(helper, &state.xmrig_proxy, &state.xmrig, path);
+                } else {
+                    Helper::start_xp(helper, &state.xmrig_proxy, &state.xmrig, path);
+                }
+            }
+            ("xvb", _) => {
+                if signal == ProcessSignal::Restart {
+                    Helper::restart_xvb(
+                        helper,
+                        &state.xvb,
+                        &state.p2pool,
+                        &state.xmrig,
+                        &state.xmrig_proxy,
+                    );
+                } else {
+                    Helper::start_xvb(
+                        helper,
+                        &state.xvb,
+                        &state.p2pool,
+                        &state.xmrig,
+                        &state.xmrig_proxy,
+                    );
+                }
+            }
+            _ => return Err((StatusCode::NOT_FOUND, "Unknown process")),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+// Prometheus exporter.
+// Optional server (enabled in the [Gupaxx] tab) exposing the same snapshot as
+// the local HTTP API plus [Sys], in the Prometheus text format on [/metrics].
+// Hashrates are in H/s, memory in bytes, everything is prefixed by [gupaxx_].
+
+use crate::disk::state::Gupax;
+use crate::helper::http_api::ApiStatus;
+use crate::helper::Helper;
+use crate::macros::*;
+use axum::extract::State as ApiState;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use log::*;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// Version 0.0.4 of the text exposition format.
+const CONTENT_TYPE_METRICS: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Clone)]
+pub struct Metrics {
+    helper: Arc<Mutex<Helper>>,
+}
+
+impl Metrics {
+    pub fn new(helper: &Arc<Mutex<Helper>>) -> Self {
+        Self {
+            helper: Arc::clone(helper),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Spawns the server in its own thread, listening on the address set in [Gupax].
+    pub fn spawn(self, gupax: &Gupax) {
+        let addr = format!("{}:{}", gupax.metrics_ip, gupax.metrics_port);
+        info!("Metrics | Spawning server on [{}]...", addr);
+        thread::spawn(move || self.serve(addr));
+    }
+
+    #[tokio::main]
+    async fn serve(self, addr: String) {
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                error!("Metrics | Could not bind [{}] ... FAIL ... {}", addr, e);
+                return;
+            }
+        };
+        info!("Metrics | Listening on [{}] ... OK", addr);
+        if let Err(e) = axum::serve(listener, self.router()).await {
+            error!("Metrics | Server stopped ... FAIL ... {}", e);
+        }
+    }
+
+    pub(crate) fn router(self) -> Router {
+        Router::new()
+            .route("/metrics", get(get_metrics))
+            .with_state(self)
+    }
+}
+
+async fn get_metrics(ApiState(metrics): ApiState<Metrics>) -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, CONTENT_TYPE_METRICS)],
+        render(&metrics.helper),
+    )
+}
+
+//---------------------------------------------------------------------------------------------------- Rendering
+// Renders every metric from the [gui_api_*] and [pub_sys] of the [Helper].
+pub fn render(helper: &Arc<Mutex<Helper>>) -> String {
+    let status = ApiStatus::from_helper(helper);
+    let pub_sys = Arc::clone(&lock!(helper).pub_sys);
+    let sys = lock!(pub_sys).clone();
+    let mut out = Exposition::default();
+
+    // Gupaxx
+    let version = format!("version=\"{}\"", status.gupaxx.version);
+    out.gauge("info", "Version of Gupaxx", &[(&version, 1.0)]);
+    out.gauge(
+        "uptime_seconds",
+        "Uptime of Gupaxx",
+        &[("", status.gupaxx.uptime as f64)],
+    );
+    out.gauge(
+        "cpu_usage_percent",
+        "CPU usage of Gupaxx",
+        &[("", sys.gupax_cpu_usage_f32.into())],
+    );
+    out.gauge(
+        "memory_used_bytes",
+        "Memory used by Gupaxx",
+        &[("", sys.gupax_memory_used_u64 as f64)],
+    );
+    out.gauge(
+        "system_cpu_usage_percent",
+        "CPU usage of the system",
+        &[("", sys.system_cpu_usage_f32.into())],
+    );
+    out.gauge(
+        "system_memory_used_bytes",
+        "Memory used on the system",
+        &[("", sys.system_memory_used_u64 as f64)],
+    );
+    out.gauge(
+        "system_memory_total_bytes",
+        "Total memory of the system",
+        &[("", sys.system_memory_total_u64 as f64)],
+    );
+
+    // Processes
+    let states = [
+        ("p2pool", &status.p2pool.state),
+        ("xmrig", &status.xmrig.state),
+        ("xmrig_proxy", &status.xmrig_proxy.state),
+        ("xvb", &status.xvb.state),
+    ]
+    .map(|(p, s)| format!("process=\"{p}\",state=\"{s}\""));
+    out.gauge(
+        "process_state",
+        "Current state of each process",
+        &states.each_ref().map(|l| (l.as_str(), 1.0)),
+    );
+    out.gauge(
+        "process_uptime_seconds",
+        "Uptime of each process",
+        &[
+            ("process=\"p2pool\"", status.p2pool.uptime as f64),
+            ("process=\"xmrig\"", status.xmrig.uptime as f64),
+            ("process=\"xmrig_proxy\"", status.xmrig_proxy.uptime as f64),
+        ],
+    );
+
+    // P2Pool
+    let p2pool = &status.p2pool;
+    out.gauge(
+        "p2pool_hashrate",
+        "Hashrate of the P2Pool node",
+        &[
+            ("window=\"15m\"", p2pool.hashrate_15m as f64),
+            ("window=\"1h\"", p2pool.hashrate_1h as f64),
+            ("window=\"24h\"", p2pool.hashrate_24h as f64),
+        ],
+    );
+    if let Some(shares) = p2pool.shares_found {
+        out.counter(
+            "p2pool_shares_found_total",
+            "Shares found by the P2Pool node",
+            &[("", shares as f64)],
+        );
+    }
+    out.gauge(
+        "p2pool_average_effort_percent",
+        "Average effort of P2Pool",
+        &[("", p2pool.average_effort.into())],
+    );
+    out.gauge(
+        "p2pool_current_effort_percent",
+        "Current effort of P2Pool",
+        &[("", p2pool.current_effort.into())],
+    );
+    out.gauge(
+        "p2pool_connections",
+        "Connections of the P2Pool node",
+        &[("", p2pool.connections.into())],
+    );
+    out.gauge(
+        "p2pool_sidechain_shares",
+        "Shares of the address in the PPLNS window",
+        &[("", p2pool.sidechain_shares.into())],
+    );
+    out.gauge(
+        "p2pool_sidechain_hashrate",
+        "Estimated hashrate of the address on the sidechain",
+        &[("", p2pool.sidechain_ehr.into())],
+    );
+    out.counter(
+        "p2pool_payouts_total",
+        "Payouts received since P2Pool started",
+        &[("", p2pool.payouts as f64)],
+    );
+    out.counter(
+        "p2pool_xmr_total",
+        "XMR received since P2Pool started",
+        &[("", p2pool.xmr)],
+    );
+
+    // XMRig
+    let xmrig = &status.xmrig;
+    out.gauge(
+        "xmrig_hashrate",
+        "Hashrate of XMRig",
+        &[
+            ("window=\"10s\"", xmrig.hashrate_10s.into()),
+            ("window=\"1m\"", xmrig.hashrate_1m.into()),
+            ("window=\"15m\"", xmrig.hashrate_15m.into()),
+        ],
+    );
+    out.counter(
+        "xmrig_accepted_shares_total",
+        "Shares accepted by the pool of XMRig",
+        &[("", xmrig.accepted as f64)],
+    );
+    out.counter(
+        "xmrig_rejected_shares_total",
+        "Shares rejected by the pool of XMRig",
+        &[("", xmrig.rejected as f64)],
+    );
+
+    // XMRig-Proxy
+    let xp = &status.xmrig_proxy;
+    out.gauge(
+        "xmrig_proxy_hashrate",
+        "Hashrate of XMRig-Proxy",
+        &[
+            ("window=\"1m\"", xp.hashrate_1m.into()),
+            ("window=\"10m\"", xp.hashrate_10m.into()),
+            ("window=\"1h\"", xp.hashrate_1h.into()),
+            ("window=\"12h\"", xp.hashrate_12h.into()),
+            ("window=\"24h\"", xp.hashrate_24h.into()),
+        ],
+    );
+    out.counter(
+        "xmrig_proxy_accepted_shares_total",
+        "Shares accepted by the pool of XMRig-Proxy",
+        &[("", xp.accepted.into())],
+    );
+    out.counter(
+        "xmrig_proxy_rejected_shares_total",
+        "Shares rejected by the pool of XMRig-Proxy",
+        &[("", xp.rejected.into())],
+    );
+
+    // XvB, the API gives the averages in kH/s.
+    let xvb = &status.xvb;
+    out.gauge(
+        "xvb_donor_hashrate",
+        "Average hashrate of the address on XvB",
+        &[
+            ("window=\"1h\"", f64::from(xvb.donor_1hr_avg) * 1000.0),
+            ("window=\"24h\"", f64::from(xvb.donor_24hr_avg) * 1000.0),
+        ],
+    );
+    out.gauge(
+        "xvb_time_donated_seconds",
+        "Seconds given to XvB by the last decision of the algorithm",
+        &[("", xvb.time_donated.into())],
+    );
+    out.gauge(
+        "xvb_hero_mode",
+        "Whether hero mode is enabled",
+        &[("", u8::from(xvb.hero_mode).into())],
+    );
+    out.gauge(
+        "xvb_fails",
+        "Failed requests to the XvB API",
+        &[("", xvb.fails.into())],
+    );
+
+    out.0
+}
+
+// The text exposition format, one family after the other.
+#[derive(Default)]
+struct Exposition(String);
+
+impl Exposition {
+    fn gauge(&mut self, name: &str, help: &str, samples: &[(&str, f64)]) {
+        self.family(name, "gauge", help, samples);
+    }
+
+    fn counter(&mut self, name: &str, help: &str, samples: &[(&str, f64)]) {
+        self.family(name, "counter", help, samples);
+    }
+
+    // Labels are already formatted, e.g: [window="1h"], or empty.
+    fn family(&mut self, name: &str, kind: &str, help: &str, samples: &[(&str, f64)]) {
+        let _ = writeln!(self.0, "# HELP gupaxx_{name} {help}");
+        let _ = writeln!(self.0, "# TYPE gupaxx_{name} {kind}");
+        for (labels, value) in samples {
+            if labels.is_empty() {
+                let _ = writeln!(self.0, "gupaxx_{name} {value}");
+            } else {
+                let _ = writeln!(self.0, "gupaxx_{name}{{{labels}}} {value}");
+            }
+        }
+    }
+}
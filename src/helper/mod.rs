@@ -34,6 +34,8 @@
 // piping their stdout/stderr/stdin, accessing their APIs (HTTP + disk files), etc.
 
 //---------------------------------------------------------------------------------------------------- Import
+use crate::disk::consts::{HISTORY_COMPACT_INTERVAL, HISTORY_SAMPLE_INTERVAL};
+use crate::disk::history::{unix_now, History, Sample};
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
 use crate::helper::{
     p2pool::{ImgP2pool, PubP2poolApi},
@@ -52,8 +54,14 @@ use std::{
     time::*,
 };
 
-use self::xvb::{nodes::XvbNode, PubXvbApi};
+use self::xvb::{nodes::XvbNode, split::SplitXmrig, PubXvbApi};
+pub mod args;
+pub mod http_api;
+pub mod metrics;
+pub mod node_health;
 pub mod p2pool;
+pub mod p2pool_console;
+pub mod supervisor;
 pub mod tests;
 pub mod xrig;
 pub mod xvb;
@@ -90,6 +98,8 @@ pub struct Helper {
     pub_api_xp: Arc<Mutex<PubXmrigProxyApi>>, // XMRig-Proxy API state (for Helper/XMRig-Proxy thread)
     pub_api_xvb: Arc<Mutex<PubXvbApi>>,       // XvB API state (for Helper/XvB thread)
     pub gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>, //
+    pub history: Arc<Mutex<History>>, // Persistent stats, appended every [HISTORY_SAMPLE_INTERVAL]
+    pub split_xmrig: Arc<Mutex<SplitXmrig>>, // Second XMRig of the thread split of XvB
 }
 
 // The communication between the data here and the GUI thread goes as follows:
@@ -109,6 +119,12 @@ pub struct Sys {
     pub system_cpu_model: String,
     pub system_memory: String,
     pub system_cpu_usage: String,
+    // Raw values of the above, memory in bytes.
+    pub gupax_cpu_usage_f32: f32,
+    pub gupax_memory_used_u64: u64,
+    pub system_cpu_usage_f32: f32,
+    pub system_memory_used_u64: u64,
+    pub system_memory_total_u64: u64,
 }
 
 impl Sys {
@@ -120,6 +136,11 @@ impl Sys {
             system_cpu_usage: "???%".to_string(),
             system_memory: "???GB / ???GB".to_string(),
             system_cpu_model: "???".to_string(),
+            gupax_cpu_usage_f32: 0.0,
+            gupax_memory_used_u64: 0,
+            system_cpu_usage_f32: 0.0,
+            system_memory_used_u64: 0,
+            system_memory_total_u64: 0,
         }
     }
 }
@@ -167,6 +188,10 @@ pub struct Process {
 
     // Start time of process.
     start: std::time::Instant,
+
+    // Exit code of the last crash, [None] if it was stopped by the user
+    // or is running. Used by the [Supervisor] to only restart crashes.
+    pub exit_code: Option<u32>,
 }
 
 //---------------------------------------------------------------------------------------------------- [Process] Impl
@@ -177,6 +202,7 @@ impl Process {
             state: ProcessState::Dead,
             signal: ProcessSignal::None,
             start: Instant::now(),
+            exit_code: None,
             //			stdin: Option::None,
             //			child: Option::None,
             output_parse: arc_mut!(String::with_capacity(500)),
@@ -230,7 +256,7 @@ impl Default for ProcessState {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum ProcessSignal {
     None,
     Start,
@@ -313,6 +339,8 @@ impl Helper {
             img_p2pool,
             img_xmrig,
             gupax_p2pool_api,
+            history: arc_mut!(History::new()),
+            split_xmrig: arc_mut!(SplitXmrig::default()),
         }
     }
 
@@ -361,26 +389,28 @@ impl Helper {
     ) {
         let gupax_uptime = helper.uptime.to_string();
         let cpu = &sysinfo.cpus()[0];
-        let gupax_cpu_usage = format!(
-            "{:.2}%",
-            sysinfo.process(*pid).unwrap().cpu_usage() / (max_threads as f32)
-        );
-        let gupax_memory_used_mb =
-            HumanNumber::from_u64(sysinfo.process(*pid).unwrap().memory() / 1_000_000);
+        let process = sysinfo.process(*pid).unwrap();
+        let gupax_cpu_usage_f32 = process.cpu_usage() / (max_threads as f32);
+        let gupax_cpu_usage = format!("{:.2}%", gupax_cpu_usage_f32);
+        let gupax_memory_used_u64 = process.memory();
+        let gupax_memory_used_mb = HumanNumber::from_u64(gupax_memory_used_u64 / 1_000_000);
         let gupax_memory_used_mb = format!("{} megabytes", gupax_memory_used_mb);
         let system_cpu_model = format!("{} ({}MHz)", cpu.brand(), cpu.frequency());
+        let system_memory_used_u64 = sysinfo.used_memory();
+        let system_memory_total_u64 = sysinfo.total_memory();
         let system_memory = {
-            let used = (sysinfo.used_memory() as f64) / 1_000_000_000.0;
-            let total = (sysinfo.total_memory() as f64) / 1_000_000_000.0;
+            let used = (system_memory_used_u64 as f64) / 1_000_000_000.0;
+            let total = (system_memory_total_u64 as f64) / 1_000_000_000.0;
             format!("{:.3} GB / {:.3} GB", used, total)
         };
-        let system_cpu_usage = {
+        let system_cpu_usage_f32 = {
             let mut total: f32 = 0.0;
             for cpu in sysinfo.cpus() {
                 total += cpu.cpu_usage();
             }
-            format!("{:.2}%", total / (max_threads as f32))
+            total / (max_threads as f32)
         };
+        let system_cpu_usage = format!("{:.2}%", system_cpu_usage_f32);
         *pub_sys = Sys {
             gupax_uptime,
             gupax_cpu_usage,
@@ -388,6 +418,11 @@ impl Helper {
             system_cpu_usage,
             system_memory,
             system_cpu_model,
+            gupax_cpu_usage_f32,
+            gupax_memory_used_u64,
+            system_cpu_usage_f32,
+            system_memory_used_u64,
+            system_memory_total_u64,
         };
     }
 
@@ -432,6 +467,7 @@ impl Helper {
         let pub_api_xmrig = Arc::clone(&lock.pub_api_xmrig);
         let pub_api_xp = Arc::clone(&lock.pub_api_xp);
         let pub_api_xvb = Arc::clone(&lock.pub_api_xvb);
+        let history = Arc::clone(&lock.history);
         drop(lock);
 
         let sysinfo_cpu = sysinfo::CpuRefreshKind::everything();
@@ -439,6 +475,8 @@ impl Helper {
 
         thread::spawn(move || {
             info!("Helper | Hello from helper thread! Entering loop where I will spend the rest of my days...");
+            let mut last_sample = Instant::now();
+            let mut last_compact = Instant::now();
             // Begin loop
             loop {
                 // 1. Loop init timestamp
@@ -525,6 +563,21 @@ impl Helper {
                     max_threads,
                 );
 
+                // Take a [Sample] for the [History] if something is running.
+                let sample = if last_sample.elapsed().as_secs() >= HISTORY_SAMPLE_INTERVAL
+                    && (p2pool.is_alive() || xmrig.is_alive() || xmrig_proxy.is_alive())
+                {
+                    last_sample = Instant::now();
+                    Some(Sample::new(
+                        unix_now(),
+                        &gui_api_xmrig,
+                        &gui_api_xp,
+                        &gui_api_p2pool,
+                    ))
+                } else {
+                    None
+                };
+
                 // 3. Drop... (almost) EVERYTHING... IN REVERSE!
                 drop(lock_pub_sys);
                 debug!("Helper | Unlocking (1/12) ... [pub_sys]");
@@ -555,7 +608,20 @@ impl Helper {
                 drop(lock);
                 debug!("Helper | Unlocking (12/12) ... [helper]");
 
-                // 4. Calculate if we should sleep or not.
+                // 4. Write the [History] to disk, without holding any other lock.
+                if let Some(sample) = sample {
+                    if let Err(e) = lock!(history).push_sample(sample) {
+                        warn!("Helper | History append ... FAIL ... {}", e);
+                    }
+                }
+                if last_compact.elapsed().as_secs() >= HISTORY_COMPACT_INTERVAL {
+                    last_compact = Instant::now();
+                    if let Err(e) = lock!(history).compact(unix_now()) {
+                        warn!("Helper | History compact ... FAIL ... {}", e);
+                    }
+                }
+
+                // 5. Calculate if we should sleep or not.
                 // If we should sleep, how long?
                 let elapsed = start.elapsed().as_millis();
                 if elapsed < 1000 {
@@ -568,7 +634,7 @@ impl Helper {
                     debug!("Helper | END OF LOOP - Not sleeping!");
                 }
 
-                // 5. End loop
+                // 6. End loop
             }
         });
     }
@@ -594,18 +660,27 @@ fn check_died(
             }
             false => {
                 process.state = ProcessState::Failed;
+                process.exit_code = Some(code.exit_code());
                 "Failed"
             }
         };
         let uptime = Uptime::from(start.elapsed());
         info!(
-            "{} | Stopped ... Uptime was: [{}], Exit status: [{}]",
-            process.name, uptime, exit_status
+            "{} | Stopped ... Uptime was: [{}], Exit status: [{}], Exit code: [{}]",
+            process.name,
+            uptime,
+            exit_status,
+            code.exit_code()
         );
         if let Err(e) = writeln!(
             *gui_api_output_raw,
-            "{}\n{} stopped | Uptime: [{}] | Exit status: [{}]\n{}\n\n\n\n",
-            process.name, HORI_CONSOLE, uptime, exit_status, HORI_CONSOLE
+            "{}\n{} stopped | Uptime: [{}] | Exit status: [{}] | Exit code: [{}]\n{}\n\n\n\n",
+            process.name,
+            HORI_CONSOLE,
+            uptime,
+            exit_status,
+            code.exit_code(),
+            HORI_CONSOLE
         ) {
             error!(
                 "{} Watchdog | GUI Uptime/Exit status write failed: {}",
@@ -0,0 +1,318 @@
+// Node health monitor.
+// While P2Pool is running with [failover] enabled, the Monero node it uses is probed
+// with the same [get_info] JSON-RPC as [Ping], along with the other nodes it could
+// switch to: the [REMOTE_NODES] in [Simple], the saved manual node list in [Advanced].
+//
+// The active node is unhealthy if it does not answer, is not [synchronized], is more
+// than [NODE_HEALTH_MAX_LAG] blocks behind the highest node, or its height did not
+// move for [NODE_HEALTH_STALL] seconds. After [NODE_HEALTH_MAX_FAILS] unhealthy checks
+// in a row, P2Pool is restarted on the next healthy node of the list.
+//
+// The saved state is not modified, the switch only lasts until P2Pool is restarted.
+
+use crate::components::node::{GetInfo, REMOTE_NODES};
+use crate::components::update::get_user_agent;
+use crate::disk::node::Node;
+use crate::disk::state::{P2pool, State};
+use crate::helper::{Helper, ProcessName};
+use crate::macros::*;
+use crate::miscs::output_console;
+use log::*;
+use reqwest::Client;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Seconds between two checks.
+pub const NODE_HEALTH_INTERVAL: u64 = 60;
+// Blocks the active node can be behind the highest node.
+pub const NODE_HEALTH_MAX_LAG: u64 = 3;
+// Seconds without a new block before the active node is considered stalled.
+// Blocks are every 2 minutes on average, 20 minutes without one is very unlikely.
+pub const NODE_HEALTH_STALL: u64 = 1200;
+// Unhealthy checks in a row before switching.
+pub const NODE_HEALTH_MAX_FAILS: u8 = 2;
+
+pub struct NodeHealth {
+    helper: Arc<Mutex<Helper>>,
+    state: Arc<Mutex<State>>, // The saved state [og], used to restart P2Pool.
+    node_path: PathBuf,       // The saved manual node list, read at every check.
+}
+
+// What a node answered to [get_info], [None] if it did not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Probe {
+    pub synchronized: bool,
+    pub height: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Healthy,
+    Unreachable,
+    NotSynchronized,
+    Behind(u64),
+    Stalled,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Healthy => write!(f, "healthy"),
+            Self::Unreachable => write!(f, "unreachable"),
+            Self::NotSynchronized => write!(f, "not synchronized"),
+            Self::Behind(lag) => write!(f, "[{}] blocks behind", lag),
+            Self::Stalled => write!(f, "stalled"),
+        }
+    }
+}
+
+// What the monitor remembers about the active node.
+#[derive(Debug, Default)]
+pub struct Tracker {
+    pub node: Option<Node>,     // The node the values below are about
+    pub height: u64,            // Last height seen
+    pub since: Option<Instant>, // When [height] was first seen
+    pub fails: u8,              // Unhealthy checks in a row
+}
+
+impl Tracker {
+    // Judge the active node, [best] is the highest height of all nodes.
+    pub fn check(&mut self, node: &Node, probe: Option<Probe>, best: u64, now: Instant) -> Verdict {
+        if self.node.as_ref() != Some(node) {
+            *self = Self {
+                node: Some(node.clone()),
+                ..Default::default()
+            };
+        }
+        let verdict = match probe {
+            None => Verdict::Unreachable,
+            Some(p) if !p.synchronized => Verdict::NotSynchronized,
+            Some(p) if best.saturating_sub(p.height) > NODE_HEALTH_MAX_LAG => {
+                Verdict::Behind(best - p.height)
+            }
+            Some(p) => {
+                if p.height != self.height || self.since.is_none() {
+                    self.height = p.height;
+                    self.since = Some(now);
+                }
+                if self
+                    .since
+                    .is_some_and(|s| now.duration_since(s).as_secs() >= NODE_HEALTH_STALL)
+                {
+                    Verdict::Stalled
+                } else {
+                    Verdict::Healthy
+                }
+            }
+        };
+        if verdict == Verdict::Healthy {
+            self.fails = 0;
+        } else {
+            self.fails = self.fails.saturating_add(1);
+        }
+        verdict
+    }
+}
+
+// Index of the first healthy node after [active], wrapping around.
+pub fn next_healthy(active: Option<usize>, probes: &[Option<Probe>], best: u64) -> Option<usize> {
+    let len = probes.len();
+    let start = active.map_or(0, |i| i + 1);
+    (0..len).map(|i| (start + i) % len).find(|&i| {
+        Some(i) != active
+            && probes[i].is_some_and(|p| {
+                p.synchronized && best.saturating_sub(p.height) <= NODE_HEALTH_MAX_LAG
+            })
+    })
+}
+
+// The same node, [localhost] and [127.0.0.1] are the same thing.
+fn same_node(a: &Node, b: &Node) -> bool {
+    let ip = |ip: &str| {
+        if ip == "localhost" {
+            "127.0.0.1".to_string()
+        } else {
+            ip.to_string()
+        }
+    };
+    ip(&a.ip) == ip(&b.ip) && a.rpc == b.rpc
+}
+
+impl NodeHealth {
+    pub fn new(helper: &Arc<Mutex<Helper>>, state: &Arc<Mutex<State>>, node_path: &Path) -> Self {
+        Self {
+            helper: Arc::clone(helper),
+            state: Arc::clone(state),
+            node_path: node_path.to_path_buf(),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub fn spawn(self) {
+        info!("Node Health | Spawning thread...");
+        thread::spawn(move || self.run());
+    }
+
+    #[tokio::main]
+    async fn run(self) {
+        let client = Client::new();
+        let mut tracker = Tracker::default();
+        loop {
+            tokio::time::sleep(Duration::from_secs(NODE_HEALTH_INTERVAL)).await;
+            let state = lock!(self.state).clone();
+            let alive = lock2!(self.helper, p2pool).is_alive();
+            // Custom arguments can not be changed, nothing to switch.
+            if !state.p2pool.failover
+                || !alive
+                || (!state.p2pool.simple && !state.p2pool.arguments.is_empty())
+            {
+                tracker = Tracker::default();
+                continue;
+            }
+            self.check(&client, &state, &mut tracker).await;
+        }
+    }
+
+    // The nodes P2Pool can switch to.
+    fn candidates(&self, p2pool: &P2pool) -> Vec<Node> {
+        if p2pool.simple {
+            REMOTE_NODES
+                .iter()
+                .map(|(ip, _, rpc, zmq)| Node {
+                    ip: ip.to_string(),
+                    rpc: rpc.to_string(),
+                    zmq: zmq.to_string(),
+                })
+                .collect()
+        } else {
+            match Node::get(&self.node_path) {
+                Ok(vec) => vec.into_iter().map(|(_, node)| node).collect(),
+                Err(e) => {
+                    warn!(
+                        "Node Health | Could not read the node list ... FAIL ... {}",
+                        e
+                    );
+                    vec![]
+                }
+            }
+        }
+    }
+
+    async fn check(&self, client: &Client, state: &State, tracker: &mut Tracker) {
+        // What P2Pool was really started with.
+        let active = {
+            let img = Arc::clone(&lock!(self.helper).img_p2pool);
+            let img = lock!(img);
+            Node {
+                ip: img.host.clone(),
+                rpc: img.rpc.clone(),
+                zmq: img.zmq.clone(),
+            }
+        };
+        let mut nodes = self.candidates(&state.p2pool);
+        let index = match nodes.iter().position(|n| same_node(n, &active)) {
+            Some(i) => i,
+            None => {
+                nodes.insert(0, active.clone());
+                0
+            }
+        };
+
+        // All nodes at the same time, like [Ping].
+        let handles: Vec<_> = nodes
+            .iter()
+            .map(|node| {
+                let (client, node) = (client.clone(), node.clone());
+                tokio::spawn(async move { probe(&client, &node).await })
+            })
+            .collect();
+        let mut probes = Vec::with_capacity(handles.len());
+        for handle in handles {
+            probes.push(handle.await.unwrap_or(None));
+        }
+        let best = probes.iter().flatten().map(|p| p.height).max().unwrap_or(0);
+        let verdict = tracker.check(&active, probes[index], best, Instant::now());
+        debug!(
+            "Node Health | [{}:{}] is {} ({}/{})",
+            active.ip, active.rpc, verdict, tracker.fails, NODE_HEALTH_MAX_FAILS
+        );
+        if tracker.fails < NODE_HEALTH_MAX_FAILS {
+            return;
+        }
+
+        let Some(next) = next_healthy(Some(index), &probes, best) else {
+            self.log(&format!(
+                "Node Health | [{}:{}] is {}, but no healthy node to switch to",
+                active.ip, active.rpc, verdict
+            ));
+            tracker.fails = 0;
+            return;
+        };
+        let node = &nodes[next];
+        self.log(&format!(
+            "Node Health | [{}:{}] is {}, switching to [{}:{}]",
+            active.ip, active.rpc, verdict, node.ip, node.rpc
+        ));
+        let mut p2pool = state.p2pool.clone();
+        if p2pool.simple {
+            p2pool.node.clone_from(&node.ip);
+        } else {
+            p2pool.ip.clone_from(&node.ip);
+            p2pool.rpc.clone_from(&node.rpc);
+            p2pool.zmq.clone_from(&node.zmq);
+            p2pool.selected_ip.clone_from(&node.ip);
+            p2pool.selected_rpc.clone_from(&node.rpc);
+            p2pool.selected_zmq.clone_from(&node.zmq);
+        }
+        // The other healthy nodes are the backup hosts.
+        let backup_hosts = p2pool.backup_host.then(|| {
+            nodes
+                .iter()
+                .zip(&probes)
+                .filter(|(_, p)| p.is_some_and(|p| p.synchronized))
+                .map(|(n, _)| n.clone())
+                .collect()
+        });
+        Helper::restart_p2pool(
+            &self.helper,
+            &p2pool,
+            &state.gupax.absolute_p2pool_path,
+            backup_hosts,
+        );
+        *tracker = Tracker::default();
+    }
+
+    fn log(&self, msg: &str) {
+        info!("{}", msg);
+        let gui_api = Arc::clone(&lock!(self.helper).gui_api_p2pool);
+        output_console(&mut lock!(gui_api).output, msg, ProcessName::P2pool);
+    }
+}
+
+// Send [get_info] to a node, [None] on timeout or invalid answer.
+async fn probe(client: &Client, node: &Node) -> Option<Probe> {
+    let request = client
+        .post(format!("http://{}:{}/json_rpc", node.ip, node.rpc))
+        .header("User-Agent", get_user_agent())
+        .body(r#"{"jsonrpc":"2.0","id":"0","method":"get_info"}"#);
+    let bytes = match tokio::time::timeout(Duration::from_secs(5), request.send()).await {
+        Ok(Ok(response)) => response.bytes().await.ok()?,
+        _ => return None,
+    };
+    match serde_json::from_slice::<GetInfo<'_>>(&bytes) {
+        Ok(rpc) if rpc.result.mainnet => Some(Probe {
+            synchronized: rpc.result.synchronized,
+            height: rpc.result.height,
+        }),
+        _ => {
+            warn!(
+                "Node Health | [{}:{}] responded with invalid get_info",
+                node.ip, node.rpc
+            );
+            None
+        }
+    }
+}
@@ -2,19 +2,17 @@ use super::Helper;
 use super::Process;
 use crate::components::node::RemoteNode;
 use crate::disk::state::P2pool;
+use crate::helper::args::{Args, Binary};
 use crate::helper::check_died;
 use crate::helper::check_user_input;
+use crate::helper::p2pool_console::{
+    ConsoleCapture, ConsoleCommand, ConsoleReport, P2poolBan, P2poolPeer, P2poolStatus,
+};
 use crate::helper::signal_end;
 use crate::helper::sleep_end_loop;
 use crate::helper::ProcessName;
 use crate::helper::ProcessSignal;
 use crate::helper::ProcessState;
-use crate::regex::contains_end_status;
-use crate::regex::contains_statuscommand;
-use crate::regex::contains_yourhashrate;
-use crate::regex::contains_yourshare;
-use crate::regex::estimated_hr;
-use crate::regex::nb_current_shares;
 use crate::regex::P2POOL_REGEX;
 use crate::{
     constants::*,
@@ -64,47 +62,16 @@ impl Helper {
                 i += 1;
             }
         }
-        let mut status_output = false;
+        let mut capture = ConsoleCapture::default();
         while let Some(Ok(line)) = stdout.next() {
-            // if command status is sent by gupaxx process and not the user, forward it only to update_from_status method.
-            // 25 lines after the command are the result of status, with last line finishing by update.
-            if contains_statuscommand(&line) {
-                status_output = true;
-                continue;
+            // reports asked by Gupaxx and not the user are only used to update the API.
+            let captured = capture.push(&line);
+            match captured.report {
+                Some(Ok(report)) => lock!(gui_api).update_from_console(report),
+                Some(Err(e)) => error!("P2Pool PTY | Console report error: {}", e),
+                None => (),
             }
-            if status_output {
-                if contains_yourhashrate(&line) {
-                    if let Some(ehr) = estimated_hr(&line) {
-                        debug!(
-                            "P2pool | PTY getting current estimated HR data from status: {} KH/s",
-                            ehr
-                        );
-                        // multiply by a thousand because value is given as kH/s instead H/s
-                        lock!(gui_api).sidechain_ehr = ehr;
-                        debug!(
-                            "P2pool | PTY getting current estimated HR data from status: {} H/s",
-                            lock!(gui_api).sidechain_ehr
-                        );
-                    } else {
-                        error!("P2pool | PTY Getting data from status: Lines contains Your shares but no value found: {}", line);
-                    }
-                }
-                if contains_yourshare(&line) {
-                    // update sidechain shares
-                    if let Some(shares) = nb_current_shares(&line) {
-                        debug!(
-                            "P2pool | PTY getting current shares data from status: {} share",
-                            shares
-                        );
-                        lock!(gui_api).sidechain_shares = shares;
-                    } else {
-                        error!("P2pool | PTY Getting data from status: Lines contains Your shares but no value found: {}", line);
-                    }
-                }
-                if contains_end_status(&line) {
-                    // end of status
-                    status_output = false;
-                }
+            if captured.hidden {
                 continue;
             }
             //			println!("{}", line); // For debugging.
@@ -239,7 +206,7 @@ impl Helper {
         path: &Path,
         backup_hosts: Option<Vec<Node>>,
     ) -> (Vec<String>, PathBuf, PathBuf, PathBuf) {
-        let mut args = Vec::with_capacity(500);
+        let mut args = Args::new(Binary::P2pool);
         let path = path.to_path_buf();
         let mut api_path = path;
         api_path.pop();
@@ -248,31 +215,23 @@ impl Helper {
         if state.simple {
             // Build the p2pool argument
             let (ip, rpc, zmq) = RemoteNode::get_ip_rpc_zmq(&state.node); // Get: (IP, RPC, ZMQ)
-            args.push("--wallet".to_string());
-            args.push(state.address.clone()); // Wallet address
-            args.push("--host".to_string());
-            args.push(ip.to_string()); // IP Address
-            args.push("--rpc-port".to_string());
-            args.push(rpc.to_string()); // RPC Port
-            args.push("--zmq-port".to_string());
-            args.push(zmq.to_string()); // ZMQ Port
-            args.push("--data-api".to_string());
-            args.push(api_path.display().to_string()); // API Path
-            args.push("--local-api".to_string()); // Enable API
-            args.push("--no-color".to_string()); // Remove color escape sequences, Gupax terminal can't parse it :(
-            args.push("--mini".to_string()); // P2Pool Mini
-            args.push("--light-mode".to_string()); // Assume user is not using P2Pool to mine.
+            args.push("--wallet", &state.address) // Wallet address
+                .push("--host", ip) // IP Address
+                .push("--rpc-port", rpc) // RPC Port
+                .push("--zmq-port", zmq) // ZMQ Port
+                .push("--data-api", api_path.display()) // API Path
+                .flag("--local-api") // Enable API
+                .flag("--no-color") // Remove color escape sequences, Gupax terminal can't parse it :(
+                .flag("--mini") // P2Pool Mini
+                .flag("--light-mode"); // Assume user is not using P2Pool to mine.
 
             // Push other nodes if `backup_host`.
             if let Some(nodes) = backup_hosts {
                 for node in nodes {
                     if (node.ip.as_str(), node.rpc.as_str(), node.zmq.as_str()) != (ip, rpc, zmq) {
-                        args.push("--host".to_string());
-                        args.push(node.ip.to_string());
-                        args.push("--rpc-port".to_string());
-                        args.push(node.rpc.to_string());
-                        args.push("--zmq-port".to_string());
-                        args.push(node.zmq.to_string());
+                        args.push("--host", &node.ip)
+                            .push("--rpc-port", &node.rpc)
+                            .push("--zmq-port", &node.zmq);
                     }
                 }
             }
@@ -291,34 +250,13 @@ impl Helper {
         } else {
             // Overriding command arguments
             if !state.arguments.is_empty() {
-                // This parses the input and attempts to fill out
-                // the [ImgP2pool]... This is pretty bad code...
-                let mut last = "";
-                let lock = lock!(helper);
-                let mut p2pool_image = lock!(lock.img_p2pool);
-                let mut mini = false;
-                for arg in state.arguments.split_whitespace() {
-                    match last {
-                        "--mini" => {
-                            mini = true;
-                            p2pool_image.mini = "P2Pool Mini".to_string();
-                        }
-                        "--wallet" => p2pool_image.address = Self::head_tail_of_monero_address(arg),
-                        "--host" => p2pool_image.host = arg.to_string(),
-                        "--rpc-port" => p2pool_image.rpc = arg.to_string(),
-                        "--zmq-port" => p2pool_image.zmq = arg.to_string(),
-                        "--out-peers" => p2pool_image.out_peers = arg.to_string(),
-                        "--in-peers" => p2pool_image.in_peers = arg.to_string(),
-                        "--data-api" => api_path = PathBuf::from(arg),
-                        _ => (),
-                    }
-                    if !mini {
-                        p2pool_image.mini = "P2Pool Main".to_string();
-                    }
-                    let arg = if arg == "localhost" { "127.0.0.1" } else { arg };
-                    args.push(arg.to_string());
-                    last = arg;
+                // The UI refuses to start with invalid arguments,
+                // what is still not understood is given as it is to P2Pool.
+                args = Args::parse_lossy(Binary::P2pool, &state.arguments);
+                if let Some(data_api) = args.value("--data-api") {
+                    api_path = PathBuf::from(data_api);
                 }
+                *lock2!(helper, img_p2pool) = ImgP2pool::from_args(&args);
             // Else, build the argument
             } else {
                 let ip = if state.ip == "localhost" {
@@ -326,27 +264,19 @@ impl Helper {
                 } else {
                     &state.ip
                 };
-                args.push("--wallet".to_string());
-                args.push(state.address.clone()); // Wallet
-                args.push("--host".to_string());
-                args.push(ip.to_string()); // IP
-                args.push("--rpc-port".to_string());
-                args.push(state.rpc.to_string()); // RPC
-                args.push("--zmq-port".to_string());
-                args.push(state.zmq.to_string()); // ZMQ
-                args.push("--loglevel".to_string());
-                args.push(state.log_level.to_string()); // Log Level
-                args.push("--out-peers".to_string());
-                args.push(state.out_peers.to_string()); // Out Peers
-                args.push("--in-peers".to_string());
-                args.push(state.in_peers.to_string()); // In Peers
-                args.push("--data-api".to_string());
-                args.push(api_path.display().to_string()); // API Path
-                args.push("--local-api".to_string()); // Enable API
-                args.push("--no-color".to_string()); // Remove color escape sequences
-                args.push("--light-mode".to_string()); // Assume user is not using P2Pool to mine.
+                args.push("--wallet", &state.address) // Wallet
+                    .push("--host", ip) // IP
+                    .push("--rpc-port", &state.rpc) // RPC
+                    .push("--zmq-port", &state.zmq) // ZMQ
+                    .push("--loglevel", state.log_level) // Log Level
+                    .push("--out-peers", state.out_peers) // Out Peers
+                    .push("--in-peers", state.in_peers) // In Peers
+                    .push("--data-api", api_path.display()) // API Path
+                    .flag("--local-api") // Enable API
+                    .flag("--no-color") // Remove color escape sequences
+                    .flag("--light-mode"); // Assume user is not using P2Pool to mine.
                 if state.mini {
-                    args.push("--mini".to_string());
+                    args.flag("--mini");
                 }; // Mini
 
                 // Push other nodes if `backup_host`.
@@ -360,12 +290,9 @@ impl Helper {
                         if (node.ip.as_str(), node.rpc.as_str(), node.zmq.as_str())
                             != (ip, &state.rpc, &state.zmq)
                         {
-                            args.push("--host".to_string());
-                            args.push(node.ip.to_string());
-                            args.push("--rpc-port".to_string());
-                            args.push(node.rpc.to_string());
-                            args.push("--zmq-port".to_string());
-                            args.push(node.zmq.to_string());
+                            args.push("--host", &node.ip)
+                                .push("--rpc-port", &node.rpc)
+                                .push("--zmq-port", &node.zmq);
                         }
                     }
                 }
@@ -391,7 +318,12 @@ impl Helper {
         api_path_local.push(P2POOL_API_PATH_LOCAL);
         api_path_network.push(P2POOL_API_PATH_NETWORK);
         api_path_pool.push(P2POOL_API_PATH_POOL);
-        (args, api_path_local, api_path_network, api_path_pool)
+        (
+            args.into_argv(),
+            api_path_local,
+            api_path_network,
+            api_path_pool,
+        )
     }
 
     #[cold]
@@ -440,6 +372,7 @@ impl Helper {
         lock.state = ProcessState::Syncing;
         lock.signal = ProcessSignal::None;
         lock.start = Instant::now();
+        lock.exit_code = None;
         let reader = pair.master.try_clone_reader().unwrap(); // Get STDOUT/STDERR before moving the PTY
         let mut stdin = pair.master.take_writer().unwrap();
         drop(lock);
@@ -556,23 +489,36 @@ impl Helper {
                     }
                 }
             }
-            if (lock!(gui_api).tick_status >= 60 || first_loop)
-                && lock!(process).state == ProcessState::Alive
-            {
-                debug!("P2Pool Watchdog | Reading status output of p2pool node");
-                #[cfg(target_os = "windows")]
-                if let Err(e) = write!(stdin, "statusfromgupaxx\r\n") {
-                    error!("P2Pool Watchdog | STDIN error: {}", e);
-                }
-                #[cfg(target_family = "unix")]
-                if let Err(e) = writeln!(stdin, "statusfromgupaxx") {
-                    error!("P2Pool Watchdog | STDIN error: {}", e);
+            // One report at a time, the echo of a command must not come in the middle of another report.
+            let command = match lock!(gui_api).tick_status {
+                _ if first_loop => Some(ConsoleCommand::Status),
+                20 => Some(ConsoleCommand::Peers),
+                40 => Some(ConsoleCommand::Bans),
+                t if t >= 60 => Some(ConsoleCommand::Status),
+                _ => None,
+            };
+            if let Some(command) = command {
+                if lock!(process).state == ProcessState::Alive {
+                    debug!(
+                        "P2Pool Watchdog | Reading {:?} output of p2pool node",
+                        command
+                    );
+                    #[cfg(target_os = "windows")]
+                    if let Err(e) = write!(stdin, "{}\r\n", command.input()) {
+                        error!("P2Pool Watchdog | STDIN error: {}", e);
+                    }
+                    #[cfg(target_family = "unix")]
+                    if let Err(e) = writeln!(stdin, "{}", command.input()) {
+                        error!("P2Pool Watchdog | STDIN error: {}", e);
+                    }
+                    // Flush.
+                    if let Err(e) = stdin.flush() {
+                        error!("P2Pool Watchdog | STDIN flush error: {}", e);
+                    }
                 }
-                // Flush.
-                if let Err(e) = stdin.flush() {
-                    error!("P2Pool Watchdog | STDIN flush error: {}", e);
+                if command == ConsoleCommand::Status {
+                    lock!(gui_api).tick_status = 0;
                 }
-                lock!(gui_api).tick_status = 0;
             }
 
             // Sleep (only if 900ms hasn't passed)
@@ -595,7 +541,7 @@ impl Helper {
 // This is just a snapshot of the user data when they initially started P2Pool.
 // Created by [start_p2pool()] and return to the main GUI thread where it will store it.
 // No need for an [Arc<Mutex>] since the Helper thread doesn't need this information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ImgP2pool {
     pub mini: String,      // Did the user start on the mini-chain?
     pub address: String, // What address is the current p2pool paying out to? (This gets shortened to [4xxxxx...xxxxxx])
@@ -624,6 +570,28 @@ impl ImgP2pool {
             in_peers: String::from("???"),
         }
     }
+    // What the arguments overriding the advanced settings tell, with the defaults of P2Pool for the rest.
+    // P2Pool uses the first node given, the others are backups.
+    pub fn from_args(args: &Args) -> Self {
+        let mini = if args.has("--mini") {
+            "P2Pool Mini"
+        } else if args.has("--nano") {
+            "P2Pool Nano"
+        } else {
+            "P2Pool Main"
+        };
+        Self {
+            mini: mini.to_string(),
+            address: args
+                .value("--wallet")
+                .map_or("???".to_string(), Helper::head_tail_of_monero_address),
+            host: args.value("--host").unwrap_or("127.0.0.1").to_string(),
+            rpc: args.value("--rpc-port").unwrap_or("18081").to_string(),
+            zmq: args.value("--zmq-port").unwrap_or("18083").to_string(),
+            out_peers: args.value("--out-peers").unwrap_or("10").to_string(),
+            in_peers: args.value("--in-peers").unwrap_or("450").to_string(),
+        }
+    }
 }
 
 //---------------------------------------------------------------------------------------------------- Public P2Pool API
@@ -660,11 +628,17 @@ pub struct PubP2poolApi {
     pub monero_difficulty_u64: u64,
     pub p2pool_hashrate_u64: u64,
     pub monero_hashrate_u64: u64,
+    // Raw values of the local API, the 1h hashrate is [user_p2pool_hashrate_u64].
+    pub hashrate_15m_u64: u64,
+    pub hashrate_24h_u64: u64,
+    pub average_effort_f32: f32,
+    pub current_effort_f32: f32,
+    pub connections_u32: u32,
     // Tick. Every loop this gets incremented.
     // At 60, it indicated we should read the below API files.
     pub tick: u8,
     // Tick. Every loop this gets incremented.
-    // At 10, it indicated we should fetch data from status
+    // At 20 and 40, the peers and the bans are asked to the console, at 60 the status.
     pub tick_status: u8,
     // Network API
     pub monero_difficulty: HumanNumber, // e.g: [15,000,000]
@@ -687,6 +661,12 @@ pub struct PubP2poolApi {
     // from status
     pub sidechain_shares: u32,
     pub sidechain_ehr: f32,
+    // Last reports of the console, refreshed every minute.
+    pub status: Option<P2poolStatus>,
+    pub peers: Vec<P2poolPeer>,
+    pub bans: Vec<P2poolBan>,
+    // Miners connected to the stratum, and the ones that left.
+    pub workers: Vec<P2poolWorker>,
 }
 
 impl Default for PubP2poolApi {
@@ -722,6 +702,11 @@ impl PubP2poolApi {
             monero_difficulty_u64: 0,
             p2pool_hashrate_u64: 0,
             monero_hashrate_u64: 0,
+            hashrate_15m_u64: 0,
+            hashrate_24h_u64: 0,
+            average_effort_f32: 0.0,
+            current_effort_f32: 0.0,
+            connections_u32: 0,
             monero_difficulty: HumanNumber::unknown(),
             monero_hashrate: HumanNumber::unknown(),
             hash: String::from("???"),
@@ -738,6 +723,10 @@ impl PubP2poolApi {
             user_monero_percent: HumanNumber::unknown(),
             sidechain_shares: 0,
             sidechain_ehr: 0.0,
+            status: None,
+            peers: vec![],
+            bans: vec![],
+            workers: vec![],
         }
     }
 
@@ -758,10 +747,36 @@ impl PubP2poolApi {
             tick_status: std::mem::take(&mut gui_api.tick_status),
             sidechain_shares: std::mem::take(&mut gui_api.sidechain_shares),
             sidechain_ehr: std::mem::take(&mut gui_api.sidechain_ehr),
+            status: std::mem::take(&mut gui_api.status),
+            peers: std::mem::take(&mut gui_api.peers),
+            bans: std::mem::take(&mut gui_api.bans),
             ..pub_api.clone()
         };
     }
 
+    // The shares and the pool-side hashrate of the address are used by XvB.
+    pub(super) fn update_from_console(&mut self, report: ConsoleReport) {
+        match report {
+            ConsoleReport::Status(status) => {
+                debug!(
+                    "P2Pool | current shares and estimated HR from status: {:?} shares, {:?} H/s",
+                    status.shares.map(|s| s.blocks),
+                    status.hashrate
+                );
+                // without a value, the last one is kept.
+                if let Some(shares) = status.shares {
+                    self.sidechain_shares = shares.blocks;
+                }
+                if let Some(hashrate) = status.hashrate {
+                    self.sidechain_ehr = hashrate as f32;
+                }
+                self.status = Some(*status);
+            }
+            ConsoleReport::Peers(peers) => self.peers = peers,
+            ConsoleReport::Bans(bans) => self.bans = bans,
+        }
+    }
+
     #[inline]
     // Essentially greps the output for [x.xxxxxxxxxxxx XMR] where x = a number.
     // It sums each match and counts along the way, handling an error by not adding and printing to console.
@@ -882,6 +897,12 @@ impl PubP2poolApi {
     // Mutate [PubP2poolApi] with data from a [PrivP2poolLocalApi] and the process output.
     pub(super) fn update_from_local(public: &Arc<Mutex<Self>>, local: PrivP2poolLocalApi) {
         let mut public = lock!(public);
+        let workers = local
+            .workers
+            .iter()
+            .filter_map(|w| P2poolWorker::from_str(w))
+            .collect();
+        let workers = P2poolWorker::merge(&public.workers, workers);
         *public = Self {
             hashrate_15m: HumanNumber::from_u64(local.hashrate_15m),
             hashrate_1h: HumanNumber::from_u64(local.hashrate_1h),
@@ -891,6 +912,12 @@ impl PubP2poolApi {
             current_effort: HumanNumber::to_percent(local.current_effort),
             connections: HumanNumber::from_u32(local.connections),
             user_p2pool_hashrate_u64: local.hashrate_1h,
+            hashrate_15m_u64: local.hashrate_15m,
+            hashrate_24h_u64: local.hashrate_24h,
+            average_effort_f32: local.average_effort,
+            current_effort_f32: local.current_effort,
+            connections_u32: local.connections,
+            workers,
             ..std::mem::take(&mut *public)
         };
     }
@@ -1061,7 +1088,7 @@ impl PubP2poolApi {
 //---------------------------------------------------------------------------------------------------- Private P2Pool "Local" Api
 // This matches directly to P2Pool's [local/stratum] JSON API file (excluding a few stats).
 // P2Pool seems to initialize all stats at 0 (or 0.0), so no [Option] wrapper seems needed.
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub(super) struct PrivP2poolLocalApi {
     pub hashrate_15m: u64,
     pub hashrate_1h: u64,
@@ -1070,6 +1097,8 @@ pub(super) struct PrivP2poolLocalApi {
     pub average_effort: f32,
     pub current_effort: f32,
     pub connections: u32, // This is a `uint32_t` in `p2pool`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workers: Vec<String>, // Parsed into [P2poolWorker]
 }
 
 impl Default for PrivP2poolLocalApi {
@@ -1088,6 +1117,7 @@ impl PrivP2poolLocalApi {
             average_effort: 0.0,
             current_effort: 0.0,
             connections: 0,
+            workers: vec![],
         }
     }
 
@@ -1103,6 +1133,65 @@ impl PrivP2poolLocalApi {
     }
 }
 
+//---------------------------------------------------------------------------------------------------- P2Pool Worker
+// A miner connected to the stratum of P2Pool, from the [workers] of [local/stratum].
+// P2Pool writes each of them as ["IP:port,connected seconds,difficulty,hashrate,rig name"].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct P2poolWorker {
+    pub address: String, // IP:port
+    pub uptime: u64,     // Seconds since it connected
+    pub difficulty: u64,
+    pub hashrate: u64,
+    pub name: String,  // Rig name, can be empty
+    pub missing: bool, // Was connected, not anymore
+}
+
+impl P2poolWorker {
+    pub fn from_str(worker: &str) -> Option<Self> {
+        let mut fields = worker.splitn(5, ',');
+        let address = fields.next()?.to_string();
+        let uptime = fields.next()?.parse().ok()?;
+        let difficulty = fields.next()?.parse().ok()?;
+        let hashrate = fields.next()?.parse().ok()?;
+        let name = fields.next().unwrap_or_default().to_string();
+        Some(Self {
+            address,
+            uptime,
+            difficulty,
+            hashrate,
+            name,
+            missing: false,
+        })
+    }
+
+    // The port changes when a rig reconnects, so a rig is its IP and name.
+    fn same_rig(&self, other: &Self) -> bool {
+        let ip = |a: &str| a.rsplit_once(':').map_or(a, |(ip, _)| ip).to_string();
+        ip(&self.address) == ip(&other.address) && self.name == other.name
+    }
+
+    // The workers now connected, followed by the previous ones that are not anymore.
+    pub fn merge(old: &[Self], new: Vec<Self>) -> Vec<Self> {
+        let missing: Vec<Self> = old
+            .iter()
+            .filter(|o| !new.iter().any(|n| n.same_rig(o)))
+            .map(|o| {
+                if !o.missing {
+                    warn!(
+                        "P2Pool Local API | Worker [{}] [{}] disconnected",
+                        o.name, o.address
+                    );
+                }
+                Self {
+                    missing: true,
+                    ..o.clone()
+                }
+            })
+            .collect();
+        new.into_iter().chain(missing).collect()
+    }
+}
+
 //---------------------------------------------------------------------------------------------------- Private P2Pool "Network" API
 // This matches P2Pool's [network/stats] JSON API file.
 #[derive(Debug, Serialize, Deserialize, Clone)]
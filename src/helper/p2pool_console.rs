@@ -0,0 +1,387 @@
+// Reports of the P2Pool console asked by Gupaxx: [status], [peers] and [bans].
+// P2Pool runs a command if the input starts with its name, so Gupaxx sends [statusfromgupaxx]:
+// the terminal echoes it before P2Pool answers, which marks the start of a report that is not shown to the user.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// A report has never been that long, something is wrong with its end.
+const MAX_STATUS_LINES: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    Status,
+    Peers,
+    Bans,
+}
+impl ConsoleCommand {
+    const ALL: [Self; 3] = [Self::Status, Self::Peers, Self::Bans];
+    pub fn input(self) -> &'static str {
+        match self {
+            Self::Status => "statusfromgupaxx",
+            Self::Peers => "peersfromgupaxx",
+            Self::Bans => "bansfromgupaxx",
+        }
+    }
+    fn from_echo(line: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| line.trim_start().starts_with(c.input()))
+    }
+}
+
+// Blocks of the address, or of everyone, in the PPLNS window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowBlocks {
+    pub blocks: u32,
+    pub uncles: u32,
+    pub orphans: u32,
+}
+impl WindowBlocks {
+    // [2160 blocks (+79 uncles, 0 orphans)]
+    fn from_str(s: &str) -> Option<Self> {
+        static BLOCKS: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^(\d+) blocks \(\+(\d+) uncles, (\d+) orphans\)$").unwrap());
+        let c = BLOCKS.captures(s)?;
+        Some(Self {
+            blocks: c[1].parse().ok()?,
+            uncles: c[2].parse().ok()?,
+            orphans: c[3].parse().ok()?,
+        })
+    }
+}
+
+// Output of [status]. Hashrates are in H/s, durations in seconds.
+// The lines change between the versions of P2Pool, so a field missing or not understood is [None]:
+// only the shares and the pool-side hashrate of the address are needed by XvB.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct P2poolStatus {
+    // SideChain
+    pub monero_node: Option<String>,
+    pub main_height: Option<u64>,
+    pub main_hashrate: Option<u64>,
+    pub sidechain: Option<String>, // [default], [mini] or [nano]
+    pub sidechain_height: Option<u64>,
+    pub sidechain_hashrate: Option<u64>,
+    pub pplns_window: Option<WindowBlocks>,
+    pub pplns_duration: Option<u64>,
+    pub wallet: Option<String>,
+    pub shares: Option<WindowBlocks>,
+    pub reward_share: Option<f32>, // percent of the block reward
+    pub hashrate: Option<u64>,     // pool-side hashrate of the address, not written by older P2Pool
+    // StratumServer
+    pub hashrate_15m: Option<u64>,
+    pub hashrate_1h: Option<u64>,
+    pub hashrate_24h: Option<u64>,
+    pub total_hashes: Option<u64>,
+    pub shares_found: Option<u64>,
+    pub average_effort: Option<f32>, // percent
+    pub current_effort: Option<f32>, // percent
+    pub stratum_connections: Option<u32>,
+    // P2PServer
+    pub p2p_connections: Option<u32>,
+    pub p2p_incoming: Option<u32>,
+    pub peer_list_size: Option<u32>,
+    pub uptime: Option<u64>,
+}
+impl P2poolStatus {
+    // Lines between the echo of the command and [Uptime], without the echo.
+    // Only a status without the shares and the hashrate of the address is an error.
+    pub fn from_str(report: &str) -> Result<Self> {
+        let fields = report
+            .lines()
+            .filter_map(|l| l.split_once(" = "))
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .collect::<Vec<_>>();
+        let field = |name: &str| fields.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+        let text = |name| field(name).map(str::to_string);
+        let number = |name| field(name)?.parse().ok();
+        let hashrate = |name| parse_hashrate(field(name)?);
+        let duration = |name| parse_duration(field(name)?);
+        let blocks = |name| WindowBlocks::from_str(field(name)?);
+        let percent = |name| parse_percent(field(name)?);
+        let status = Self {
+            monero_node: text("Monero node"),
+            main_height: number("Main chain height"),
+            main_hashrate: hashrate("Main chain hashrate"),
+            sidechain: text("Side chain ID"),
+            sidechain_height: number("Side chain height"),
+            sidechain_hashrate: hashrate("Side chain hashrate"),
+            pplns_window: blocks("PPLNS window"),
+            pplns_duration: duration("PPLNS window duration"),
+            wallet: text("Your wallet address"),
+            shares: blocks("Your shares"),
+            // [0.103% (0.000623516125 XMR)]
+            reward_share: field("Block reward share")
+                .and_then(|v| parse_percent(v.split_once(' ').map_or(v, |(p, _)| p))),
+            hashrate: hashrate("Your hashrate (pool-side)"),
+            hashrate_15m: hashrate("Hashrate (15m est)"),
+            hashrate_1h: hashrate("Hashrate (1h  est)"),
+            hashrate_24h: hashrate("Hashrate (24h est)"),
+            total_hashes: hashrate("Total hashes"),
+            shares_found: number("Shares found"),
+            average_effort: percent("Average effort"),
+            current_effort: percent("Current effort"),
+            stratum_connections: connections_of_section(report, "StratumServer").map(|(c, _)| c),
+            p2p_connections: connections_of_section(report, "P2PServer").map(|(c, _)| c),
+            p2p_incoming: connections_of_section(report, "P2PServer").map(|(_, i)| i),
+            peer_list_size: number("Peer list size").map(|n: u64| n as u32),
+            uptime: duration("Uptime"),
+        };
+        if status.shares.is_none() && status.hashrate.is_none() {
+            return Err(match field("Your shares") {
+                Some(_) => anyhow!("invalid [Your shares] in the status"),
+                None => anyhow!("no [Your shares] in the status"),
+            });
+        }
+        Ok(status)
+    }
+}
+
+// StratumServer and P2PServer both write [Connections] as [12 (2 incoming)],
+// the one after the header of [section] is used.
+fn connections_of_section(report: &str, section: &str) -> Option<(u32, u32)> {
+    report
+        .lines()
+        .skip_while(|l| !matches!(split_prefix(l), (Some(s), "status") if s == section))
+        .filter_map(|l| l.split_once(" = "))
+        .find(|(k, _)| k.trim() == "Connections")?
+        .1
+        .trim()
+        .strip_suffix(" incoming)")
+        .and_then(|v| v.split_once(" ("))
+        .and_then(|(t, i)| Some((t.parse().ok()?, i.parse().ok()?)))
+}
+
+// A peer from [peers], written by P2Pool as tab separated columns after the direction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct P2poolPeer {
+    pub incoming: bool,
+    pub address: String,
+    pub uptime: u64,         // seconds since the connection
+    pub ping: Option<u32>,   // ms
+    pub software: String,    // like [v4.1], empty if not given
+    pub height: Option<u64>, // highest sidechain height the peer broadcasted
+}
+impl P2poolPeer {
+    // [O	0h 38m 41s	  91 ms	v4.1	8775201	[::ffff:5.9.17.234]:37888]
+    // The columns are recognized by their format, so their order does not matter.
+    pub fn from_str(line: &str) -> Option<Self> {
+        let mut columns = line.split('\t').map(str::trim).filter(|c| !c.is_empty());
+        let incoming = match columns.next()? {
+            "I" => true,
+            "O" => false,
+            _ => return None,
+        };
+        let mut peer = Self {
+            incoming,
+            ..Default::default()
+        };
+        let mut uptime = None;
+        for column in columns {
+            if let Some(ping) = column.strip_suffix(" ms") {
+                peer.ping = ping.trim().parse().ok();
+            } else if let Some(d) = parse_duration(column) {
+                uptime = Some(d);
+            } else if column.chars().all(|c| c.is_ascii_digit()) {
+                peer.height = column.parse().ok();
+            } else if column.contains(':') {
+                peer.address = column.to_string();
+            } else if column.starts_with('v') || column.starts_with("P2Pool") {
+                peer.software = column.to_string();
+            }
+        }
+        if peer.address.is_empty() {
+            return None;
+        }
+        peer.uptime = uptime?;
+        Some(peer)
+    }
+}
+
+// A banned IP from [bans], with the time left before the ban ends.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct P2poolBan {
+    pub address: String,
+    pub remaining: Option<u64>, // seconds
+}
+impl P2poolBan {
+    // [1.2.3.4 (9m 58s)], the words around the duration are not used.
+    pub fn from_str(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (address, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        if !address.contains(['.', ':']) || address.starts_with("Total:") {
+            return None;
+        }
+        let remaining = rest
+            .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .filter_map(parse_duration)
+            .reduce(|a, b| a + b);
+        Some(Self {
+            address: address.to_string(),
+            remaining,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsoleReport {
+    Status(Box<P2poolStatus>),
+    Peers(Vec<P2poolPeer>),
+    Bans(Vec<P2poolBan>),
+}
+
+#[derive(Debug)]
+enum Pending {
+    Status(String),
+    Peers(Vec<P2poolPeer>),
+    Bans(Vec<P2poolBan>),
+}
+impl Pending {
+    fn new(command: ConsoleCommand) -> Self {
+        match command {
+            ConsoleCommand::Status => Self::Status(String::new()),
+            ConsoleCommand::Peers => Self::Peers(vec![]),
+            ConsoleCommand::Bans => Self::Bans(vec![]),
+        }
+    }
+    fn finish(self) -> Result<ConsoleReport> {
+        match self {
+            Self::Status(report) => {
+                P2poolStatus::from_str(&report).map(|s| ConsoleReport::Status(Box::new(s)))
+            }
+            Self::Peers(peers) => Ok(ConsoleReport::Peers(peers)),
+            Self::Bans(bans) => Ok(ConsoleReport::Bans(bans)),
+        }
+    }
+}
+
+// What a line of output is for.
+#[derive(Debug)]
+pub struct Captured {
+    pub hidden: bool,                          // part of a report, not shown to the user
+    pub report: Option<Result<ConsoleReport>>, // a report ended with this line
+}
+
+// Follows the output of P2Pool line by line to pull out the reports asked by Gupaxx.
+// Other threads of P2Pool can write in the middle of a report, their lines are shown as usual.
+#[derive(Debug, Default)]
+pub struct ConsoleCapture {
+    pending: Option<Pending>,
+}
+impl ConsoleCapture {
+    pub fn push(&mut self, line: &str) -> Captured {
+        if let Some(command) = ConsoleCommand::from_echo(line) {
+            // the previous report did not end, what it got is still used.
+            let report = self
+                .pending
+                .replace(Pending::new(command))
+                .map(Pending::finish);
+            return Captured {
+                hidden: true,
+                report,
+            };
+        }
+        let (category, content) = split_prefix(line);
+        let (hidden, end) = match (&mut self.pending, category) {
+            (None, _) => (false, false),
+            // the sections of the status are written with a prefix, their fields without.
+            (Some(Pending::Status(report)), None) => {
+                report.push_str(line);
+                report.push('\n');
+                let end = content.starts_with("Uptime ");
+                if !end && report.lines().count() > MAX_STATUS_LINES {
+                    self.pending = None;
+                    return Captured {
+                        hidden: false,
+                        report: Some(Err(anyhow!("the status did not end"))),
+                    };
+                }
+                (true, end)
+            }
+            (Some(Pending::Status(report)), Some(_)) if content == "status" => {
+                report.push_str(line);
+                report.push('\n');
+                (true, false)
+            }
+            (Some(Pending::Status(_)), Some(_)) => (false, false),
+            // peers and bans are written in one go, any other line ends them.
+            (Some(Pending::Peers(peers)), Some("P2PServer")) => {
+                match P2poolPeer::from_str(content) {
+                    Some(peer) => {
+                        peers.push(peer);
+                        (true, false)
+                    }
+                    None => (content.starts_with("Total:"), true),
+                }
+            }
+            (Some(Pending::Bans(bans)), Some("P2PServer")) => match P2poolBan::from_str(content) {
+                Some(ban) => {
+                    bans.push(ban);
+                    (true, false)
+                }
+                None => (content.starts_with("Total:"), true),
+            },
+            (Some(_), _) => (false, true),
+        };
+        let report = if end {
+            self.pending.take().map(Pending::finish)
+        } else {
+            None
+        };
+        Captured { hidden, report }
+    }
+}
+
+// [2024-03-25 21:31:21.7919 SideChain status] is the category [SideChain] and the content [status].
+// The fields of a multi-line report have no prefix.
+fn split_prefix(line: &str) -> (Option<&str>, &str) {
+    static PREFIX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?:[A-Z]+\s+)?\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d+ (\S+) ?").unwrap()
+    });
+    match PREFIX.captures(line) {
+        Some(c) => (
+            c.get(1).map(|m| m.as_str()),
+            &line[c.get(0).map_or(0, |m| m.end())..],
+        ),
+        None => (None, line.trim()),
+    }
+}
+
+// [14.452 KH/s] or [6064238155], like P2Pool writes them.
+fn parse_hashrate(s: &str) -> Option<u64> {
+    let s = s.strip_suffix("H/s").unwrap_or(s).trim();
+    let (number, coeff) = match s.chars().last()? {
+        'K' => (&s[..s.len() - 1], 1e3),
+        'M' => (&s[..s.len() - 1], 1e6),
+        'G' => (&s[..s.len() - 1], 1e9),
+        'T' => (&s[..s.len() - 1], 1e12),
+        _ => (s, 1.0),
+    };
+    let number = number.trim().parse::<f64>().ok()?;
+    (number >= 0.0).then(|| (number * coeff).round() as u64)
+}
+
+// [1d 2h 3m 4s]
+fn parse_duration(s: &str) -> Option<u64> {
+    if s.is_empty() {
+        return None;
+    }
+    s.split_whitespace().try_fold(0, |total, part| {
+        let unit = match part.chars().last()? {
+            'd' => 86400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        let n = part[..part.len() - 1].parse::<u64>().ok()?;
+        Some(total + n * unit)
+    })
+}
+
+// [112.543%]
+fn parse_percent(s: &str) -> Option<f32> {
+    s.strip_suffix('%')?.parse().ok()
+}
@@ -0,0 +1,309 @@
+// Process supervisor.
+// Thread restarting P2Pool, XMRig and XMRig-Proxy when they crash, following the
+// [SupervisorPolicy] of their section in the saved state. A crash is a process
+// in the [Failed] state with an exit code set by [check_died], so a process
+// stopped by the user is never restarted.
+//
+// Restarts are delayed by the backoff, doubled after each restart and capped by
+// [SUPERVISOR_MAX_BACKOFF]. After [max_restarts] crashes in a row, the supervisor
+// gives up until the process stays alive longer than the crash-loop window.
+// If XvB is running, it is restarted once the dependency is back, so the
+// algorithm re-applies its decision on the new process.
+//
+// With [auto_rollback], a process replaced by an update that crashes before
+// ever being [Alive] makes the supervisor roll back the update.
+
+use crate::components::node::Ping;
+use crate::components::update::{check_p2pool_path, check_xmrig_path, check_xp_path, Update};
+use crate::disk::state::{State, SupervisorPolicy};
+use crate::helper::{Helper, Process, ProcessName, ProcessState};
+use crate::miscs::output_console;
+use crate::utils::sudo::SudoState;
+use crate::{constants::*, macros::*};
+use log::*;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Upper limit of the delay between two restarts, in seconds.
+pub const SUPERVISOR_MAX_BACKOFF: u64 = 300;
+
+pub struct Supervisor {
+    helper: Arc<Mutex<Helper>>,
+    state: Arc<Mutex<State>>, // The saved state [og], used to restart processes.
+    sudo: Arc<Mutex<SudoState>>,
+    ping: Arc<Mutex<Ping>>,
+    node_path: PathBuf, // The saved manual node list, for the backup hosts of P2Pool.
+    state_path: PathBuf, // Where [og] is saved after a rollback.
+    exe: String,        // Path of the running Gupaxx binary.
+}
+
+// What the supervisor remembers about one process.
+#[derive(Debug, Default)]
+pub struct Watch {
+    pub restarts: u8,              // Restarts in a row, reset after [crash_loop_window]
+    pub handled: Option<Instant>,  // Start time of the last crash already handled
+    pub retry_at: Option<Instant>, // When the scheduled restart happens
+    pub resync_xvb: bool,          // Restart XvB once the process is [Alive]
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Decision {
+    Restart { delay: Duration, attempt: u8 },
+    GiveUp,
+}
+
+impl Watch {
+    // Called once per crash, with how long the process ran.
+    pub fn on_crash(&mut self, policy: &SupervisorPolicy, uptime: Duration) -> Decision {
+        if uptime.as_secs() >= policy.crash_loop_window {
+            self.restarts = 0;
+        }
+        if self.restarts >= policy.max_restarts {
+            return Decision::GiveUp;
+        }
+        let delay = backoff(policy.backoff, self.restarts);
+        self.restarts += 1;
+        Decision::Restart {
+            delay,
+            attempt: self.restarts,
+        }
+    }
+}
+
+// [base * 2^restarts] seconds, capped by [SUPERVISOR_MAX_BACKOFF].
+pub fn backoff(base: u64, restarts: u8) -> Duration {
+    let secs = base
+        .saturating_mul(1_u64 << restarts.min(32))
+        .min(SUPERVISOR_MAX_BACKOFF);
+    Duration::from_secs(secs)
+}
+
+impl Supervisor {
+    pub fn new(
+        helper: &Arc<Mutex<Helper>>,
+        state: &Arc<Mutex<State>>,
+        sudo: &Arc<Mutex<SudoState>>,
+        ping: &Arc<Mutex<Ping>>,
+        node_path: &Path,
+        state_path: &Path,
+        exe: &str,
+    ) -> Self {
+        Self {
+            helper: Arc::clone(helper),
+            state: Arc::clone(state),
+            sudo: Arc::clone(sudo),
+            ping: Arc::clone(ping),
+            node_path: node_path.to_path_buf(),
+            state_path: state_path.to_path_buf(),
+            exe: exe.to_string(),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub fn spawn(self) {
+        info!("Supervisor | Spawning thread...");
+        thread::spawn(move || self.run());
+    }
+
+    fn run(self) {
+        let mut watches = [
+            (ProcessName::P2pool, Watch::default()),
+            (ProcessName::Xmrig, Watch::default()),
+            (ProcessName::XmrigProxy, Watch::default()),
+        ];
+        loop {
+            for (name, watch) in watches.iter_mut() {
+                self.tick(*name, watch);
+            }
+            sleep!(1000);
+        }
+    }
+
+    fn process(&self, name: ProcessName) -> Arc<Mutex<Process>> {
+        let helper = lock!(self.helper);
+        match name {
+            ProcessName::P2pool => Arc::clone(&helper.p2pool),
+            ProcessName::Xmrig => Arc::clone(&helper.xmrig),
+            ProcessName::XmrigProxy => Arc::clone(&helper.xmrig_proxy),
+            ProcessName::Xvb => Arc::clone(&helper.xvb),
+        }
+    }
+
+    fn policy(&self, name: ProcessName) -> SupervisorPolicy {
+        let state = lock!(self.state);
+        match name {
+            ProcessName::P2pool => state.p2pool.supervisor.clone(),
+            ProcessName::Xmrig => state.xmrig.supervisor.clone(),
+            _ => state.xmrig_proxy.supervisor.clone(),
+        }
+    }
+
+    fn tick(&self, name: ProcessName, watch: &mut Watch) {
+        let (state, exit_code, start) = {
+            let p = self.process(name);
+            let p = lock!(p);
+            (p.state, p.exit_code, p.start)
+        };
+
+        // The dependency is back, re-sync XvB.
+        if watch.resync_xvb && state == ProcessState::Alive {
+            watch.resync_xvb = false;
+            if lock!(self.process(ProcessName::Xvb)).is_alive() {
+                self.log(name, "Supervisor | Process is back, re-syncing XvB");
+                let state = lock!(self.state).clone();
+                Helper::restart_xvb(
+                    &self.helper,
+                    &state.xvb,
+                    &state.p2pool,
+                    &state.xmrig,
+                    &state.xmrig_proxy,
+                );
+            }
+        }
+
+        // The updated binary started fine.
+        if state == ProcessState::Alive && self.untested(name) {
+            lock2!(self.state, version)
+                .untested
+                .retain(|n| *n != name.to_string());
+            if let Err(e) = State::save(&mut lock!(self.state), &self.state_path) {
+                warn!("Supervisor | Saving state ... FAIL ... {}", e);
+            }
+        }
+
+        let crashed = state == ProcessState::Failed && exit_code.is_some();
+        if !crashed {
+            watch.retry_at = None;
+            return;
+        }
+        let new_crash = watch.handled != Some(start);
+        watch.handled = Some(start);
+        if new_crash && lock!(self.state).gupax.auto_rollback && self.untested(name) {
+            self.rollback(name);
+        }
+        let policy = self.policy(name);
+        if !policy.enabled {
+            return;
+        }
+
+        // New crash.
+        if new_crash {
+            let code = exit_code.unwrap_or_default();
+            match watch.on_crash(&policy, start.elapsed()) {
+                Decision::Restart { delay, attempt } => {
+                    watch.retry_at = Some(Instant::now() + delay);
+                    self.log(
+                        name,
+                        &format!(
+                            "Supervisor | Crashed with exit code [{}], restarting in [{}s] ({}/{})",
+                            code,
+                            delay.as_secs(),
+                            attempt,
+                            policy.max_restarts
+                        ),
+                    );
+                }
+                Decision::GiveUp => {
+                    self.log(
+                        name,
+                        &format!(
+                            "Supervisor | Crashed with exit code [{}], [{}] restarts in a row, giving up",
+                            code, policy.max_restarts
+                        ),
+                    );
+                }
+            }
+        }
+
+        // Scheduled restart.
+        if watch.retry_at.is_some_and(|at| Instant::now() >= at) {
+            watch.retry_at = None;
+            match self.start(name) {
+                Ok(()) => {
+                    info!("Supervisor | {} restart ... OK", name);
+                    watch.resync_xvb = true;
+                }
+                Err(e) => self.log(name, &format!("Supervisor | Restart failed: {}", e)),
+            }
+        }
+    }
+
+    // Replaced by the last update and not [Alive] since.
+    fn untested(&self, name: ProcessName) -> bool {
+        lock2!(self.state, version)
+            .untested
+            .contains(&name.to_string())
+    }
+
+    fn rollback(&self, name: ProcessName) {
+        let result = Update::get_backup_dir()
+            .and_then(|dir| Update::rollback(&self.state, &self.state_path, &self.exe, &dir));
+        match result {
+            Ok(version) => self.log(
+                name,
+                &format!(
+                    "Supervisor | Crashed on its first start after the update, rolled back to [{}]. Restart Gupaxx to use it",
+                    version
+                ),
+            ),
+            Err(e) => self.log(name, &format!("Supervisor | Rollback failed: {}", e)),
+        }
+    }
+
+    // The process is already dead, so [start_*] is used: [restart_*] waits for
+    // the [Waiting] state, which is only set when a live process is killed.
+    fn start(&self, name: ProcessName) -> Result<(), &'static str> {
+        let mut state = lock!(self.state);
+        let _ = state.update_absolute_path();
+        let state = state.clone();
+        let helper = &self.helper;
+        match name {
+            ProcessName::P2pool => {
+                if !check_p2pool_path(&state.gupax.p2pool_path) {
+                    return Err(P2POOL_PATH_NOT_VALID);
+                }
+                let path = &state.gupax.absolute_p2pool_path;
+                let backup_hosts =
+                    Ping::saved_backup_hosts(&self.ping, &state.p2pool, &self.node_path);
+                Helper::start_p2pool(helper, &state.p2pool, path, backup_hosts);
+            }
+            ProcessName::Xmrig => {
+                if !check_xmrig_path(&state.gupax.xmrig_path) {
+                    return Err(XMRIG_PATH_NOT_VALID);
+                }
+                let path = &state.gupax.absolute_xmrig_path;
+                Helper::start_xmrig(helper, &state.xmrig, path, Arc::clone(&self.sudo));
+            }
+            ProcessName::XmrigProxy => {
+                if !check_xp_path(&state.gupax.xmrig_proxy_path) {
+                    return Err(XMRIG_PROXY_PATH_NOT_VALID);
+                }
+                let path = &state.gupax.absolute_xp_path;
+                Helper::start_xp(helper, &state.xmrig_proxy, &state.xmrig, path);
+            }
+            ProcessName::Xvb => (),
+        }
+        Ok(())
+    }
+
+    // Written to the console of the process and to the log.
+    fn log(&self, name: ProcessName, msg: &str) {
+        info!("{} {}", name, msg);
+        let helper = lock!(self.helper);
+        match name {
+            ProcessName::P2pool => {
+                output_console(&mut lock!(helper.gui_api_p2pool).output, msg, name)
+            }
+            ProcessName::Xmrig => {
+                output_console(&mut lock!(helper.gui_api_xmrig).output, msg, name)
+            }
+            ProcessName::XmrigProxy => {
+                output_console(&mut lock!(helper.gui_api_xp).output, msg, name)
+            }
+            ProcessName::Xvb => output_console(&mut lock!(helper.gui_api_xvb).output, msg, name),
+        }
+    }
+}
@@ -231,6 +231,7 @@ Uptime         = 0h 2m 4s
             average_effort: 100.000,
             current_effort: 200.000,
             connections: 1234,
+            workers: vec![],
         };
         let network = PrivP2poolNetworkApi {
             difficulty: 300_000_000_000,
@@ -314,6 +315,75 @@ Uptime         = 0h 2m 4s
         assert!(process.lock().unwrap().state == ProcessState::Alive);
     }
 
+    #[test]
+    fn p2pool_local_api_workers() {
+        use crate::helper::p2pool::P2poolWorker;
+        use crate::helper::PubP2poolApi;
+        use std::sync::{Arc, Mutex};
+        let data = r#"{
+				"hashrate_15m": 12000,
+				"hashrate_1h": 11000,
+				"hashrate_24h": 10000,
+				"shares_found": 3,
+				"average_effort": 100.0,
+				"current_effort": 50.0,
+				"connections": 2,
+				"workers": [
+					"192.168.1.10:51234,3600,120000,8000,rig1",
+					"[::1]:40000,60,15000,1000,rig,with,commas",
+					"invalid"
+				]
+			}"#;
+        let local = PrivP2poolLocalApi::from_str(data).unwrap();
+        let public = Arc::new(Mutex::new(PubP2poolApi::new()));
+        PubP2poolApi::update_from_local(&public, local);
+        let workers = public.lock().unwrap().workers.clone();
+        assert_eq!(workers.len(), 2);
+        assert_eq!(
+            workers[0],
+            P2poolWorker {
+                address: "192.168.1.10:51234".to_string(),
+                uptime: 3600,
+                difficulty: 120000,
+                hashrate: 8000,
+                name: "rig1".to_string(),
+                missing: false,
+            }
+        );
+        assert_eq!(workers[1].address, "[::1]:40000");
+        assert_eq!(workers[1].name, "rig,with,commas");
+
+        // rig1 reconnects on another port, the other one disappears.
+        let data = r#"{
+				"hashrate_15m": 8000,
+				"hashrate_1h": 8000,
+				"hashrate_24h": 8000,
+				"shares_found": 3,
+				"average_effort": 100.0,
+				"current_effort": 50.0,
+				"connections": 1,
+				"workers": ["192.168.1.10:51299,10,120000,7000,rig1"]
+			}"#;
+        let local = PrivP2poolLocalApi::from_str(data).unwrap();
+        PubP2poolApi::update_from_local(&public, local);
+        let workers = public.lock().unwrap().workers.clone();
+        assert_eq!(workers.len(), 2);
+        assert_eq!(workers[0].address, "192.168.1.10:51299");
+        assert!(!workers[0].missing);
+        assert_eq!(workers[1].name, "rig,with,commas");
+        assert!(workers[1].missing);
+
+        // Still missing, and back once it reconnects.
+        let merged = P2poolWorker::merge(&workers, vec![workers[0].clone()]);
+        assert!(merged[1].missing);
+        let back = P2poolWorker::from_str("[::1]:40001,1,15000,900,rig,with,commas").unwrap();
+        let merged = P2poolWorker::merge(&merged, vec![back]);
+        assert_eq!(merged.len(), 2);
+        assert!(!merged[0].missing);
+        assert_eq!(merged[0].name, "rig,with,commas");
+        assert!(merged[1].missing);
+    }
+
     #[test]
     fn serde_priv_p2pool_local_api() {
         let data = r#"{
@@ -508,8 +578,12 @@ Uptime         = 0h 2m 4s
     };
 
     use crate::{
-        disk::state::P2pool,
-        helper::{p2pool::PubP2poolApi, xrig::xmrig::PubXmrigApi, xvb::rounds::XvbRound},
+        disk::state::{P2pool, Xvb},
+        helper::{
+            p2pool::PubP2poolApi,
+            xrig::xmrig::PubXmrigApi,
+            xvb::{rounds::XvbRound, strategy::XvbMode},
+        },
         macros::lock,
         XVB_TIME_ALGO,
     };
@@ -533,18 +607,20 @@ Uptime         = 0h 2m 4s
         let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
         let gui_api_xmrig = Arc::new(Mutex::new(PubXmrigApi::new()));
         let state_p2pool = P2pool::default();
+        let state_xvb = Xvb::default();
         lock!(gui_api_p2pool).p2pool_difficulty_u64 = 95000000;
         let share = 1;
         // verify that if one share found (enough for vip round) but not enough for donor round, no time will be given to xvb, except if in hero mode.
         // 15mn average HR of xmrig is 5kH/s
         lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 0.0;
         lock!(gui_api_xmrig).hashrate_raw_15m = 5000.0;
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = false;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         // verify that default mode will give x seconds
         assert_eq!(given_time, 0);
@@ -559,12 +635,13 @@ Uptime         = 0h 2m 4s
             / 1000.0;
         assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::Vip));
         // verify that hero mode will give x seconds
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = true;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         assert_eq!(given_time, 45);
         // verify that right round should be detected.
@@ -578,12 +655,13 @@ Uptime         = 0h 2m 4s
         // verify that if one share and not enough for donor vip round (should be in donor round), right amount of time will be given to xvb for default and hero mode
         lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 0.0;
         lock!(gui_api_xmrig).hashrate_raw_15m = 8000.0;
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = false;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         // verify that default mode will give x seconds
         assert_eq!(given_time, 75);
@@ -598,12 +676,13 @@ Uptime         = 0h 2m 4s
             / 1000.0;
         assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::Donor));
         // verify that hero mode will give x seconds
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = true;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         assert_eq!(given_time, 253);
         // verify that right round should be detected.
@@ -617,12 +696,13 @@ Uptime         = 0h 2m 4s
         // verify that if one share and not enough for donor whale round(should be in donor vip), right amount of time will be given to xvb for default and hero mode
         lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 0.0;
         lock!(gui_api_xmrig).hashrate_raw_15m = 19000.0;
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = false;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         // verify that default mode will give x seconds
         assert_eq!(given_time, 316);
@@ -637,12 +717,13 @@ Uptime         = 0h 2m 4s
             / 1000.0;
         assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::DonorVip));
         // verify that hero mode will give x seconds
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = true;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         assert_eq!(given_time, 454);
         // verify that right round should be detected.
@@ -656,12 +737,13 @@ Uptime         = 0h 2m 4s
         // verify that if one share and not enough for donor mega round, right amount of time will be given to xvb for default and hero mode
         lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 0.0;
         lock!(gui_api_xmrig).hashrate_raw_15m = 105000.0;
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = false;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         // verify that default mode will give x seconds
         assert_eq!(given_time, 572);
@@ -676,12 +758,13 @@ Uptime         = 0h 2m 4s
             / 1000.0;
         assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::DonorWhale));
         // verify that hero mode will give x seconds
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = true;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         assert_eq!(given_time, 573);
         // verify that right round should be detected.
@@ -695,12 +778,13 @@ Uptime         = 0h 2m 4s
         // verify that if one share and enough for donor mega round, right amount of time will be given to xvb for default and hero mode
         lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 0.0;
         lock!(gui_api_xmrig).hashrate_raw_15m = 1205000.0;
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = false;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         // verify that default mode will give x seconds
         assert_eq!(given_time, 498);
@@ -715,12 +799,13 @@ Uptime         = 0h 2m 4s
             / 1000.0;
         assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::DonorMega));
         // verify that hero mode will give x seconds
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = true;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         assert_eq!(given_time, 597);
         // verify that right round should be detected.
@@ -735,12 +820,13 @@ Uptime         = 0h 2m 4s
         lock!(gui_api_xvb).output.clear();
         lock!(gui_api_xmrig).hashrate_raw_15m = 12500.0;
         lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 5.0;
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = false;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         // verify that default mode will give x seconds
         assert_eq!(given_time, 240);
@@ -758,12 +844,13 @@ Uptime         = 0h 2m 4s
         assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::DonorVip));
         // verify that hero mode will give x seconds
         lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 5.0;
-        lock!(gui_api_xvb).stats_priv.runtime_hero_mode = true;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
         let given_time = calcul_donated_time(
             lock!(gui_api_xmrig).hashrate_raw_15m,
             &gui_api_p2pool,
             &gui_api_xvb,
             &state_p2pool,
+            &state_xvb,
         );
         assert_eq!(given_time, 378);
         // verify that right round should be detected.
@@ -777,4 +864,1929 @@ Uptime         = 0h 2m 4s
                 / 1000.0;
         assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::DonorVip));
     }
+    #[test]
+    fn algorithm_stratum_hashrate() {
+        use crate::helper::xvb::strategy::hashrate_divergence;
+        let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
+        let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
+        let state_p2pool = P2pool::default();
+        let mut state_xvb = Xvb {
+            mode: XvbMode::Hero,
+            ..Default::default()
+        };
+        lock!(gui_api_p2pool).p2pool_difficulty_u64 = 95000000;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
+        // 4kH/s of other rigs on the stratum, the sidechain estimate does not see them yet.
+        lock!(gui_api_p2pool).user_p2pool_hashrate_u64 = 4000;
+        lock!(gui_api_p2pool).sidechain_ehr = 0.0;
+        let estimate = calcul_donated_time(
+            8000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        state_xvb.stratum_hashrate = true;
+        let stratum = calcul_donated_time(
+            8000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        // The other rigs keep the share, more time can be donated.
+        assert!(stratum > estimate, "{stratum} > {estimate}");
+        assert!(stratum < XVB_TIME_ALGO);
+        // The sidechain sees a rig that is not on the stratum.
+        lock!(gui_api_p2pool).sidechain_ehr = 10000.0;
+        calcul_donated_time(
+            8000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        assert!(lock!(gui_api_xvb)
+            .output
+            .contains("may not be pointed at this p2pool"));
+
+        assert_eq!(hashrate_divergence(4000.0, 0.0), None);
+        assert_eq!(hashrate_divergence(10000.0, 9000.0), None);
+        assert_eq!(hashrate_divergence(4000.0, 10000.0), Some(0.6));
+        assert_eq!(hashrate_divergence(10000.0, 4000.0), Some(0.6));
+    }
+
+    #[test]
+    fn algorithm_manual_overrides() {
+        use crate::helper::xvb::algorithm::XvbSplit;
+        let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
+        let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
+        let state_p2pool = P2pool::default();
+        let mut state_xvb = Xvb::default();
+        lock!(gui_api_p2pool).p2pool_difficulty_u64 = 95000000;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
+        // fixed split, the estimates are not used.
+        state_xvb.manual_split = true;
+        state_xvb.split = 50;
+        let given_time = calcul_donated_time(
+            5000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        assert_eq!(given_time, 300);
+        assert!(lock!(gui_api_xvb)
+            .output
+            .contains("fixed split of 300 seconds"));
+        state_xvb.split_unit = XvbSplit::Seconds;
+        state_xvb.split = 700;
+        let given_time = calcul_donated_time(
+            5000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        assert_eq!(given_time, XVB_TIME_ALGO);
+        state_xvb.manual_split = false;
+        // fixed outside HR enough to keep the share, everything can be donated in hero mode.
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
+        state_xvb.manual_outside_hr = true;
+        state_xvb.outside_hr = 1000000;
+        let given_time = calcul_donated_time(
+            5000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        assert_eq!(given_time, XVB_TIME_ALGO);
+        assert!(lock!(gui_api_xvb)
+            .output
+            .contains("fixed outside HR of 1,000.000 kH/s"));
+        state_xvb.manual_outside_hr = false;
+        // mega round is reachable, but the cap is VIP Donor, in default and hero mode.
+        lock!(gui_api_xvb).stats_priv.donor_1hr_avg = 0.0;
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Default;
+        state_xvb.manual_round = true;
+        state_xvb.round_cap = XvbRound::DonorVip;
+        let given_time = calcul_donated_time(
+            1205000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        assert!(lock!(gui_api_xvb)
+            .output
+            .contains("aiming for VIP Donor round at most, Mega Donor round is reachable"));
+        lock!(gui_api_xvb).stats_priv.runtime_mode = XvbMode::Hero;
+        let hero_time = calcul_donated_time(
+            1205000.0,
+            &gui_api_p2pool,
+            &gui_api_xvb,
+            &state_p2pool,
+            &state_xvb,
+        );
+        assert_eq!(given_time, hero_time);
+        let share = 1;
+        lock!(gui_api_xvb).stats_priv.donor_1hr_avg =
+            ((given_time as f32 / XVB_TIME_ALGO as f32) * 1205000.0) / 1000.0;
+        lock!(gui_api_xvb).stats_priv.donor_24hr_avg =
+            ((given_time as f32 / XVB_TIME_ALGO as f32) * 1205000.0) / 1000.0;
+        assert_eq!(round_type(share, &gui_api_xvb), Some(XvbRound::DonorVip));
+    }
+
+    #[test]
+    fn xvb_strategies() {
+        use crate::helper::xvb::strategy::{
+            decide, minimum_hashrate_confidence, XvbSnapshot, XvbStrategy,
+        };
+        let mut snapshot = XvbSnapshot {
+            lhr: 12500.0,
+            difficulty: 95000000,
+            mini: true,
+            xvb_1h: 5000.0,
+            ..Default::default()
+        };
+        let default = decide(&snapshot);
+        assert_eq!(default.xvb, 240);
+        assert_eq!(default.p2pool + default.xvb, XVB_TIME_ALGO);
+        snapshot.mode = XvbMode::Hero;
+        let hero = decide(&snapshot);
+        assert!(hero.xvb > default.xvb);
+        assert!(hero
+            .messages
+            .contains(&"Hero mode is enabled for this decision".to_string()));
+        // keeping the share with 95% probability needs more HR than the 5% buffer.
+        snapshot.mode = XvbMode::Probabilistic;
+        let probabilistic = decide(&snapshot);
+        assert!(probabilistic.xvb <= default.xvb);
+        assert!(probabilistic
+            .messages
+            .iter()
+            .any(|m| m.contains("a share is kept in the PPLNS window with 95% probability")));
+        // the probability of the split is shown whatever the mode.
+        for allocation in [&default, &hero, &probabilistic] {
+            assert!(allocation
+                .messages
+                .last()
+                .unwrap()
+                .contains("probability to keep a share"));
+        }
+        // 12.5 kH/s is not enough for 95%, everything stays on p2pool.
+        assert_eq!(probabilistic.xvb, 0);
+        assert!(probabilistic.share_probability > default.share_probability);
+        assert!(hero.share_probability < default.share_probability);
+        // the confidence is chosen by the user.
+        snapshot.config.share_confidence = 60;
+        let lower = decide(&snapshot);
+        assert!(lower.xvb > probabilistic.xvb);
+        assert!(lower.share_probability >= 0.60);
+        snapshot.config.share_confidence = 95;
+        // with one share on average in the window, the probability to have one is 63%.
+        let one_share = 95000000.0 / (2160.0 * 10.0);
+        let min = minimum_hashrate_confidence(95000000, true, 0.0, 1.0 - (-1.0f32).exp());
+        assert!((min - one_share).abs() < 1.0, "{min} {one_share}");
+        // the fixed split override replaces any strategy.
+        snapshot.config.manual_split = true;
+        snapshot.config.split = 10;
+        assert_eq!(decide(&snapshot).xvb, 60);
+        // new strategies only need the snapshot.
+        struct Half;
+        impl XvbStrategy for Half {
+            fn decide(&self, snapshot: &XvbSnapshot) -> crate::helper::xvb::strategy::Allocation {
+                let xvb = if snapshot.lhr > 0.0 {
+                    XVB_TIME_ALGO / 2
+                } else {
+                    0
+                };
+                crate::helper::xvb::strategy::Allocation {
+                    p2pool: XVB_TIME_ALGO - xvb,
+                    xvb,
+                    share_probability: 0.0,
+                    messages: vec![],
+                }
+            }
+        }
+        assert_eq!(Half.decide(&snapshot).xvb, 300);
+    }
+
+    #[test]
+    fn xvb_simulate() {
+        use crate::helper::xvb::simulate::{parse_timeline, report, simulate, synthetic_timeline};
+        let config = Xvb::default();
+        let timeline = synthetic_timeline(12500.0, 95000000, 1, 144);
+        let default = simulate(&timeline, XvbMode::Default, &config, true);
+        let hero = simulate(&timeline, XvbMode::Hero, &config, true);
+        assert_eq!(default.len(), 144);
+        assert_eq!(default[0].xvb, 48);
+        let donated = |cycles: &[crate::helper::xvb::simulate::SimulatedCycle]| {
+            cycles.iter().map(|c| c.xvb).sum::<u32>()
+        };
+        assert!(donated(&hero) > donated(&default));
+        // after 24 hours, the averages are the ones of the decisions.
+        let last = hero.last().unwrap();
+        assert!((last.donor_24h - last.donor_1h).abs() < 0.001);
+        assert_eq!(last.round, Some(XvbRound::Donor));
+        // donating more keeps the share with less probability.
+        assert!(last.share_probability < default.last().unwrap().share_probability);
+        let text = report(&hero, false);
+        assert!(text.contains("144 cycles"));
+        assert!(text.contains("Rounds: VIP"));
+
+        let timeline = parse_timeline(
+            r#"{"hashrate":12500,"difficulty":95000000,"shares":1,"donor_1h":5.0,"donor_24h":5.0}
+
+{"hashrate":12500,"difficulty":95000000,"shares":0}"#,
+        )
+        .unwrap();
+        assert_eq!(timeline.len(), 2);
+        let replay = simulate(&timeline, XvbMode::Default, &config, true);
+        // recorded averages are used as they are.
+        assert_eq!(replay[0].donor_1h, 5.0);
+        assert_eq!(replay[0].round, Some(XvbRound::Donor));
+        // no share, everything stays on p2pool.
+        assert_eq!(replay[1].xvb, 0);
+        assert_eq!(replay[1].round, None);
+        let err = parse_timeline("{\"hashrate\":1}\nbad").unwrap_err();
+        assert!(err.to_string().starts_with("line 2"));
+    }
+
+    fn new_helper() -> Arc<Mutex<Helper>> {
+        use crate::helper::{
+            p2pool::ImgP2pool, xrig::xmrig::ImgXmrig, xrig::xmrig_proxy::PubXmrigProxyApi, Sys,
+        };
+        use crate::{disk::gupax_p2pool_api::GupaxP2poolApi, macros::arc_mut};
+        arc_mut!(Helper::new(
+            std::time::Instant::now(),
+            arc_mut!(Sys::new()),
+            arc_mut!(Process::new(
+                ProcessName::P2pool,
+                String::new(),
+                PathBuf::new()
+            )),
+            arc_mut!(Process::new(
+                ProcessName::Xmrig,
+                String::new(),
+                PathBuf::new()
+            )),
+            arc_mut!(Process::new(
+                ProcessName::XmrigProxy,
+                String::new(),
+                PathBuf::new()
+            )),
+            arc_mut!(Process::new(
+                ProcessName::Xvb,
+                String::new(),
+                PathBuf::new()
+            )),
+            arc_mut!(PubP2poolApi::new()),
+            arc_mut!(PubXmrigApi::new()),
+            arc_mut!(PubXvbApi::new()),
+            arc_mut!(PubXmrigProxyApi::new()),
+            arc_mut!(ImgP2pool::new()),
+            arc_mut!(ImgXmrig::new()),
+            arc_mut!(GupaxP2poolApi::new())
+        ))
+    }
+
+    #[test]
+    fn http_api() {
+        let helper = new_helper();
+        {
+            let h = lock!(helper);
+            lock!(h.gui_api_p2pool).hashrate_15m_u64 = 1234;
+            lock!(h.gui_api_p2pool).shares_found = Some(2);
+            lock!(h.gui_api_xmrig).hashrate_raw_15m = 5000.0;
+            lock!(h.gui_api_xmrig).accepted_raw = 10;
+            lock!(h.gui_api_xvb).stats_priv.round_participate = Some(XvbRound::DonorVip);
+        }
+        thread::spawn(move || http_api_requests(helper))
+            .join()
+            .unwrap();
+    }
+    #[tokio::main]
+    async fn http_api_requests(helper: Arc<Mutex<Helper>>) {
+        use crate::components::node::Ping;
+        use crate::disk::state::State;
+        use crate::helper::http_api::HttpApi;
+        use crate::macros::arc_mut;
+        use crate::utils::sudo::SudoState;
+        let api = HttpApi::new(
+            &helper,
+            &arc_mut!(State::new()),
+            &arc_mut!(SudoState::new()),
+            &arc_mut!(Ping::new()),
+            &std::env::temp_dir().join("gupaxx_test_http_api_node.toml"),
+            "testtoken",
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api.router()).await.unwrap() });
+        let client = Client::new();
+
+        // GET
+        let status: serde_json::Value = client
+            .get(format!("{url}/status"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(status["p2pool"]["hashrate_15m"], 1234);
+        assert_eq!(status["p2pool"]["shares_found"], 2);
+        assert_eq!(status["p2pool"]["state"], "Dead");
+        assert_eq!(status["xmrig"]["hashrate_15m"], 5000.0);
+        assert_eq!(status["xmrig"]["accepted"], 10);
+        assert_eq!(status["xvb"]["round_type"], "VIP Donor");
+        assert!(status["xvb"]["current_node"].is_null());
+        let xmrig: serde_json::Value = client
+            .get(format!("{url}/xmrig"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(xmrig, status["xmrig"]);
+        let r = client.get(format!("{url}/monerod")).send().await.unwrap();
+        assert_eq!(r.status(), 404);
+
+        // POST
+        let post = |path: &str, token: Option<&str>| {
+            let request = client.post(format!("{url}/{path}"));
+            match token {
+                Some(t) => request.bearer_auth(t),
+                None => request,
+            }
+            .send()
+        };
+        assert_eq!(post("p2pool/stop", None).await.unwrap().status(), 401);
+        for wrong in ["wrong", "testtoke", "testtoken2", ""] {
+            assert_eq!(
+                post("p2pool/stop", Some(wrong)).await.unwrap().status(),
+                401
+            );
+        }
+        assert_eq!(
+            post("monerod/stop", Some("testtoken"))
+                .await
+                .unwrap()
+                .status(),
+            404
+        );
+        assert_eq!(
+            post("p2pool/pause", Some("testtoken"))
+                .await
+                .unwrap()
+                .status(),
+            404
+        );
+        // Nothing is running, so nothing can be stopped or restarted.
+        assert_eq!(
+            post("p2pool/stop", Some("testtoken"))
+                .await
+                .unwrap()
+                .status(),
+            409
+        );
+        assert_eq!(
+            post("xvb/restart", Some("testtoken"))
+                .await
+                .unwrap()
+                .status(),
+            409
+        );
+        // Default state has no P2Pool address.
+        assert_eq!(
+            post("p2pool/start", Some("testtoken"))
+                .await
+                .unwrap()
+                .status(),
+            400
+        );
+    }
+    #[test]
+    fn metrics() {
+        use crate::helper::metrics::render;
+        let helper = new_helper();
+        {
+            let h = lock!(helper);
+            let mut p2pool = lock!(h.gui_api_p2pool);
+            p2pool.hashrate_15m_u64 = 1234;
+            p2pool.user_p2pool_hashrate_u64 = 1000;
+            p2pool.current_effort_f32 = 54.5;
+            p2pool.connections_u32 = 10;
+            p2pool.sidechain_shares = 3;
+            drop(p2pool);
+            let mut xmrig = lock!(h.gui_api_xmrig);
+            xmrig.hashrate_raw = 5000.5;
+            xmrig.accepted_raw = 10;
+            xmrig.rejected_raw = 1;
+            drop(xmrig);
+            lock!(h.gui_api_xp).hashrate_24h = 20000.0;
+            let mut xvb = lock!(h.gui_api_xvb);
+            xvb.stats_priv.donor_1hr_avg = 1.5;
+            xvb.time_donated = 120;
+            drop(xvb);
+            lock!(h.pub_sys).system_memory_total_u64 = 16_000_000_000;
+        }
+        let metrics = render(&helper);
+        for line in [
+            "# TYPE gupaxx_p2pool_hashrate gauge",
+            "gupaxx_p2pool_hashrate{window=\"15m\"} 1234",
+            "gupaxx_p2pool_hashrate{window=\"1h\"} 1000",
+            "gupaxx_p2pool_current_effort_percent 54.5",
+            "gupaxx_p2pool_connections 10",
+            "gupaxx_p2pool_sidechain_shares 3",
+            "gupaxx_process_state{process=\"p2pool\",state=\"Dead\"} 1",
+            "gupaxx_xmrig_hashrate{window=\"10s\"} 5000.5",
+            "# TYPE gupaxx_xmrig_accepted_shares_total counter",
+            "gupaxx_xmrig_accepted_shares_total 10",
+            "gupaxx_xmrig_rejected_shares_total 1",
+            "gupaxx_xmrig_proxy_hashrate{window=\"24h\"} 20000",
+            "gupaxx_xvb_donor_hashrate{window=\"1h\"} 1500",
+            "gupaxx_xvb_time_donated_seconds 120",
+            "gupaxx_system_memory_total_bytes 16000000000",
+        ] {
+            assert!(metrics.lines().any(|l| l == line), "missing: {line}");
+        }
+        // No share info yet, so no sample at all rather than a wrong 0.
+        assert!(!metrics.contains("gupaxx_p2pool_shares_found_total"));
+        // Every sample must be a valid [name{labels} value] line.
+        for line in metrics.lines().filter(|l| !l.starts_with('#')) {
+            let (_, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "invalid: {line}");
+        }
+    }
+
+    #[test]
+    fn supervisor_backoff() {
+        use crate::disk::state::SupervisorPolicy;
+        use crate::helper::supervisor::{backoff, Decision, Watch, SUPERVISOR_MAX_BACKOFF};
+        use std::time::Duration;
+        let policy = SupervisorPolicy {
+            enabled: true,
+            max_restarts: 3,
+            backoff: 5,
+            crash_loop_window: 600,
+        };
+        let short = Duration::from_secs(10);
+        let mut watch = Watch::default();
+        // Exponential backoff until [max_restarts] is reached.
+        for (attempt, delay) in [(1, 5), (2, 10), (3, 20)] {
+            assert_eq!(
+                watch.on_crash(&policy, short),
+                Decision::Restart {
+                    delay: Duration::from_secs(delay),
+                    attempt
+                }
+            );
+        }
+        assert_eq!(watch.on_crash(&policy, short), Decision::GiveUp);
+        // Staying alive longer than the window resets the count.
+        assert_eq!(
+            watch.on_crash(&policy, Duration::from_secs(600)),
+            Decision::Restart {
+                delay: Duration::from_secs(5),
+                attempt: 1
+            }
+        );
+        // Capped, even with absurd values.
+        assert_eq!(backoff(5, 10), Duration::from_secs(SUPERVISOR_MAX_BACKOFF));
+        assert_eq!(
+            backoff(u64::MAX, u8::MAX),
+            Duration::from_secs(SUPERVISOR_MAX_BACKOFF)
+        );
+    }
+
+    #[test]
+    fn node_health() {
+        use crate::components::node::GetInfo;
+        use crate::disk::node::Node;
+        use crate::helper::node_health::{
+            next_healthy, Probe, Tracker, Verdict, NODE_HEALTH_MAX_FAILS, NODE_HEALTH_STALL,
+        };
+        use std::time::{Duration, Instant};
+        // Same answer as [Ping] gets, with the height.
+        let data = r#"{"id":"0","jsonrpc":"2.0","result":{"height":3200000,"mainnet":true,"synchronized":true,"status":"OK"}}"#;
+        let rpc: GetInfo = serde_json::from_str(data).unwrap();
+        assert_eq!(rpc.result.height, 3200000);
+
+        let ok = |height| {
+            Some(Probe {
+                synchronized: true,
+                height,
+            })
+        };
+        let node = Node::localhost();
+        let now = Instant::now();
+        let mut tracker = Tracker::default();
+        assert_eq!(tracker.check(&node, ok(100), 100, now), Verdict::Healthy);
+        assert_eq!(tracker.check(&node, ok(96), 100, now), Verdict::Behind(4));
+        assert_eq!(tracker.check(&node, None, 100, now), Verdict::Unreachable);
+        assert_eq!(tracker.fails, NODE_HEALTH_MAX_FAILS);
+        // A healthy check resets the count.
+        assert_eq!(tracker.check(&node, ok(101), 101, now), Verdict::Healthy);
+        assert_eq!(tracker.fails, 0);
+        // Same height for too long.
+        let later = now + Duration::from_secs(NODE_HEALTH_STALL);
+        assert_eq!(tracker.check(&node, ok(101), 101, later), Verdict::Stalled);
+        let not_synced = Some(Probe {
+            synchronized: false,
+            height: 101,
+        });
+        assert_eq!(
+            tracker.check(&node, not_synced, 101, later),
+            Verdict::NotSynchronized
+        );
+
+        // Next healthy node after the active one, wrapping around.
+        let probes = [ok(100), None, not_synced, ok(90), ok(99)];
+        assert_eq!(next_healthy(Some(0), &probes, 100), Some(4));
+        assert_eq!(next_healthy(Some(4), &probes, 100), Some(0));
+        assert_eq!(next_healthy(Some(0), &[ok(100), None], 100), None);
+    }
+    #[test]
+    fn xvb_reevaluation_triggers() {
+        use crate::helper::xvb::{
+            algorithm::{record_interrupted_cycle, CycleConditions},
+            nodes::{XvbNode, XvbNodeConfig},
+            PubXvbApi,
+        };
+        use crate::macros::lock;
+        use std::sync::{Arc, Mutex};
+        let start = CycleConditions {
+            shares: 2,
+            difficulty: 100_000_000,
+            hashrate: 10000.0,
+            node: XvbNode::Xvb(XvbNodeConfig::europe()),
+            node_offline: false,
+            time_donated: 240,
+        };
+        // small variations do not stop the cycle.
+        let now = CycleConditions {
+            shares: 1,
+            difficulty: 110_000_000,
+            hashrate: 8000.0,
+            ..start.clone()
+        };
+        assert_eq!(start.reevaluation_trigger(&now), None);
+        let now = CycleConditions {
+            shares: 0,
+            ..start.clone()
+        };
+        assert!(start.reevaluation_trigger(&now).is_some());
+        // without share at the start, nothing can leave the window.
+        let no_share = CycleConditions {
+            shares: 0,
+            ..start.clone()
+        };
+        assert_eq!(no_share.reevaluation_trigger(&no_share), None);
+        let now = CycleConditions {
+            difficulty: 130_000_000,
+            ..start.clone()
+        };
+        assert!(start.reevaluation_trigger(&now).is_some());
+        let now = CycleConditions {
+            difficulty: 70_000_000,
+            ..start.clone()
+        };
+        assert!(start.reevaluation_trigger(&now).is_some());
+        let now = CycleConditions {
+            hashrate: 4000.0,
+            ..start.clone()
+        };
+        assert!(start.reevaluation_trigger(&now).is_some());
+        // a faster node is not an offline one.
+        let now = CycleConditions {
+            node: XvbNode::Xvb(XvbNodeConfig::north_america()),
+            ..start.clone()
+        };
+        let reason = start.reevaluation_trigger(&now).unwrap();
+        assert!(reason.starts_with("Preferred XvB node changed from "));
+        assert!(!reason.contains("offline"));
+        let now = CycleConditions {
+            node_offline: true,
+            ..now
+        };
+        assert!(start
+            .reevaluation_trigger(&now)
+            .unwrap()
+            .contains("went offline"));
+        // the node does not matter if nothing is donated.
+        let p2pool_only = CycleConditions {
+            time_donated: 0,
+            ..start.clone()
+        };
+        let now = CycleConditions {
+            node: XvbNode::P2pool,
+            ..p2pool_only
+        };
+        assert_eq!(p2pool_only.reevaluation_trigger(&now), None);
+
+        // interrupted after 360s on p2pool and 60s on XvB.
+        let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
+        record_interrupted_cycle(&gui_api_xvb, &start, 420);
+        let api = lock!(gui_api_xvb);
+        assert_eq!(
+            api.p2pool_sent_last_hour_samples.0.back(),
+            Some(&(10000.0 * 360.0 / 420.0))
+        );
+        assert_eq!(
+            api.xvb_sent_last_hour_samples.0.back(),
+            Some(&(10000.0 * 60.0 / 420.0))
+        );
+    }
+    // the watchdog locks the APIs of the processes while the algorithm runs.
+    #[tokio::test]
+    async fn xvb_reevaluation_watchdog() {
+        use crate::helper::p2pool::PubP2poolApi;
+        use crate::helper::xrig::{xmrig::PubXmrigApi, xmrig_proxy::PubXmrigProxyApi};
+        use crate::helper::xvb::{
+            algorithm::CycleConditions, check_reevaluation_triggers, split::SplitXmrig, PubXvbApi,
+        };
+        use crate::macros::{arc_mut, lock};
+        use std::path::PathBuf;
+        use tokio::time::Instant;
+        let process = arc_mut!(Process::new(
+            ProcessName::Xvb,
+            String::new(),
+            PathBuf::new()
+        ));
+        lock!(process).state = ProcessState::Alive;
+        let gui_api = arc_mut!(PubXvbApi::new());
+        let gui_api_p2pool = arc_mut!(PubP2poolApi::new());
+        lock!(gui_api_p2pool).sidechain_shares = 2;
+        lock!(gui_api_p2pool).p2pool_difficulty_u64 = 100_000_000;
+        let gui_api_xmrig = arc_mut!(PubXmrigApi::new());
+        lock!(gui_api_xmrig).hashrate_raw = 10000.0;
+        let gui_api_xp = arc_mut!(PubXmrigProxyApi::new());
+        let split_xmrig = arc_mut!(SplitXmrig::default());
+        let handle_algo = arc_mut!(Some(tokio::spawn(std::future::pending::<()>())));
+        let start = CycleConditions {
+            shares: 2,
+            difficulty: 100_000_000,
+            hashrate: 10000.0,
+            node: lock!(gui_api).stats_priv.node.clone(),
+            node_offline: false,
+            time_donated: 240,
+        };
+        let cycle_conditions = arc_mut!(Some(start));
+        let last_algorithm = arc_mut!(Instant::now());
+        let time_donated = arc_mut!(240);
+        let check = || {
+            check_reevaluation_triggers(
+                &process,
+                &gui_api,
+                &gui_api_p2pool,
+                &gui_api_xmrig,
+                &gui_api_xp,
+                &split_xmrig,
+                &handle_algo,
+                &cycle_conditions,
+                &last_algorithm,
+                &time_donated,
+                false,
+            )
+        };
+        // nothing changed, the cycle continues.
+        check();
+        assert!(lock!(cycle_conditions).is_some());
+        // the share left the window, the cycle is stopped.
+        lock!(gui_api_p2pool).sidechain_shares = 0;
+        check();
+        assert!(lock!(cycle_conditions).is_none());
+        assert!(lock!(gui_api)
+            .output
+            .contains("stopping the current cycle to decide again"));
+        let handle = lock!(handle_algo).take().unwrap();
+        assert!(handle.await.unwrap_err().is_cancelled());
+    }
+    #[test]
+    fn xvb_round_tracker() {
+        use crate::disk::history::XvbDecision;
+        use crate::helper::xvb::{
+            priv_stats::XvbPrivStats,
+            public_stats::XvbPubStats,
+            rounds::{count_rounds, RoundTracker, XvbRound},
+        };
+        let address = "4A5Dwt2qKwKEQrZfo4aBkSNtvDDAzSFbAJcyFkdW5RwDh9U4WgeZrgKT4hUoE2gv8h6NmsNMTyjsEL8eSLMbABds5rYFWnw";
+        let mut stats_pub = XvbPubStats {
+            winner: "48aF2pFf...9zNQmVh5".to_string(),
+            roll_winner: 1,
+            roll_round: 2,
+            ..Default::default()
+        };
+        let mut stats_priv = XvbPrivStats {
+            donor_1hr_avg: 12.0,
+            donor_24hr_avg: 11.0,
+            round_participate: Some(XvbRound::DonorVip),
+            ..Default::default()
+        };
+        let decision = |time, donated| XvbDecision {
+            time,
+            donated,
+            ..Default::default()
+        };
+        let decisions = [decision(50, 600), decision(100, 300), decision(700, 200)];
+        let mut tracker = RoundTracker::default();
+        // the first draw seen only starts following the round.
+        assert_eq!(
+            tracker.update(100, &stats_pub, &stats_priv, address, &decisions),
+            None
+        );
+        assert_eq!(
+            tracker.update(700, &stats_pub, &stats_priv, address, &decisions),
+            None
+        );
+        // new draw, won by this address.
+        stats_pub.winner = Helper::head_tail_of_monero_address(address);
+        stats_pub.roll_winner = 3;
+        stats_priv.round_participate = None;
+        let record = tracker
+            .update(1300, &stats_pub, &stats_priv, address, &decisions)
+            .unwrap();
+        assert_eq!(record.time, 1300);
+        assert_eq!(record.round, Some(XvbRound::DonorVip));
+        assert_eq!(record.donor_1h, 12.0);
+        assert_eq!(record.donated, 500);
+        assert!(record.won);
+        assert_eq!(record.roll_winner, 3);
+        // the next round starts without share.
+        stats_pub.winner = "48aF2pFf...9zNQmVh5".to_string();
+        let record = tracker
+            .update(1900, &stats_pub, &stats_priv, address, &decisions)
+            .unwrap();
+        assert_eq!(record.round, None);
+        assert_eq!(record.donated, 0);
+        assert!(!record.won);
+
+        let rounds = [
+            Some(XvbRound::Donor),
+            None,
+            Some(XvbRound::Donor),
+            Some(XvbRound::Vip),
+        ];
+        assert_eq!(
+            count_rounds(rounds.iter()),
+            vec![
+                (None, 1),
+                (Some(XvbRound::Vip), 1),
+                (Some(XvbRound::Donor), 2)
+            ]
+        );
+    }
+    #[test]
+    fn xvb_nodes() {
+        use crate::components::node::TIMEOUT_NODE_PING;
+        use crate::helper::xvb::nodes::{select_node, XvbNode, XvbNodeConfig};
+        use crate::regex::detect_new_node_xmrig;
+        let custom = XvbNodeConfig {
+            name: "Custom".to_string(),
+            host: "xvb.example.com".to_string(),
+            port: 3333,
+            tls: false,
+            keepalive: false,
+        };
+        let nodes = [
+            XvbNodeConfig::europe(),
+            XvbNodeConfig::north_america(),
+            custom.clone(),
+        ];
+        // without current node, the fastest online is used.
+        assert_eq!(select_node(None, &nodes, &[100, 50, 80]), Some(1));
+        assert_eq!(
+            select_node(None, &nodes, &[100, TIMEOUT_NODE_PING, 80]),
+            Some(2)
+        );
+        assert_eq!(select_node(None, &nodes, &[TIMEOUT_NODE_PING; 3]), None);
+        // the current node is kept if not clearly slower.
+        assert_eq!(
+            select_node(Some(&nodes[0]), &nodes, &[100, 80, 90]),
+            Some(0)
+        );
+        assert_eq!(select_node(Some(&nodes[0]), &nodes, &[40, 25, 90]), Some(0));
+        assert_eq!(
+            select_node(Some(&nodes[0]), &nodes, &[100, 50, 90]),
+            Some(1)
+        );
+        // or if it is offline.
+        assert_eq!(
+            select_node(Some(&nodes[0]), &nodes, &[TIMEOUT_NODE_PING, 100, 90]),
+            Some(2)
+        );
+        // a current node not in the list anymore is replaced.
+        assert_eq!(
+            select_node(Some(&XvbNodeConfig::default()), &nodes[1..], &[100, 90]),
+            Some(1)
+        );
+
+        let line =
+            |pool: &str| format!("[2024-05-24 12:00:00.000]  net      use pool {pool} 1.2.3.4");
+        assert_eq!(
+            detect_new_node_xmrig(&line("127.0.0.1:3333"), &nodes, "127.0.0.1:3333"),
+            Some(XvbNode::P2pool)
+        );
+        assert_eq!(
+            detect_new_node_xmrig(&line("na.xmrvsbeast.com:4247"), &nodes, "127.0.0.1:3333"),
+            Some(XvbNode::Xvb(XvbNodeConfig::north_america()))
+        );
+        assert_eq!(
+            detect_new_node_xmrig(&line("xvb.example.com:3333"), &nodes, "127.0.0.1:3333"),
+            Some(XvbNode::Xvb(custom))
+        );
+        // a node removed from the list is not recognised.
+        assert_eq!(
+            detect_new_node_xmrig(
+                &line("eu.xmrvsbeast.com:4247"),
+                &nodes[1..],
+                "127.0.0.1:3333"
+            ),
+            None
+        );
+        assert_eq!(
+            XvbNode::Xvb(XvbNodeConfig::europe()).to_string(),
+            "XvB European Node"
+        );
+    }
+    #[test]
+    fn xvb_local_endpoints() {
+        use crate::disk::state::{P2pool, Xmrig, XmrigProxy};
+        use crate::helper::xvb::endpoints::LocalEndpoints;
+        use crate::helper::xvb::nodes::{XvbNode, XvbNodeConfig};
+        use crate::regex::detect_new_node_xmrig;
+        // default states are the default endpoints.
+        let (mut p2pool, mut xmrig, mut xp) =
+            (P2pool::default(), Xmrig::default(), XmrigProxy::default());
+        assert_eq!(
+            LocalEndpoints::new(&p2pool, &xmrig, &xp),
+            LocalEndpoints::default()
+        );
+        // advanced modes without arguments.
+        p2pool.simple = false;
+        xmrig.simple = false;
+        xmrig.api_ip = "localhost".to_string();
+        xmrig.api_port = "28088".to_string();
+        xmrig.ip = "192.168.1.2".to_string();
+        xmrig.port = "3334".to_string();
+        xp.simple = false;
+        xp.ip = String::new();
+        xp.port = "3400".to_string();
+        xp.api_ip = "127.0.0.1".to_string();
+        xp.api_port = "28089".to_string();
+        let endpoints = LocalEndpoints::new(&p2pool, &xmrig, &xp);
+        assert_eq!(endpoints.p2pool, "127.0.0.1:3333");
+        assert_eq!(endpoints.xmrig_api, "127.0.0.1:28088");
+        assert_eq!(endpoints.xp, "127.0.0.1:3400");
+        assert_eq!(endpoints.xp_api, "127.0.0.1:28089");
+        assert_eq!(LocalEndpoints::xmrig_pool(&xmrig), "192.168.1.2:3334");
+        // arguments override the other settings.
+        p2pool.arguments = "--wallet 4abc --stratum 0.0.0.0:3334,[::]:3334 --local-api".to_string();
+        xmrig.arguments = "--url localhost:3334 --http-host 0.0.0.0 --http-port 28000".to_string();
+        xp.arguments = "-o 127.0.0.1:3334 -b [::]:3401 --http-port=28090".to_string();
+        let endpoints = LocalEndpoints::new(&p2pool, &xmrig, &xp);
+        assert_eq!(endpoints.p2pool, "127.0.0.1:3334");
+        assert_eq!(endpoints.xmrig_api, "127.0.0.1:28000");
+        assert_eq!(endpoints.xp, "[::1]:3401");
+        assert_eq!(endpoints.xp_api, "127.0.0.1:28090");
+        assert_eq!(LocalEndpoints::xmrig_pool(&xmrig), "127.0.0.1:3334");
+        p2pool.arguments = "--stratum=192.168.1.2:4444".to_string();
+        assert_eq!(LocalEndpoints::p2pool_stratum(&p2pool), "192.168.1.2:4444");
+        assert_eq!(
+            endpoints.api_url(true, true),
+            "http://127.0.0.1:28090/1/config"
+        );
+        assert_eq!(
+            endpoints.api_url(false, false),
+            "http://127.0.0.1:28000/1/summary"
+        );
+        // the pools given to xmrig follow them.
+        assert_eq!(XvbNode::P2pool.pool(&endpoints), "127.0.0.1:3334");
+        assert_eq!(XvbNode::XmrigProxy.pool(&endpoints), "[::1]:3401");
+        assert_eq!(
+            XvbNode::Xvb(XvbNodeConfig::europe()).pool(&endpoints),
+            "eu.xmrvsbeast.com:4247"
+        );
+        let line =
+            |pool: &str| format!("[2024-05-24 12:00:00.000]  net      use pool {pool} 1.2.3.4");
+        assert_eq!(
+            detect_new_node_xmrig(&line("127.0.0.1:3334"), &[], &endpoints.p2pool),
+            Some(XvbNode::P2pool)
+        );
+        assert_eq!(
+            detect_new_node_xmrig(&line("127.0.0.1:3333"), &[], &endpoints.p2pool),
+            None
+        );
+    }
+    #[test]
+    fn xvb_stratum_switcher() {
+        stratum_switcher_session();
+    }
+    // Fake stratum upstream: answers the login with its own session and job, then accepts everything.
+    // Returns its address and the requests it received.
+    async fn fake_upstream(name: &'static str) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        use serde_json::json;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(vec![]));
+        tokio::spawn(enclose::enc!((requests) async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(enclose::enc!((requests) async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+                        let result = if request["method"] == "login" {
+                            json!({
+                                "id": format!("{name}-session"),
+                                "job": {"blob": "00", "job_id": format!("{name}-job"), "target": "ffffffff", "id": format!("{name}-session")},
+                                "status": "OK"
+                            })
+                        } else {
+                            json!({"status": "OK"})
+                        };
+                        lock!(requests).push(request.clone());
+                        let response = json!({"id": request["id"], "jsonrpc": "2.0", "error": null, "result": result});
+                        writer.write_all(format!("{}\n", response).as_bytes()).await.unwrap();
+                    }
+                }));
+            }
+        }));
+        (address, requests)
+    }
+    async fn read_line(
+        lines: &mut tokio::io::Lines<tokio::io::BufReader<tokio::net::tcp::OwnedReadHalf>>,
+    ) -> serde_json::Value {
+        let line = tokio::time::timeout(std::time::Duration::from_secs(5), lines.next_line())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+    #[tokio::main]
+    async fn stratum_switcher_session() {
+        use crate::helper::xvb::{
+            endpoints::LocalEndpoints,
+            nodes::{XvbNode, XvbNodeConfig},
+            switcher::{StratumSwitcher, Upstream},
+        };
+        use crate::GUPAX_VERSION_UNDERSCORE;
+        use serde_json::json;
+        use std::time::Duration;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        let address = "4AdkPJoxn7JCvAby9szgnt93MSEwdnxdhaASxbTBm6x5dCwmsDep2UYN4FhStDn5i11nsJbpU7oj59ahg8gXb1Mg3viqCuk";
+        let (p2pool, p2pool_requests) = fake_upstream("p2pool").await;
+        let (xvb, xvb_requests) = fake_upstream("xvb").await;
+        let endpoints = LocalEndpoints {
+            p2pool,
+            ..Default::default()
+        };
+        let (host, port) = xvb.rsplit_once(':').unwrap();
+        let node = XvbNode::Xvb(XvbNodeConfig {
+            name: "Fake".to_string(),
+            host: host.to_string(),
+            port: port.parse().unwrap(),
+            tls: false,
+            keepalive: false,
+        });
+        let switcher = StratumSwitcher::bind(
+            "127.0.0.1:0",
+            Upstream::new(&XvbNode::P2pool, &endpoints, address),
+        )
+        .await
+        .unwrap();
+
+        // a miner connects to the switcher like to a pool.
+        let stream = tokio::net::TcpStream::connect(switcher.address())
+            .await
+            .unwrap();
+        let (reader, mut miner) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let login = json!({"id": 1, "jsonrpc": "2.0", "method": "login", "params": {"login": "rig", "pass": "x", "agent": "XMRig/6.21.0", "algo": ["rx/0"]}});
+        miner
+            .write_all(format!("{}\n", login).as_bytes())
+            .await
+            .unwrap();
+        let response = read_line(&mut lines).await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["job"]["job_id"], "p2pool-job");
+        // the miner gets its own session, not the one of the upstream.
+        let session = response["result"]["id"].as_str().unwrap().to_string();
+        assert_ne!(session, "p2pool-session");
+        assert_eq!(response["result"]["job"]["id"], session.as_str());
+        assert_eq!(switcher.miners(), 1);
+
+        let submit = |id: u32, job: &str| json!({"id": id, "jsonrpc": "2.0", "method": "submit", "params": {"id": session, "job_id": job, "nonce": "00000001", "result": "ab"}});
+        miner
+            .write_all(format!("{}\n", submit(2, "p2pool-job")).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(read_line(&mut lines).await["id"], 2);
+        {
+            let requests = lock!(p2pool_requests);
+            assert_eq!(requests[0]["params"]["login"], GUPAX_VERSION_UNDERSCORE);
+            assert_eq!(requests[1]["params"]["id"], "p2pool-session");
+            assert_eq!(requests[1]["params"]["job_id"], "p2pool-job");
+        }
+
+        // switching sends the job of the new upstream to the connected miner.
+        switcher.switch(Upstream::new(&node, &endpoints, address));
+        let job = read_line(&mut lines).await;
+        assert_eq!(job["method"], "job");
+        assert_eq!(job["params"]["job_id"], "xvb-job");
+        assert_eq!(job["params"]["id"], session.as_str());
+        miner
+            .write_all(format!("{}\n", submit(3, "xvb-job")).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(read_line(&mut lines).await["id"], 3);
+        {
+            let requests = lock!(xvb_requests);
+            assert_eq!(requests[0]["params"]["login"], "4AdkPJox");
+            assert_eq!(requests[0]["params"]["agent"], "XMRig/6.21.0");
+            assert_eq!(requests[1]["params"]["id"], "xvb-session");
+        }
+        assert_eq!(lock!(p2pool_requests).len(), 2);
+        assert_eq!(switcher.upstream().node, node);
+
+        // an unreachable upstream is reported and the miner stays where it was.
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = closed.local_addr().unwrap().port();
+        drop(closed);
+        let offline = XvbNode::Xvb(XvbNodeConfig {
+            name: "Offline".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: false,
+            keepalive: false,
+        });
+        switcher.switch(Upstream::new(&offline, &endpoints, address));
+        let mut failed = None;
+        for _ in 0..50 {
+            failed = switcher.take_failed();
+            if failed.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(failed, Some(offline));
+        miner
+            .write_all(format!("{}\n", submit(4, "xvb-job")).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(read_line(&mut lines).await["id"], 4);
+        assert_eq!(lock!(xvb_requests).len(), 3);
+    }
+    #[test]
+    fn xvb_remote_rigs() {
+        remote_rigs();
+    }
+    // Fake HTTP API of XMRig: answers the summary and the config, keeps the configs it receives.
+    async fn fake_xmrig_api() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        use serde_json::json;
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let configs = Arc::new(Mutex::new(vec![]));
+        tokio::spawn(enclose::enc!((configs) async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = BufReader::new(stream);
+                let mut request = String::new();
+                stream.read_line(&mut request).await.unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    stream.read_line(&mut header).await.unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let body = if request.starts_with("PUT /1/config") {
+                    let mut body = vec![0; length];
+                    stream.read_exact(&mut body).await.unwrap();
+                    lock!(configs).push(serde_json::from_slice(&body).unwrap());
+                    String::new()
+                } else if request.starts_with("GET /1/config") {
+                    json!({
+                        "cpu": {"*": {"intensity": 1, "threads": 4, "affinity": -1}},
+                        "pools": [{"url": "127.0.0.1:3333", "user": "", "rig-id": null, "tls": false, "keepalive": false}]
+                    })
+                    .to_string()
+                } else {
+                    json!({
+                        "worker_id": "rig",
+                        "resources": {"load_average": [null, null, null]},
+                        "connection": {"diff": 0, "accepted": 0, "rejected": 0},
+                        "hashrate": {"total": [1200.0, 1100.0, null]}
+                    })
+                    .to_string()
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        }));
+        (address, configs)
+    }
+    #[tokio::main]
+    async fn remote_rigs() {
+        use crate::helper::xvb::{
+            endpoints::LocalEndpoints,
+            nodes::{XvbNode, XvbNodeConfig},
+            rigs::{
+                fetch_hashrates, rig_stratum, update_rigs, update_rigs_hashrate, XvbRig,
+                XvbRigConfig,
+            },
+            PubXvbApi,
+        };
+        let address = "4AdkPJoxn7JCvAby9szgnt93MSEwdnxdhaASxbTBm6x5dCwmsDep2UYN4FhStDn5i11nsJbpU7oj59ahg8gXb1Mg3viqCuk";
+        let client = reqwest::Client::new();
+        let (api, configs) = fake_xmrig_api().await;
+        // nothing listens on a port just released.
+        let dead = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let rigs = vec![
+            XvbRigConfig {
+                name: "Garage".to_string(),
+                api,
+                token: "secret".to_string(),
+                p2pool: "192.168.1.10:3333".to_string(),
+            },
+            XvbRigConfig {
+                name: "Office".to_string(),
+                api: dead,
+                ..Default::default()
+            },
+        ];
+        // the longest average is used.
+        let hashrates = fetch_hashrates(&client, &rigs).await;
+        assert_eq!(*hashrates[0].as_ref().unwrap(), 1100.0);
+        assert!(hashrates[1].is_err());
+        // only the failing rig is reported, the other one is switched.
+        let node = XvbNode::Xvb(XvbNodeConfig::europe());
+        let endpoints = LocalEndpoints::default();
+        let failed = update_rigs(&client, &rigs, &node, &endpoints, address).await;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, "Office");
+        let config = lock!(configs)[0].clone();
+        assert_eq!(config["pools"][0]["url"], node.pool(&endpoints));
+        assert_eq!(config["pools"][0]["user"], "4AdkPJox");
+        assert_eq!(config["pools"][0]["rig-id"], "Garage");
+        assert_eq!(config["pools"][0]["tls"], true);
+        // P2Pool as reached by the rig, not the local stratum.
+        update_rigs(&client, &rigs, &XvbNode::P2pool, &endpoints, address).await;
+        assert_eq!(lock!(configs)[1]["pools"][0]["url"], "192.168.1.10:3333");
+        // without it, the local stratum is given on the address of this host seen by the rig.
+        let lan = std::net::IpAddr::from([192, 168, 1, 5]);
+        assert_eq!(rig_stratum("127.0.0.1:3333", lan), "192.168.1.5:3333");
+        assert_eq!(
+            rig_stratum("[::1]:3333", "fd00::5".parse().unwrap()),
+            "[fd00::5]:3333"
+        );
+        assert_eq!(rig_stratum("10.0.0.2:3333", lan), "10.0.0.2:3333");
+        assert_eq!(
+            rig_stratum("127.0.0.1:3333", std::net::IpAddr::from([127, 0, 0, 1])),
+            "127.0.0.1:3333"
+        );
+        let mut rig = XvbRigConfig {
+            api: "127.0.0.1:18088".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            rig.pool(&XvbNode::P2pool, &endpoints).unwrap(),
+            "127.0.0.1:3333"
+        );
+        // a rig that can not be located is not switched to its own loopback.
+        rig.api = "not an address".to_string();
+        assert!(rig.pool(&XvbNode::P2pool, &endpoints).is_err());
+        assert!(rig.pool(&node, &endpoints).is_ok());
+        let failed = update_rigs(&client, &[rig], &XvbNode::P2pool, &endpoints, address).await;
+        assert!(failed[0]
+            .1
+            .to_string()
+            .starts_with("the address of P2Pool could not be found"));
+        // the unreachable rig is not counted and reported once.
+        let pub_api = Arc::new(Mutex::new(PubXvbApi::new()));
+        let gui_api = Arc::new(Mutex::new(PubXvbApi::new()));
+        lock!(pub_api).rigs = rigs.into_iter().map(XvbRig::new).collect();
+        update_rigs_hashrate(&client, &pub_api, &gui_api).await;
+        update_rigs_hashrate(&client, &pub_api, &gui_api).await;
+        assert_eq!(lock!(gui_api).rigs_hashrate(), 1100.0);
+        assert!(lock!(gui_api).rigs[1].error.is_some());
+        assert_eq!(
+            lock!(gui_api)
+                .output
+                .matches("Rig Office can not be reached")
+                .count(),
+            1
+        );
+    }
+    #[test]
+    fn xvb_thread_split() {
+        use crate::helper::xvb::{
+            endpoints::LocalEndpoints,
+            nodes::{XvbNode, XvbNodeConfig},
+            split::{SplitXmrig, ThreadSplit},
+        };
+        // rounded down for XvB, P2Pool keeps a thread.
+        assert_eq!(
+            ThreadSplit::new(8, 300),
+            Some(ThreadSplit { p2pool: 4, xvb: 4 })
+        );
+        assert_eq!(
+            ThreadSplit::new(8, 100),
+            Some(ThreadSplit { p2pool: 7, xvb: 1 })
+        );
+        assert_eq!(
+            ThreadSplit::new(8, 70),
+            Some(ThreadSplit { p2pool: 8, xvb: 0 })
+        );
+        assert_eq!(
+            ThreadSplit::new(4, 600),
+            Some(ThreadSplit { p2pool: 1, xvb: 3 })
+        );
+        assert_eq!(ThreadSplit::new(1, 300), None);
+        // time of a cycle giving the same hashrate to XvB.
+        assert_eq!(ThreadSplit { p2pool: 6, xvb: 2 }.time_donated(), 150);
+        assert_eq!(ThreadSplit { p2pool: 8, xvb: 0 }.time_donated(), 0);
+        let node = XvbNode::Xvb(XvbNodeConfig::europe());
+        let address = "4AdkPJoxn7JCvAby9szgnt93MSEwdnxdhaASxbTBm6x5dCwmsDep2UYN4FhStDn5i11nsJbpU7oj59ahg8gXb1Mg3viqCuk";
+        let args = SplitXmrig::args(
+            3,
+            &node,
+            &LocalEndpoints::default(),
+            address,
+            "Gupaxx",
+            "token",
+        )
+        .join(" ");
+        assert!(args.starts_with(&format!(
+            "--url {} --user 4AdkPJox --threads 3 --no-color --http-host 127.0.0.1 --http-port 18090",
+            node.pool(&LocalEndpoints::default())
+        )));
+        assert!(args.ends_with("--http-access-token=token --rig-id Gupaxx --tls --keepalive"));
+    }
+    #[test]
+    fn xvb_thread_split_disabled() {
+        use crate::helper::xrig::xmrig::ImgXmrig;
+        use crate::helper::xvb::{
+            algorithm::thread_split,
+            rigs::{XvbRig, XvbRigConfig},
+            split::ThreadSplit,
+            PubXvbApi,
+        };
+        let gui_api = Arc::new(Mutex::new(PubXvbApi::new()));
+        let img_xmrig = Arc::new(Mutex::new(ImgXmrig {
+            threads: "8".to_string(),
+            ..Default::default()
+        }));
+        assert_eq!(
+            thread_split(false, &gui_api, &img_xmrig, 300),
+            Some(ThreadSplit { p2pool: 4, xvb: 4 })
+        );
+        assert_eq!(thread_split(true, &gui_api, &img_xmrig, 300), None);
+        // the time donated includes the hashrate of the rigs, the cycle is split in time.
+        lock!(gui_api).rigs = vec![XvbRig::new(XvbRigConfig::default())];
+        assert_eq!(thread_split(false, &gui_api, &img_xmrig, 300), None);
+        assert!(lock!(gui_api)
+            .output
+            .contains("The thread split is only used without remote rigs."));
+    }
+    #[test]
+    #[cfg(unix)]
+    fn xvb_thread_split_process() {
+        use crate::helper::xvb::{
+            endpoints::LocalEndpoints,
+            nodes::{XvbNode, XvbNodeConfig},
+            split::{SplitXmrig, ThreadSplit},
+        };
+        use std::path::Path;
+        let node = XvbNode::Xvb(XvbNodeConfig::europe());
+        let endpoints = LocalEndpoints::default();
+        let split = ThreadSplit { p2pool: 2, xvb: 2 };
+        let mut xmrig = SplitXmrig::default();
+        assert_eq!(xmrig.settled_hashrate(), Some(0.0));
+        // [yes] runs until it is killed whatever the arguments.
+        let yes = Path::new("/usr/bin/yes");
+        xmrig.start(yes, split, &node, &endpoints, "", "").unwrap();
+        assert!(xmrig.is_running());
+        // the hashrates are not compared until the second XMRig reported once.
+        assert_eq!(xmrig.settled_hashrate(), None);
+        let moved = ThreadSplit { p2pool: 3, xvb: 2 };
+        xmrig.start(yes, moved, &node, &endpoints, "", "").unwrap();
+        assert_eq!(xmrig.status().unwrap().split, moved);
+        assert!(xmrig.stop());
+        assert!(!xmrig.stop());
+        assert!(xmrig.status().is_none());
+        assert!(!xmrig.take_exited());
+        // nor while XMRig gets back the threads.
+        assert_eq!(xmrig.settled_hashrate(), None);
+        // an exit without a stop is noticed once.
+        xmrig
+            .start(
+                Path::new("/usr/bin/false"),
+                split,
+                &node,
+                &endpoints,
+                "",
+                "",
+            )
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert!(xmrig.take_exited());
+        assert!(!xmrig.take_exited());
+        assert!(xmrig.status().is_none());
+    }
+    #[test]
+    fn xvb_update_xmrig_threads() {
+        update_threads();
+    }
+    #[tokio::main]
+    async fn update_threads() {
+        use crate::helper::xrig::update_xmrig_threads;
+        let client = reqwest::Client::new();
+        let (api, configs) = fake_xmrig_api().await;
+        update_xmrig_threads(&client, &format!("http://{}/1/config", api), "", 3)
+            .await
+            .unwrap();
+        let config = lock!(configs)[0].clone();
+        assert_eq!(config["cpu"]["rx"], serde_json::json!([-1, -1, -1]));
+        // the profile of [--threads] is left for the other algorithms.
+        assert_eq!(config["cpu"]["*"]["threads"], 4);
+    }
+    #[test]
+    fn args_round_trip() {
+        use crate::helper::args::{ArgError, Args, Binary};
+        // canonical arguments are written back as they were typed.
+        for (binary, input) in [
+            (
+                Binary::P2pool,
+                "--wallet 4abc --host 127.0.0.1 --rpc-port 18081 --zmq-port 18083 --host node.org --rpc-port 18089 --zmq-port 18084 --merge-mine tari://node:18102 tari_wallet --loglevel 3 --mini --local-api",
+            ),
+            (
+                Binary::Xmrig,
+                "--url 127.0.0.1:3333 --user rig --url pool.org:443 --tls --threads 4 --randomx-wrmsr=6 --no-color",
+            ),
+            (
+                Binary::XmrigProxy,
+                "--url 127.0.0.1:3333 --bind 0.0.0.0:3355 --bind [::]:3355 --mode nicehash --http-port 18089",
+            ),
+        ] {
+            let args = Args::parse(binary, input).unwrap();
+            assert_eq!(args.to_string(), input);
+            assert_eq!(Args::parse(binary, &args.to_string()).unwrap(), args);
+        }
+        // short names and [--option=value] are written with the long name and a separate value.
+        let args =
+            Args::parse(Binary::Xmrig, "-o localhost:3333 -t=4 -k --http-port=18088").unwrap();
+        assert_eq!(
+            args.to_string(),
+            "--url localhost:3333 --threads 4 --keepalive --http-port 18088"
+        );
+        assert_eq!(Args::parse(Binary::Xmrig, &args.to_string()).unwrap(), args);
+        assert_eq!(args.value("--url"), Some("localhost:3333"));
+        assert_eq!(args.value("--threads"), Some("4"));
+        assert_eq!(args.value("--tls"), None);
+        assert!(args.has("--keepalive"));
+        // the first pool is the one used.
+        let args = Args::parse(Binary::XmrigProxy, "-o a:1 -o b:2").unwrap();
+        assert_eq!(args.value("--url"), Some("a:1"));
+        // errors, the first one is returned.
+        for (binary, input, error) in [
+            (
+                Binary::P2pool,
+                "--wallet 4abc --threads 4",
+                ArgError::Unknown(Binary::P2pool, "--threads".to_string()),
+            ),
+            (
+                Binary::Xmrig,
+                "--threads 4 --threads 2",
+                ArgError::Duplicate("--threads".to_string()),
+            ),
+            (
+                Binary::P2pool,
+                "--wallet --mini",
+                ArgError::MissingValue("--wallet".to_string()),
+            ),
+            (
+                Binary::P2pool,
+                "--merge-mine tari://node:18102",
+                ArgError::MissingValue("--merge-mine".to_string()),
+            ),
+            (
+                Binary::P2pool,
+                "--mini=1",
+                ArgError::UnexpectedValue("--mini=1".to_string()),
+            ),
+            (
+                Binary::XmrigProxy,
+                "--bind 0.0.0.0:3355 3356",
+                ArgError::UnexpectedValue("3356".to_string()),
+            ),
+            (
+                Binary::P2pool,
+                "--rpc-port 70000",
+                ArgError::InvalidValue(
+                    "--rpc-port".to_string(),
+                    "70000".to_string(),
+                    "a port between 1 and 65535".to_string(),
+                ),
+            ),
+            (
+                Binary::P2pool,
+                "--out-peers 500",
+                ArgError::InvalidValue(
+                    "--out-peers".to_string(),
+                    "500".to_string(),
+                    "a number between 10 and 450".to_string(),
+                ),
+            ),
+        ] {
+            assert_eq!(Args::parse(binary, input), Err(error));
+        }
+        assert_eq!(
+            ArgError::Unknown(Binary::XmrigProxy, "--threads".to_string()).to_string(),
+            "--threads is not an option of XMRig-Proxy"
+        );
+        // at the start, what is not understood is passed as it is.
+        let args = Args::parse_lossy(Binary::Xmrig, "--url localhost:3333 --new-option value");
+        assert_eq!(args.to_string(), "--url localhost:3333 --new-option value");
+        // the binaries do not understand [localhost] alone.
+        let mut args = Args::parse_lossy(
+            Binary::Xmrig,
+            "--http-host localhost --http-access-token user",
+        );
+        args.set("--http-access-token", "gupaxx")
+            .set_flag("--http-no-restricted")
+            .set_flag("--http-no-restricted");
+        assert_eq!(
+            args.into_argv(),
+            [
+                "--http-host",
+                "127.0.0.1",
+                "--http-access-token",
+                "gupaxx",
+                "--http-no-restricted"
+            ]
+        );
+    }
+    #[test]
+    fn args_override_checked() {
+        use crate::helper::args::{override_error, override_warning, ArgError, Binary};
+        // the arguments are only used in the advanced mode.
+        assert_eq!(override_error(Binary::Xmrig, true, "--bad"), None);
+        assert_eq!(override_error(Binary::Xmrig, false, ""), None);
+        assert_eq!(override_error(Binary::Xmrig, false, "--bad"), None);
+        assert_eq!(
+            override_warning(Binary::Xmrig, false, "--bad"),
+            Some(ArgError::Unknown(Binary::Xmrig, "--bad".to_string()))
+        );
+        // an option of a newer XMRig does not block the start, it is given with its value.
+        let input = "-o 127.0.0.1:3333 --daemon-zmq-port 18083 --spend-secret-key abc -t 4";
+        assert_eq!(override_error(Binary::Xmrig, false, input), None);
+        assert!(override_warning(Binary::Xmrig, false, input).is_some());
+        assert_eq!(
+            crate::helper::args::Args::parse_lossy(Binary::Xmrig, input).into_argv(),
+            [
+                "--url",
+                "127.0.0.1:3333",
+                "--daemon-zmq-port",
+                "18083",
+                "--spend-secret-key",
+                "abc",
+                "--threads",
+                "4"
+            ]
+        );
+        // the structural errors still block it, even after an unknown option.
+        assert_eq!(
+            override_error(Binary::Xmrig, false, "--bad 1 --threads"),
+            Some(ArgError::MissingValue("--threads".to_string()))
+        );
+        assert!(matches!(
+            override_error(Binary::P2pool, false, "--bad --rpc-port port"),
+            Some(ArgError::InvalidValue(..))
+        ));
+        assert!(override_error(Binary::P2pool, false, "--mini --mini").is_some());
+    }
+    #[test]
+    fn args_build_and_image() {
+        use crate::disk::state::{P2pool, Xmrig, XmrigProxy};
+        use crate::helper::{p2pool::ImgP2pool, xrig::xmrig::ImgXmrig};
+        use std::path::Path;
+        let helper = new_helper();
+        // P2Pool image filled from the override, P2Pool defaults for the rest.
+        let address = "4A5Dwt2qKwKEQrZfo4aBkSNtvDDAzSFbAJcyFkdW5RwDh9U4WgeZrgKT4hUoE2gv8h6NmsNMTyjsEL8eSLMbABds5rYFWnw";
+        let mut p2pool = P2pool {
+            simple: false,
+            ..Default::default()
+        };
+        p2pool.arguments = format!(
+            "--wallet {} --host localhost --rpc-port 18089 --host node.org --data-api /tmp/p2pool --nano",
+            address
+        );
+        let (args, local, _, _) = Helper::build_p2pool_args_and_mutate_img(
+            &helper,
+            &p2pool,
+            Path::new("/opt/p2pool/p2pool"),
+            None,
+        );
+        assert_eq!(args[2..4], ["--host", "127.0.0.1"]);
+        assert!(local.starts_with("/tmp/p2pool"));
+        let img = lock!(lock!(helper).img_p2pool).clone();
+        assert_eq!(
+            img,
+            ImgP2pool {
+                mini: "P2Pool Nano".to_string(),
+                address: "4A5Dwt2q...s5rYFWnw".to_string(),
+                host: "localhost".to_string(),
+                rpc: "18089".to_string(),
+                zmq: "18083".to_string(),
+                out_peers: "10".to_string(),
+                in_peers: "450".to_string(),
+            }
+        );
+        // XMRig always gets the token of Gupaxx, once.
+        let xmrig = Xmrig {
+            simple: false,
+            arguments: "-o pool.org:3333 -t 6 --http-host localhost --http-port 28088 --http-access-token=mine"
+                .to_string(),
+            token: "gupaxx".to_string(),
+            ..Default::default()
+        };
+        let (args, api) =
+            Helper::build_xmrig_args_and_mutate_img(&helper, &xmrig, Path::new("/opt/xmrig/xmrig"));
+        assert_eq!(api, "127.0.0.1:28088");
+        let args = args.join(" ");
+        assert!(args.ends_with(
+            "--url pool.org:3333 --threads 6 --http-host 127.0.0.1 --http-port 28088 --http-access-token gupaxx --http-no-restricted"
+        ));
+        assert_eq!(
+            lock!(lock!(helper).img_xmrig).clone(),
+            ImgXmrig {
+                threads: "6".to_string(),
+                url: "pool.org:3333".to_string(),
+                path: "/opt/xmrig/xmrig".into(),
+            }
+        );
+        // the simple modes go through the same model.
+        let (args, _) = Helper::build_xmrig_args_and_mutate_img(
+            &helper,
+            &Xmrig::default(),
+            Path::new("/opt/xmrig/xmrig"),
+        );
+        let args = args
+            .iter()
+            .skip_while(|a| *a != "--url")
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!(crate::helper::args::Args::parse(
+            crate::helper::args::Binary::Xmrig,
+            &args.join(" ")
+        )
+        .is_ok());
+        let xp = XmrigProxy {
+            simple: false,
+            arguments: "-o localhost:3333 -b 0.0.0.0:3355".to_string(),
+            token: "gupaxx".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Helper::build_xp_args(&helper, &xp),
+            [
+                "--url",
+                "localhost:3333",
+                "--bind",
+                "0.0.0.0:3355",
+                "--http-access-token",
+                "gupaxx",
+                "--http-no-restricted"
+            ]
+        );
+        assert_eq!(lock!(lock!(helper).pub_api_xp).node, "localhost:3333");
+    }
+    // Lines of the output and the reports they ended, with the lines shown to the user.
+    fn capture_console(
+        output: &str,
+    ) -> (
+        Vec<anyhow::Result<crate::helper::p2pool_console::ConsoleReport>>,
+        Vec<String>,
+    ) {
+        let mut capture = crate::helper::p2pool_console::ConsoleCapture::default();
+        let (mut reports, mut shown) = (vec![], vec![]);
+        for line in output.lines() {
+            let captured = capture.push(line);
+            reports.extend(captured.report);
+            if !captured.hidden {
+                shown.push(line.to_string());
+            }
+        }
+        (reports, shown)
+    }
+    #[test]
+    fn p2pool_console_status() {
+        use crate::helper::p2pool_console::{ConsoleReport, P2poolStatus, WindowBlocks};
+        use crate::helper::PubP2poolApi;
+        let output = "statusfromgupaxx
+2024-09-10 08:45:15.9215 SideChain status
+Monero node               = node.moneroworld.com:18089:ZMQ:18084 (5.9.17.234)
+Main chain height         = 3235620
+Main chain hashrate       = 2.561 GH/s
+Side chain ID             = mini
+Side chain height         = 8775201
+Side chain hashrate       = 14.230 MH/s
+PPLNS window              = 2160 blocks (+62 uncles, 0 orphans)
+PPLNS window duration     = 4h 13m 6s
+Your wallet address       = 4A5Dwt2qKwKEQrZfo4aBkSNtvDDAzSFbAJcyFkdW5RwDh9U4WgeZrgKT4hUoE2gv8h6NmsNMTyjsEL8eSLMbABds5rYFWnw
+Your shares               = 3 blocks (+1 uncles, 0 orphans)
+Block reward share        = 0.103% (0.000623516125 XMR)
+Your hashrate (pool-side) = 14.452 KH/s
+2024-09-10 08:45:15.9216 StratumServer status
+Hashrate (15m est) = 13.931 KH/s
+Hashrate (1h  est) = 14.112 KH/s
+Hashrate (24h est) = 14.050 KH/s
+Total hashes       = 6064238155
+Shares found       = 25
+Average effort     = 112.543%
+Current effort     = 34.126%
+Connections        = 2 (0 incoming)
+2024-09-10 08:45:15.9216 P2Pool SHARE FOUND: mainchain height 3235620, sidechain height 8775202, diff 107342152, client 127.0.0.1:53422, effort 34.126%
+2024-09-10 08:45:15.9216 P2PServer status
+Connections    = 12 (2 incoming)
+Peer list size = 1340
+Uptime         = 1d 2h 3m 4s
+2024-09-10 08:45:16.0042 P2Pool Your wallet has a new share";
+        let (reports, shown) = capture_console(output);
+        // a line of another thread in the middle of the report is still shown.
+        assert_eq!(
+            shown,
+            [
+                "2024-09-10 08:45:15.9216 P2Pool SHARE FOUND: mainchain height 3235620, sidechain height 8775202, diff 107342152, client 127.0.0.1:53422, effort 34.126%",
+                "2024-09-10 08:45:16.0042 P2Pool Your wallet has a new share"
+            ]
+        );
+        let ConsoleReport::Status(status) = reports.into_iter().next().unwrap().unwrap() else {
+            panic!("not a status");
+        };
+        assert_eq!(
+            *status,
+            P2poolStatus {
+                monero_node: Some("node.moneroworld.com:18089:ZMQ:18084 (5.9.17.234)".to_string()),
+                main_height: Some(3235620),
+                main_hashrate: Some(2_561_000_000),
+                sidechain: Some("mini".to_string()),
+                sidechain_height: Some(8775201),
+                sidechain_hashrate: Some(14_230_000),
+                pplns_window: Some(WindowBlocks {
+                    blocks: 2160,
+                    uncles: 62,
+                    orphans: 0
+                }),
+                pplns_duration: Some(4 * 3600 + 13 * 60 + 6),
+                wallet: Some("4A5Dwt2qKwKEQrZfo4aBkSNtvDDAzSFbAJcyFkdW5RwDh9U4WgeZrgKT4hUoE2gv8h6NmsNMTyjsEL8eSLMbABds5rYFWnw".to_string()),
+                shares: Some(WindowBlocks {
+                    blocks: 3,
+                    uncles: 1,
+                    orphans: 0
+                }),
+                reward_share: Some(0.103),
+                hashrate: Some(14452),
+                hashrate_15m: Some(13931),
+                hashrate_1h: Some(14112),
+                hashrate_24h: Some(14050),
+                total_hashes: Some(6064238155),
+                shares_found: Some(25),
+                average_effort: Some(112.543),
+                current_effort: Some(34.126),
+                stratum_connections: Some(2),
+                p2p_connections: Some(12),
+                p2p_incoming: Some(2),
+                peer_list_size: Some(1340),
+                uptime: Some(86400 + 2 * 3600 + 3 * 60 + 4),
+            }
+        );
+        // the shares and the pool-side hashrate go to the API used by XvB.
+        let mut api = PubP2poolApi::new();
+        api.update_from_console(ConsoleReport::Status(status));
+        assert_eq!(api.sidechain_shares, 3);
+        assert_eq!(api.sidechain_ehr, 14452.0);
+        assert_eq!(api.status.as_ref().unwrap().p2p_connections, Some(12));
+    }
+    #[test]
+    fn p2pool_console_status_older() {
+        use crate::helper::p2pool_console::ConsoleReport;
+        use crate::helper::PubP2poolApi;
+        // the output of [get_current_shares], P2Pool did not write the pool-side hashrate yet.
+        let output = "statusfromgupaxx
+2024-03-25 21:31:21.7919 SideChain status
+Monero node               = node2.monerodevs.org:18089:ZMQ:18084 (37.187.74.171)
+Main chain height         = 3113042
+Main chain hashrate       = 1.985 GH/s
+Side chain ID             = mini
+Side chain height         = 7230432
+Side chain hashrate       = 8.925 MH/s
+PPLNS window              = 2160 blocks (+79 uncles, 0 orphans)
+PPLNS window duration     = 6h 9m 46s
+Your wallet address       = 4A5Dwt2qKwKEQrZfo4aBkSNtvDDAzSFbAJcyFkdW5RwDh9U4WgeZrgKT4hUoE2gv8h6NmsNMTyjsEL8eSLMbABds5rYFWnw
+Your shares               = 0 blocks (+0 uncles, 0 orphans)
+Block reward share        = 0.000% (0.000000000000 XMR)
+2024-03-25 21:31:21.7920 StratumServer status
+Hashrate (15m est) = 0 H/s
+Hashrate (1h  est) = 0 H/s
+Hashrate (24h est) = 0 H/s
+Total hashes       = 0
+Shares found       = 0
+Average effort     = 0.000%
+Current effort     = 0.000%
+Connections        = 0 (0 incoming)
+2024-03-25 21:31:21.7920 P2PServer status
+Connections    = 10 (0 incoming)
+Peer list size = 1209
+Uptime         = 0h 2m 4s";
+        let (reports, shown) = capture_console(output);
+        assert!(shown.is_empty());
+        let ConsoleReport::Status(status) = reports.into_iter().next().unwrap().unwrap() else {
+            panic!("not a status");
+        };
+        assert_eq!(status.shares.unwrap().blocks, 0);
+        assert_eq!(status.hashrate, None);
+        assert_eq!(status.uptime, Some(124));
+        assert_eq!(status.stratum_connections, Some(0));
+        assert_eq!(status.p2p_connections, Some(10));
+        // without the pool-side hashrate, the last one is kept.
+        let mut api = PubP2poolApi::new();
+        api.sidechain_ehr = 1000.0;
+        api.update_from_console(ConsoleReport::Status(status));
+        assert_eq!(api.sidechain_ehr, 1000.0);
+    }
+    #[test]
+    fn p2pool_console_status_trimmed() {
+        use crate::helper::p2pool_console::{ConsoleReport, WindowBlocks};
+        use crate::helper::PubP2poolApi;
+        // another version of P2Pool, with lines renamed, missing or written differently.
+        let output = "statusfromgupaxx
+2024-09-10 08:45:15.9215 SideChain status
+Monero node               = node.moneroworld.com:18089:ZMQ:18084 (5.9.17.234)
+Main chain height         = 3235620
+Main chain hashrate       = 2.561 GH/s
+Sidechain                 = mini
+PPLNS window              = 2160 blocks
+Wallet                    = 4A5Dwt2qKwKEQrZfo4aBkSNtvDDAzSFbAJcyFkdW5RwDh9U4WgeZrgKT4hUoE2gv8h6NmsNMTyjsEL8eSLMbABds5rYFWnw
+Your shares               = 5 blocks (+0 uncles, 1 orphans)
+Your hashrate (pool-side) = 9.1 KH/s
+2024-09-10 08:45:15.9216 P2PServer status
+Connections    = 12
+Uptime         = 1d 2h 3m 4s";
+        let (reports, shown) = capture_console(output);
+        assert!(shown.is_empty());
+        let ConsoleReport::Status(status) = reports.into_iter().next().unwrap().unwrap() else {
+            panic!("not a status");
+        };
+        assert_eq!(
+            status.shares,
+            Some(WindowBlocks {
+                blocks: 5,
+                uncles: 0,
+                orphans: 1
+            })
+        );
+        assert_eq!(status.hashrate, Some(9100));
+        assert_eq!(status.main_height, Some(3235620));
+        assert_eq!(status.sidechain, None);
+        assert_eq!(status.wallet, None);
+        assert_eq!(status.pplns_window, None);
+        assert_eq!(status.reward_share, None);
+        assert_eq!(status.p2p_connections, None);
+        assert_eq!(status.stratum_connections, None);
+        // XvB still gets the shares and the hashrate.
+        let mut api = PubP2poolApi::new();
+        api.update_from_console(ConsoleReport::Status(status));
+        assert_eq!(api.sidechain_shares, 5);
+        assert_eq!(api.sidechain_ehr, 9100.0);
+    }
+    #[test]
+    fn p2pool_console_errors() {
+        // unexpected values are errors, not panics.
+        let (reports, _) = capture_console(
+            "statusfromgupaxx
+2024-03-25 21:31:21.7919 SideChain status
+Your shares               = many blocks
+Uptime         = 0h 2m 4s",
+        );
+        assert!(reports[0].is_err());
+        let (reports, _) = capture_console(
+            "statusfromgupaxx
+2024-03-25 21:31:21.7919 SideChain status
+Main chain height         = 3113042
+Uptime         = 0h 2m 4s",
+        );
+        assert_eq!(
+            reports[0].as_ref().unwrap_err().to_string(),
+            "no [Your shares] in the status"
+        );
+        // a status without its end stops being captured.
+        let mut output = "statusfromgupaxx\n".to_string();
+        for _ in 0..100 {
+            output.push_str("Unknown = line\n");
+        }
+        let (reports, shown) = capture_console(&output);
+        assert_eq!(
+            reports[0].as_ref().unwrap_err().to_string(),
+            "the status did not end"
+        );
+        assert!(!shown.is_empty());
+        // a new command ends the report before it.
+        let (reports, _) = capture_console(
+            "statusfromgupaxx
+2024-03-25 21:31:21.7919 SideChain status
+peersfromgupaxx",
+        );
+        assert!(reports[0].is_err());
+    }
+    #[test]
+    fn p2pool_console_peers_bans() {
+        use crate::helper::p2pool_console::{ConsoleReport, P2poolBan, P2poolPeer};
+        use crate::helper::PubP2poolApi;
+        let output = "peersfromgupaxx
+2024-09-10 08:46:15.0155 P2PServer O\t0h 38m 41s      \t  91 ms\tv4.1    \t8775201   \t[::ffff:5.9.17.234]:37888
+2024-09-10 08:46:15.0155 P2PServer I\t1d 2h 5m 9s     \t   4 ms\tv3.10   \t8775200   \t192.168.1.20:41216
+2024-09-10 08:46:15.0156 P2PServer Total: 2 peers (1 incoming)
+2024-09-10 08:46:16.1000 StratumServer SHARE FOUND
+bansfromgupaxx
+2024-09-10 08:46:35.0155 P2PServer 203.0.113.7 (9m 58s)
+2024-09-10 08:46:35.0155 P2PServer 2001:db8::1 (0h 0m 12s)
+2024-09-10 08:46:35.0155 P2PServer Total: 2 banned IPs
+bansfromgupaxx
+2024-09-10 08:47:35.0155 P2Pool new block";
+        let (reports, shown) = capture_console(output);
+        assert_eq!(
+            shown,
+            [
+                "2024-09-10 08:46:16.1000 StratumServer SHARE FOUND",
+                "2024-09-10 08:47:35.0155 P2Pool new block"
+            ]
+        );
+        let reports = reports.into_iter().map(|r| r.unwrap()).collect::<Vec<_>>();
+        assert_eq!(
+            reports,
+            [
+                ConsoleReport::Peers(vec![
+                    P2poolPeer {
+                        incoming: false,
+                        address: "[::ffff:5.9.17.234]:37888".to_string(),
+                        uptime: 38 * 60 + 41,
+                        ping: Some(91),
+                        software: "v4.1".to_string(),
+                        height: Some(8775201),
+                    },
+                    P2poolPeer {
+                        incoming: true,
+                        address: "192.168.1.20:41216".to_string(),
+                        uptime: 86400 + 2 * 3600 + 5 * 60 + 9,
+                        ping: Some(4),
+                        software: "v3.10".to_string(),
+                        height: Some(8775200),
+                    },
+                ]),
+                ConsoleReport::Bans(vec![
+                    P2poolBan {
+                        address: "203.0.113.7".to_string(),
+                        remaining: Some(598),
+                    },
+                    P2poolBan {
+                        address: "2001:db8::1".to_string(),
+                        remaining: Some(12),
+                    },
+                ]),
+                // no ban, the report ends with the next line.
+                ConsoleReport::Bans(vec![]),
+            ]
+        );
+        let mut api = PubP2poolApi::new();
+        for report in reports {
+            api.update_from_console(report);
+        }
+        assert_eq!(api.peers.len(), 2);
+        assert!(api.bans.is_empty());
+    }
 }
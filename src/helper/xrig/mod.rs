@@ -17,6 +17,7 @@ pub async fn update_xmrig_config(
     api_uri: &str,
     token: &str,
     node: &XvbNode,
+    pool: &str,
     address: &str,
     rig: &str,
 ) -> Result<()> {
@@ -26,11 +27,10 @@ pub async fn update_xmrig_config(
         .header(AUTHORIZATION, ["Bearer ", token].concat());
     let mut config = request.send().await?.json::<Value>().await?;
     // modify node configuration
-    let uri = [node.url(), ":".to_string(), node.port()].concat();
-    info!("replace xmrig config with node {}", uri);
+    info!("replace xmrig config with node {}", pool);
     *config
         .pointer_mut("/pools/0/url")
-        .ok_or_else(|| anyhow!("pools/0/url does not exist in xmrig config"))? = uri.into();
+        .ok_or_else(|| anyhow!("pools/0/url does not exist in xmrig config"))? = pool.into();
     *config
         .pointer_mut("/pools/0/user")
         .ok_or_else(|| anyhow!("pools/0/user does not exist in xmrig config"))? = node
@@ -57,6 +57,34 @@ pub async fn update_xmrig_config(
         .await?;
     anyhow::Ok(())
 }
+// change the number of threads of xmrig, without affinity.
+pub async fn update_xmrig_threads(
+    client: &Client,
+    api_uri: &str,
+    token: &str,
+    threads: u16,
+) -> Result<()> {
+    let request = client
+        .get(api_uri)
+        .header(AUTHORIZATION, ["Bearer ", token].concat());
+    let mut config = request.send().await?.json::<Value>().await?;
+    info!("replace xmrig config with {} threads", threads);
+    // [--threads] is kept in the "*" profile, the "rx" profile takes precedence over it.
+    config
+        .pointer_mut("/cpu")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow!("cpu does not exist in xmrig config"))?
+        .insert("rx".to_string(), vec![-1; threads as usize].into());
+    client
+        .put(api_uri)
+        .header("Authorization", ["Bearer ", token].concat())
+        .header("Content-Type", "application/json")
+        .timeout(std::time::Duration::from_secs(5))
+        .body(config.to_string())
+        .send()
+        .await?;
+    anyhow::Ok(())
+}
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 struct Hashrate {
     total: [Option<f32>; 3],
@@ -1,4 +1,6 @@
+use crate::helper::args::{Args, Binary};
 use crate::helper::xrig::update_xmrig_config;
+use crate::helper::xvb::endpoints::LocalEndpoints;
 use crate::helper::{check_died, check_user_input, sleep_end_loop, Process};
 use crate::helper::{Helper, ProcessName, ProcessSignal, ProcessState};
 use crate::helper::{PubXvbApi, XvbNode};
@@ -63,9 +65,13 @@ impl Helper {
             // need to verify if node still working
             // for that need to catch "connect error"
             // only check if xvb process is used and xmrig-proxy is not.
-            if lock!(process_xvb).is_alive() && !lock!(process_xp).is_alive() {
+            // with the stratum switcher, xmrig only sees the switcher.
+            if lock!(process_xvb).is_alive()
+                && !lock!(process_xp).is_alive()
+                && lock!(pub_api_xvb).switcher.is_none()
+            {
                 if contains_error(&line) {
-                    let current_node = lock!(pub_api_xvb).current_node;
+                    let current_node = lock!(pub_api_xvb).current_node.clone();
                     if let Some(current_node) = current_node {
                         // updating current node to None, will stop sending signal of FailedNode until new node is set
                         // send signal to update node.
@@ -78,7 +84,11 @@ impl Helper {
                     info!("XMRig PTY Parse | new pool detected");
                     // need to update current node because it was updated.
                     // if custom node made by user, it is not supported because algo is deciding which node to use.
-                    let node = detect_new_node_xmrig(&line);
+                    let (nodes, p2pool) = {
+                        let api = lock!(pub_api_xvb);
+                        (api.nodes.clone(), api.endpoints.p2pool.clone())
+                    };
+                    let node = detect_new_node_xmrig(&line, &nodes, &p2pool);
                     if node.is_none() {
                         error!("XMRig PTY Parse | node is not understood, switching to backup.");
                         // update with default will choose which XvB to prefer. Will update XvB to use p2pool.
@@ -187,6 +197,8 @@ impl Helper {
         let process_xp = Arc::clone(&lock!(helper).xmrig_proxy);
         let path = path.to_path_buf();
         let token = state.token.clone();
+        // pool to come back to if xmrig-proxy stops after redirecting xmrig.
+        let pool = LocalEndpoints::xmrig_pool(state);
         let img_xmrig = Arc::clone(&lock!(helper).img_xmrig);
         let pub_api_xvb = Arc::clone(&lock!(helper).pub_api_xvb);
         thread::spawn(move || {
@@ -203,6 +215,7 @@ impl Helper {
                 process_xp,
                 &img_xmrig,
                 &pub_api_xvb,
+                &pool,
             );
         });
     }
@@ -217,7 +230,8 @@ impl Helper {
         state: &crate::disk::state::Xmrig,
         path: &std::path::Path,
     ) -> (Vec<String>, String) {
-        let mut args = Vec::with_capacity(500);
+        let mut argv = Vec::with_capacity(500);
+        let mut args = Args::new(Binary::Xmrig);
         let mut api_ip = String::with_capacity(15);
         let mut api_port = String::with_capacity(5);
         let path = path.to_path_buf();
@@ -226,9 +240,9 @@ impl Helper {
         // Before that though, add the ["--prompt"] flag and set it
         // to emptyness so that it doesn't show up in the output.
         if cfg!(unix) {
-            args.push(r#"--prompt="#.to_string());
-            args.push("--".to_string());
-            args.push(path.display().to_string());
+            argv.push(r#"--prompt="#.to_string());
+            argv.push("--".to_string());
+            argv.push(path.display().to_string());
         }
 
         // [Simple]
@@ -239,24 +253,19 @@ impl Helper {
             } else {
                 state.simple_rig.clone()
             }; // Rig name
-            args.push("--url".to_string());
-            args.push("127.0.0.1:3333".to_string()); // Local P2Pool (the default)
-            args.push("--threads".to_string());
-            args.push(state.current_threads.to_string()); // Threads
-            args.push("--user".to_string());
-            args.push(rig); // Rig name
-            args.push("--no-color".to_string()); // No color
-            args.push("--http-host".to_string());
-            args.push("127.0.0.1".to_string()); // HTTP API IP
-            args.push("--http-port".to_string());
-            args.push("18088".to_string()); // HTTP API Port
+            args.push("--url", "127.0.0.1:3333") // Local P2Pool (the default)
+                .push("--threads", state.current_threads) // Threads
+                .push("--user", rig) // Rig name
+                .flag("--no-color") // No color
+                .push("--http-host", "127.0.0.1") // HTTP API IP
+                .push("--http-port", "18088"); // HTTP API Port
             if state.pause != 0 {
-                args.push("--pause-on-active".to_string());
-                args.push(state.pause.to_string());
+                args.push("--pause-on-active", state.pause);
             } // Pause on active
             *lock2!(helper, img_xmrig) = ImgXmrig {
                 threads: state.current_threads.to_string(),
                 url: "127.0.0.1:3333 (Local P2Pool)".to_string(),
+                path: path.clone(),
             };
 
             lock2!(helper, pub_api_xmrig).node = "127.0.0.1:3333 (Local P2Pool)".to_string();
@@ -267,32 +276,20 @@ impl Helper {
         } else {
             // Overriding command arguments
             if !state.arguments.is_empty() {
-                // This parses the input and attempts to fill out
-                // the [ImgXmrig]... This is pretty bad code...
-                let mut last = "";
-                let lock = lock!(helper);
-                let mut xmrig_image = lock!(lock.img_xmrig);
-                for arg in state.arguments.split_whitespace() {
-                    match last {
-                        "--threads" => xmrig_image.threads = arg.to_string(),
-                        "--url" => xmrig_image.url = arg.to_string(),
-                        "--http-host" => {
-                            api_ip = if arg == "localhost" {
-                                "127.0.0.1".to_string()
-                            } else {
-                                arg.to_string()
-                            }
-                        }
-                        "--http-port" => api_port = arg.to_string(),
-                        _ => (),
-                    }
-                    args.push(if arg == "localhost" {
+                // The UI refuses to start with invalid arguments,
+                // what is still not understood is given as it is to XMRig.
+                args = Args::parse_lossy(Binary::Xmrig, &state.arguments);
+                if let Some(ip) = args.value("--http-host") {
+                    api_ip = if ip == "localhost" {
                         "127.0.0.1".to_string()
                     } else {
-                        arg.to_string()
-                    });
-                    last = arg;
+                        ip.to_string()
+                    };
+                }
+                if let Some(port) = args.value("--http-port") {
+                    api_port = port.to_string();
                 }
+                *lock2!(helper, img_xmrig) = ImgXmrig::from_args(&args, &path);
             // Else, build the argument
             } else {
                 // XMRig doesn't understand [localhost]
@@ -312,39 +309,35 @@ impl Helper {
                     state.api_port.to_string()
                 };
                 let url = format!("{}:{}", ip, state.port); // Combine IP:Port into one string
-                args.push("--user".to_string());
-                args.push(state.address.clone()); // Wallet
-                args.push("--threads".to_string());
-                args.push(state.current_threads.to_string()); // Threads
-                args.push("--rig-id".to_string());
-                args.push(state.rig.to_string()); // Rig ID
-                args.push("--url".to_string());
-                args.push(url.clone()); // IP/Port
-                args.push("--http-host".to_string());
-                args.push(api_ip.to_string()); // HTTP API IP
-                args.push("--http-port".to_string());
-                args.push(api_port.to_string()); // HTTP API Port
-                args.push("--no-color".to_string()); // No color escape codes
+                args.push("--user", &state.address) // Wallet
+                    .push("--threads", state.current_threads) // Threads
+                    .push("--rig-id", &state.rig) // Rig ID
+                    .push("--url", &url) // IP/Port
+                    .push("--http-host", &api_ip) // HTTP API IP
+                    .push("--http-port", &api_port) // HTTP API Port
+                    .flag("--no-color"); // No color escape codes
                 if state.tls {
-                    args.push("--tls".to_string());
+                    args.flag("--tls");
                 } // TLS
                 if state.keepalive {
-                    args.push("--keepalive".to_string());
+                    args.flag("--keepalive");
                 } // Keepalive
                 if state.pause != 0 {
-                    args.push("--pause-on-active".to_string());
-                    args.push(state.pause.to_string());
+                    args.push("--pause-on-active", state.pause);
                 } // Pause on active
                 *lock2!(helper, img_xmrig) = ImgXmrig {
                     url: url.clone(),
                     threads: state.current_threads.to_string(),
+                    path: path.clone(),
                 };
                 lock2!(helper, pub_api_xmrig).node = url;
             }
         }
-        args.push(format!("--http-access-token={}", state.token)); // HTTP API Port
-        args.push("--http-no-restricted".to_string());
-        (args, format!("{}:{}", api_ip, api_port))
+        // Gupaxx needs full access to the HTTP API, whatever the user gave.
+        args.set("--http-access-token", &state.token) // HTTP API Token
+            .set_flag("--http-no-restricted");
+        argv.extend(args.into_argv());
+        (argv, format!("{}:{}", api_ip, api_port))
     }
 
     // We actually spawn [sudo] on Unix, with XMRig being the argument.
@@ -385,6 +378,7 @@ impl Helper {
         process_xp: Arc<Mutex<Process>>,
         img_xmrig: &Arc<Mutex<ImgXmrig>>,
         pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
+        pool: &str,
     ) {
         // 1a. Create PTY
         debug!("XMRig | Creating PTY...");
@@ -440,6 +434,7 @@ impl Helper {
         lock.state = ProcessState::NotMining;
         lock.signal = ProcessSignal::None;
         lock.start = Instant::now();
+        lock.exit_code = None;
         drop(lock);
 
         // // 4. Spawn PTY read thread
@@ -460,6 +455,7 @@ impl Helper {
             }
             "http://".to_owned() + &api_ip_port + XMRIG_API_SUMMARY_URI
         };
+        let api_config = "http://".to_owned() + &api_ip_port + XMRIG_API_CONFIG_URI;
         info!("XMRig | Final API URI: {}", api_uri);
 
         // Reset stats before loop
@@ -545,9 +541,10 @@ impl Helper {
                 info!("XMRig Process |  redirect xmrig to p2pool since XMRig-Proxy is not alive anymore");
                 if let Err(err) = update_xmrig_config(
                     &client,
-                    XMRIG_CONFIG_URL,
+                    &api_config,
                     token,
                     &XvbNode::P2pool,
+                    pool,
                     "",
                     GUPAX_VERSION_UNDERSCORE,
                 )
@@ -582,7 +579,7 @@ impl Helper {
         gui_api_output_raw: &mut String,
         sudo: &Arc<Mutex<SudoState>>,
     ) -> bool {
-        let signal = lock!(process).signal;
+        let signal = lock!(process).signal.clone();
         if signal == ProcessSignal::Stop || signal == ProcessSignal::Restart {
             debug!("XMRig Watchdog | Stop/Restart SIGNAL caught");
             // macOS requires [sudo] again to kill [XMRig]
@@ -650,10 +647,11 @@ impl Helper {
     }
 }
 //---------------------------------------------------------------------------------------------------- [ImgXmrig]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ImgXmrig {
     pub threads: String,
     pub url: String,
+    pub path: PathBuf, // binary of XMRig, also used by the thread split of XvB
 }
 
 impl Default for ImgXmrig {
@@ -667,6 +665,15 @@ impl ImgXmrig {
         Self {
             threads: "???".to_string(),
             url: "???".to_string(),
+            path: PathBuf::new(),
+        }
+    }
+    // Without [--threads], XMRig chooses how many threads it uses.
+    pub fn from_args(args: &Args, path: &Path) -> Self {
+        Self {
+            threads: args.value("--threads").unwrap_or("???").to_string(),
+            url: args.value("--url").unwrap_or("???").to_string(),
+            path: path.to_path_buf(),
         }
     }
 }
@@ -686,6 +693,8 @@ pub struct PubXmrigApi {
     pub hashrate_raw: f32,
     pub hashrate_raw_1m: f32,
     pub hashrate_raw_15m: f32,
+    pub accepted_raw: u128,
+    pub rejected_raw: u128,
     pub node: String,
 }
 
@@ -709,6 +718,8 @@ impl PubXmrigApi {
             hashrate_raw: 0.0,
             hashrate_raw_1m: 0.0,
             hashrate_raw_15m: 0.0,
+            accepted_raw: 0,
+            rejected_raw: 0,
             node: UNKNOWN_DATA.to_string(),
         }
     }
@@ -788,6 +799,8 @@ impl PubXmrigApi {
             hashrate_raw,
             hashrate_raw_1m,
             hashrate_raw_15m,
+            accepted_raw: private.connection.accepted,
+            rejected_raw: private.connection.rejected,
             ..std::mem::take(&mut *public)
         }
     }
@@ -809,7 +822,7 @@ pub struct PrivXmrigApi {
 impl PrivXmrigApi {
     #[inline]
     // Send an HTTP request to XMRig's API, serialize it into [Self] and return it
-    async fn request_xmrig_api(
+    pub(crate) async fn request_xmrig_api(
         client: &Client,
         api_uri: &str,
         token: &str,
@@ -824,6 +837,17 @@ impl PrivXmrigApi {
             .json()
             .await?)
     }
+    // Longest average available, like the controllable hashrate of the local XMRig.
+    pub(crate) fn hashrate(&self) -> f32 {
+        self.hashrate
+            .total
+            .iter()
+            .rev()
+            .flatten()
+            .copied()
+            .find(|h| *h > 0.0)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -12,19 +12,19 @@ use std::{
 use tokio::spawn;
 
 use crate::{
-    disk::state::Xmrig,
+    disk::state::{Xmrig, XmrigProxy},
     helper::{
+        args::{Args, Binary},
         check_died, check_user_input, signal_end, sleep_end_loop,
         xrig::update_xmrig_config,
-        xvb::{nodes::XvbNode, PubXvbApi},
+        xvb::{endpoints::LocalEndpoints, nodes::XvbNode, PubXvbApi},
         Helper, Process, ProcessName, ProcessSignal, ProcessState,
     },
     macros::{arc_mut, lock, lock2, sleep},
     miscs::output_console,
     regex::{contains_timeout, contains_usepool, detect_new_node_xmrig, XMRIG_REGEX},
-    GUPAX_VERSION_UNDERSCORE, UNKNOWN_DATA,
+    GUPAX_VERSION_UNDERSCORE, UNKNOWN_DATA, XMRIG_API_CONFIG_URI, XMRIG_API_SUMMARY_URI,
 };
-use crate::{XMRIG_CONFIG_URL, XMRIG_PROXY_SUMMARY_URL};
 
 use super::xmrig::PubXmrigApi;
 impl Helper {
@@ -63,9 +63,10 @@ impl Helper {
             // need to verify if node still working
             // for that need to catch "connect error"
             // only switch nodes of XvB if XvB process is used
-            if lock!(process_xvb).is_alive() {
+            // with the stratum switcher, xmrig-proxy only sees the switcher.
+            if lock!(process_xvb).is_alive() && lock!(pub_api_xvb).switcher.is_none() {
                 if contains_timeout(&line) {
-                    let current_node = lock!(pub_api_xvb).current_node;
+                    let current_node = lock!(pub_api_xvb).current_node.clone();
                     if let Some(current_node) = current_node {
                         // updating current node to None, will stop sending signal of FailedNode until new node is set
                         // send signal to update node.
@@ -81,7 +82,11 @@ impl Helper {
                     // need to update current node because it was updated.
                     // if custom node made by user, it is not supported because algo is deciding which node to use.
 
-                    let node = detect_new_node_xmrig(&line);
+                    let (nodes, p2pool) = {
+                        let api = lock!(pub_api_xvb);
+                        (api.nodes.clone(), api.endpoints.p2pool.clone())
+                    };
+                    let node = detect_new_node_xmrig(&line, &nodes, &p2pool);
                     if node.is_none() {
                         warn!(
                             "XMRig-Proxy PTY Parse | node is not understood, switching to backup."
@@ -105,7 +110,7 @@ impl Helper {
         helper: &Arc<Mutex<Self>>,
         state: &crate::disk::state::XmrigProxy,
     ) -> Vec<String> {
-        let mut args = Vec::with_capacity(500);
+        let mut args = Args::new(Binary::XmrigProxy);
         let api_ip;
         let api_port;
         let ip;
@@ -119,20 +124,22 @@ impl Helper {
             } else {
                 state.simple_rig.clone()
             }; // Rig name
-            args.push("-o".to_string());
-            args.push("127.0.0.1:3333".to_string()); // Local P2Pool (the default)
-            args.push("-b".to_string());
-            args.push("0.0.0.0:3355".to_string());
-            args.push("--user".to_string());
-            args.push(rig); // Rig name
-            args.push("--no-color".to_string()); // No color
-            args.push("--http-host".to_string());
-            args.push("127.0.0.1".to_string()); // HTTP API IP
-            args.push("--http-port".to_string());
-            args.push("18089".to_string()); // HTTP API Port
+            args.push("--url", "127.0.0.1:3333") // Local P2Pool (the default)
+                .push("--bind", "0.0.0.0:3355")
+                .push("--user", rig) // Rig name
+                .flag("--no-color") // No color
+                .push("--http-host", "127.0.0.1") // HTTP API IP
+                .push("--http-port", "18089"); // HTTP API Port
             lock2!(helper, pub_api_xp).node = "127.0.0.1:3333 (Local P2Pool)".to_string();
 
         // [Advanced]
+        } else if !state.arguments.is_empty() {
+            // Overriding command arguments, the UI refuses to start with invalid ones,
+            // what is still not understood is given as it is to XMRig-Proxy.
+            args = Args::parse_lossy(Binary::XmrigProxy, &state.arguments);
+            if let Some(url) = args.value("--url") {
+                lock2!(helper, pub_api_xp).node = url.to_string();
+            }
         } else {
             // XMRig doesn't understand [localhost]
             let p2pool_ip = if state.p2pool_ip == "localhost" || state.p2pool_ip.is_empty() {
@@ -163,30 +170,25 @@ impl Helper {
             };
             let p2pool_url = format!("{}:{}", p2pool_ip, state.p2pool_port); // Combine IP:Port into one string
             let bind_url = format!("{}:{}", ip, port); // Combine IP:Port into one string
-            args.push("--user".to_string());
-            args.push(state.address.clone()); // Wallet
-            args.push("--rig-id".to_string());
-            args.push(state.rig.to_string()); // Rig ID
-            args.push("-o".to_string());
-            args.push(p2pool_url.clone()); // IP/Port
-            args.push("-b".to_string());
-            args.push(bind_url.clone()); // IP/Port
-            args.push("--http-host".to_string());
-            args.push(api_ip.to_string()); // HTTP API IP
-            args.push("--http-port".to_string());
-            args.push(api_port.to_string()); // HTTP API Port
-            args.push("--no-color".to_string()); // No color escape codes
+            args.push("--user", &state.address) // Wallet
+                .push("--rig-id", &state.rig) // Rig ID
+                .push("--url", &p2pool_url) // IP/Port
+                .push("--bind", &bind_url) // IP/Port
+                .push("--http-host", &api_ip) // HTTP API IP
+                .push("--http-port", &api_port) // HTTP API Port
+                .flag("--no-color"); // No color escape codes
             if state.tls {
-                args.push("--tls".to_string());
+                args.flag("--tls");
             } // TLS
             if state.keepalive {
-                args.push("--keepalive".to_string());
+                args.flag("--keepalive");
             } // Keepalive
             lock2!(helper, pub_api_xp).node = p2pool_url;
         }
-        args.push(format!("--http-access-token={}", state.token)); // HTTP API Port
-        args.push("--http-no-restricted".to_string());
-        args
+        // Gupaxx needs full access to the HTTP API, whatever the user gave.
+        args.set("--http-access-token", &state.token) // HTTP API Token
+            .set_flag("--http-no-restricted");
+        args.into_argv()
     }
 
     pub fn stop_xp(helper: &Arc<Mutex<Self>>) {
@@ -252,9 +254,8 @@ impl Helper {
         let process_xvb = Arc::clone(&lock!(helper).xvb);
         let process_xmrig = Arc::clone(&lock!(helper).xmrig);
         let path = path.to_path_buf();
-        let token = state_proxy.token.clone();
+        let state_proxy = state_proxy.clone();
         let state_xmrig = state_xmrig.clone();
-        let redirect_xmrig = state_proxy.redirect_local_xmrig;
         let pub_api_xvb = Arc::clone(&lock!(helper).pub_api_xvb);
         let gui_api_xmrig = Arc::clone(&lock!(helper).gui_api_xmrig);
         thread::spawn(move || {
@@ -264,9 +265,8 @@ impl Helper {
                 &pub_api,
                 args,
                 path,
-                &token,
+                &state_proxy,
                 &state_xmrig,
-                redirect_xmrig,
                 process_xvb,
                 process_xmrig,
                 &pub_api_xvb,
@@ -283,15 +283,17 @@ impl Helper {
         pub_api: &Arc<Mutex<PubXmrigProxyApi>>,
         args: Vec<String>,
         path: std::path::PathBuf,
-        token_proxy: &str,
+        state_proxy: &XmrigProxy,
         state_xmrig: &Xmrig,
-        xmrig_redirect: bool,
         process_xvb: Arc<Mutex<Process>>,
         process_xmrig: Arc<Mutex<Process>>,
         pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
         gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
     ) {
-        lock!(process).start = Instant::now();
+        let mut lock = lock!(process);
+        lock.start = Instant::now();
+        lock.exit_code = None;
+        drop(lock);
         // spawn pty
         debug!("XMRig-Proxy | Creating PTY...");
         let pty = portable_pty::native_pty_system();
@@ -321,9 +323,19 @@ impl Helper {
         let child_pty = arc_mut!(pair.slave.spawn_command(cmd).unwrap());
         drop(pair.slave);
         let mut stdin = pair.master.take_writer().unwrap();
-        // to refactor to let user use his own ports
-        let api_summary_xp = XMRIG_PROXY_SUMMARY_URL;
-        let api_config_xmrig = XMRIG_CONFIG_URL;
+        let token_proxy = &state_proxy.token;
+        let xmrig_redirect = state_proxy.redirect_local_xmrig;
+        let api_summary_xp = format!(
+            "http://{}/{}",
+            LocalEndpoints::xp_api(state_proxy),
+            XMRIG_API_SUMMARY_URI
+        );
+        let api_config_xmrig = format!(
+            "http://{}/{}",
+            LocalEndpoints::xmrig_api(state_xmrig),
+            XMRIG_API_CONFIG_URI
+        );
+        let stratum_xp = LocalEndpoints::xp_stratum(state_proxy);
 
         // set state
         let client = Client::new();
@@ -393,7 +405,7 @@ impl Helper {
             );
             // update data from api
             debug!("XMRig-Proxy Watchdog | Attempting HTTP API request...");
-            match PrivXmrigProxyApi::request_xp_api(&client, api_summary_xp, token_proxy).await {
+            match PrivXmrigProxyApi::request_xp_api(&client, &api_summary_xp, token_proxy).await {
                 Ok(priv_api) => {
                     debug!("XMRig-Proxy Watchdog | HTTP API request OK, attempting [update_from_priv()]");
                     PubXmrigProxyApi::update_from_priv(pub_api, priv_api);
@@ -414,9 +426,10 @@ impl Helper {
                 info!("redirect local xmrig instance to xmrig-proxy");
                 if let Err(err) = update_xmrig_config(
                     &client,
-                    api_config_xmrig,
+                    &api_config_xmrig,
                     &state_xmrig.token,
                     &XvbNode::XmrigProxy,
+                    &stratum_xp,
                     "",
                     GUPAX_VERSION_UNDERSCORE,
                 )
@@ -1,5 +1,6 @@
+use crate::disk::history::{unix_now, History, XvbDecision};
+use crate::helper::xrig::xmrig::ImgXmrig;
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
-use crate::helper::xvb::api_url_xmrig;
 use crate::helper::xvb::current_controllable_hr;
 use crate::helper::ProcessName;
 use crate::miscs::output_console;
@@ -10,193 +11,155 @@ use std::{
 };
 
 use log::{debug, info, warn};
-use readable::num::Float;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use tokio::time::sleep;
 
 use crate::{
     helper::{
         p2pool::PubP2poolApi,
-        xrig::{update_xmrig_config, xmrig::PubXmrigApi},
-        xvb::nodes::XvbNode,
+        xrig::xmrig::PubXmrigApi,
+        xvb::{
+            nodes::XvbNode,
+            split::{apply_thread_split, stop_thread_split, SplitXmrig, ThreadSplit},
+            strategy::{decide, XvbSnapshot},
+            update_miners,
+        },
     },
     macros::lock,
-    BLOCK_PPLNS_WINDOW_MAIN, BLOCK_PPLNS_WINDOW_MINI, SECOND_PER_BLOCK_P2POOL, XVB_BUFFER,
-    XVB_ROUND_DONOR_MEGA_MIN_HR, XVB_ROUND_DONOR_MIN_HR, XVB_ROUND_DONOR_VIP_MIN_HR,
-    XVB_ROUND_DONOR_WHALE_MIN_HR, XVB_TIME_ALGO,
+    XVB_TIME_ALGO, XVB_TRIGGER_DIFFICULTY, XVB_TRIGGER_HASHRATE,
 };
 
 use super::{PubXvbApi, SamplesAverageHour};
 
-pub(crate) fn calcul_donated_time(
-    lhr: f32,
-    gui_api_p2pool: &Arc<Mutex<PubP2poolApi>>,
-    gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
-    state_p2pool: &crate::disk::state::P2pool,
-) -> u32 {
-    let p2pool_ehr = lock!(gui_api_p2pool).sidechain_ehr;
-    // what if ehr stay still for the next ten minutes ? mHR will augment every ten minutes because it thinks that oHR is decreasing.
-    //
-    let avg_hr = calc_last_hour_avg_hash_rate(&lock!(gui_api_xvb).p2pool_sent_last_hour_samples);
-    let mut p2pool_ohr = p2pool_ehr - avg_hr;
-    if p2pool_ohr < 0.0 {
-        p2pool_ohr = 0.0;
-    }
-    info!("XvB Process | p2pool sidechain HR - last hour average HR = estimated outside HR\n{p2pool_ehr} - {avg_hr} = {p2pool_ohr}");
-    let mut min_hr = minimum_hashrate_share(
-        lock!(gui_api_p2pool).p2pool_difficulty_u64,
-        state_p2pool.mini,
-        p2pool_ohr,
-    );
-    if min_hr.is_sign_negative() {
-        info!("XvB Process | if minimum HR is negative, it is 0.");
-        min_hr = 0.0;
-    }
-    info!("Xvb Process | hr {}, min_hr: {} ", lhr, min_hr);
-    // numbers are divided by a thousands to print kH/s and not H/s
-    let msg_lhr = format!(
-        "{} kH/s local HR from Xmrig",
-        Float::from_3((lhr / 1000.0).into())
-    );
-    let msg_mhr = format!(
-        "{} kH/s minimum required local HR to keep a share in PPLNS window",
-        Float::from_3((min_hr / 1000.0).into())
-    );
-    let msg_ehr = format!(
-        "{} kH/s estimated sent the last hour for your address on p2pool, including this instance",
-        Float::from_3((p2pool_ehr / 1000.0).into())
-    );
-    output_console(
-        &mut lock!(gui_api_xvb).output,
-        &msg_lhr,
-        crate::helper::ProcessName::Xvb,
-    );
-    output_console(
-        &mut lock!(gui_api_xvb).output,
-        &msg_mhr,
-        crate::helper::ProcessName::Xvb,
-    );
-    output_console(
-        &mut lock!(gui_api_xvb).output,
-        &msg_ehr,
-        crate::helper::ProcessName::Xvb,
-    );
-    // calculate how much time can be spared
-    let mut spared_time = time_that_could_be_spared(lhr, min_hr);
+// Unit of the fixed split of a cycle between P2Pool and XvB.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub enum XvbSplit {
+    #[default]
+    Percent,
+    Seconds,
+}
 
-    if spared_time > 0 {
-        // if not hero option
-        if !lock!(gui_api_xvb).stats_priv.runtime_hero_mode {
-            let xvb_chr = lock!(gui_api_xvb).stats_priv.donor_1hr_avg * 1000.0;
-            info!("current HR on XvB (last hour): {xvb_chr}");
-            let shr = calc_last_hour_avg_hash_rate(&lock!(gui_api_xvb).xvb_sent_last_hour_samples);
-            // calculate how much time needed to be spared to be in most round type minimum HR + buffer
-            spared_time = minimum_time_for_highest_accessible_round(spared_time, lhr, xvb_chr, shr);
+impl XvbSplit {
+    // Seconds of the cycle given to XvB for this split value, never more than [XVB_TIME_ALGO].
+    pub fn seconds(&self, value: u32) -> u32 {
+        match self {
+            Self::Percent => XVB_TIME_ALGO * value.min(100) / 100,
+            Self::Seconds => value.min(XVB_TIME_ALGO),
         }
     }
-    if lock!(gui_api_xvb).stats_priv.runtime_hero_mode {
-        output_console(
-            &mut lock!(gui_api_xvb).output,
-            "Hero mode is enabled for this decision",
-            crate::helper::ProcessName::Xvb,
-        );
-    }
-    spared_time
-}
-fn minimum_hashrate_share(difficulty: u64, mini: bool, ohr: f32) -> f32 {
-    let pws = if mini {
-        BLOCK_PPLNS_WINDOW_MINI
-    } else {
-        BLOCK_PPLNS_WINDOW_MAIN
-    };
-    let minimum_hr = ((difficulty / (pws * SECOND_PER_BLOCK_P2POOL)) as f32 * XVB_BUFFER) - ohr;
-    info!("XvB Process | (difficulty / (window pplns blocks * seconds per p2pool block) * BUFFER) - outside HR = minimum HR to keep a share\n({difficulty} / ({pws} * {SECOND_PER_BLOCK_P2POOL}) * {XVB_BUFFER}) - {ohr} = {minimum_hr}");
-    minimum_hr
 }
-fn time_that_could_be_spared(hr: f32, min_hr: f32) -> u32 {
-    // percent of time minimum
-    let minimum_time_required_on_p2pool = XVB_TIME_ALGO as f32 / (hr / min_hr);
-    info!("XvB Process | Time of algo / local hashrate / minimum hashrate = minimum time required on p2pool\n{XVB_TIME_ALGO} / ({hr} / {min_hr}) = {minimum_time_required_on_p2pool}");
-    let spared_time = XVB_TIME_ALGO as f32 - minimum_time_required_on_p2pool;
-    info!("XvB Process | Time of algo - minimum time required on p2pool = time that can be spared.\n{XVB_TIME_ALGO} - {minimum_time_required_on_p2pool} = {spared_time}");
-    // if less than 6 seconds, XMRig could hardly have the time to mine anything.
-    if spared_time >= 6.0 {
-        return spared_time as u32;
+
+impl std::fmt::Display for XvbSplit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
     }
-    info!(
-        "XvB Process | sparted time is equal or less than 6 seconds, so everything goes to p2pool."
-    );
-    0
 }
 
-// spared time, local hr, current 1h average hr already mining on XvB, 1h average local HR sent on XvB.
-fn minimum_time_for_highest_accessible_round(st: u32, lhr: f32, chr: f32, shr: f32) -> u32 {
-    // we remove one second that could possibly be sent, because if the time needed is a float, it will be rounded up.
-    // this subtraction can not fail because mnimum spared time is >= 6.
-    let hr_for_xvb = ((st - 1) as f32 / XVB_TIME_ALGO as f32) * lhr;
-    info!(
-        "hr for xvb is: ({st} / {}) * {lhr} = {hr_for_xvb}H/s",
-        XVB_TIME_ALGO
-    );
-    let ohr = chr - shr;
-    info!("ohr is: {chr} - {shr} = {ohr}H/s");
-    let min_mega = XVB_ROUND_DONOR_MEGA_MIN_HR as f32 - ohr;
-    info!(
-        "minimum required HR for mega round is: {} - {ohr} = {min_mega}H/s",
-        XVB_ROUND_DONOR_MEGA_MIN_HR
-    );
-    let min_whale = XVB_ROUND_DONOR_WHALE_MIN_HR as f32 - ohr;
-    info!(
-        "minimum required HR for whale round is: {} - {ohr} = {min_whale}H/s",
-        XVB_ROUND_DONOR_WHALE_MIN_HR
-    );
-    let min_donorvip = XVB_ROUND_DONOR_VIP_MIN_HR as f32 - ohr;
-    info!(
-        "minimum required HR for donor vip round is: {} - {ohr} = {min_donorvip}H/s",
-        XVB_ROUND_DONOR_VIP_MIN_HR
-    );
-    let min_donor = XVB_ROUND_DONOR_MIN_HR as f32 - ohr;
-    info!(
-        "minimum required HR for donor round is: {} - {ohr} = {min_donor}H/s",
-        XVB_ROUND_DONOR_MIN_HR
-    );
-    let min = match hr_for_xvb {
-        x if x > min_mega => {
-            info!("trying to get Mega round");
-            info!(
-                "minimum second to send = ((({x} - ({x} - {min_mega})) / {lhr}) * {}) ",
-                XVB_TIME_ALGO
-            );
-            min_mega
+// Conditions in which the current cycle was decided.
+// The watchdog compares them every loop with the current ones to decide again before the end of the cycle.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CycleConditions {
+    pub shares: u32,        // shares in the PPLNS window
+    pub difficulty: u64,    // p2pool sidechain difficulty
+    pub hashrate: f32,      // short average of the controllable HR
+    pub node: XvbNode,      // preferred XvB node
+    pub node_offline: bool, // the preferred node changed because the previous one stopped answering
+    pub time_donated: u32,  // seconds given to XvB for this cycle
+}
+
+impl CycleConditions {
+    // Reason to stop the current cycle and decide again now, if any.
+    pub(crate) fn reevaluation_trigger(&self, now: &CycleConditions) -> Option<String> {
+        if self.shares > 0 && now.shares == 0 {
+            return Some("The last share left the PPLNS window".to_string());
         }
-        x if x > min_whale => {
-            info!("trying to get Whale round");
-            info!(
-                "minimum second to send = ((({x} - ({x} - {min_whale})) / {lhr}) * {}) ",
-                XVB_TIME_ALGO
-            );
-            min_whale
+        if self.difficulty > 0
+            && now.difficulty > 0
+            && (now.difficulty as f32 - self.difficulty as f32).abs() / self.difficulty as f32
+                >= XVB_TRIGGER_DIFFICULTY
+        {
+            return Some(format!(
+                "Sidechain difficulty changed from {} to {}",
+                self.difficulty, now.difficulty
+            ));
         }
-        x if x > min_donorvip => {
-            info!("trying to get Vip Donor round");
-            info!(
-                "minimum second to send = ((({x} - ({x} - {min_donorvip})) / {lhr}) * {}) ",
-                XVB_TIME_ALGO
-            );
-            min_donorvip
+        if self.hashrate > 0.0 && now.hashrate < self.hashrate * XVB_TRIGGER_HASHRATE {
+            return Some(format!(
+                "Local hashrate collapsed from {:.0} H/s to {:.0} H/s",
+                self.hashrate, now.hashrate
+            ));
         }
-        x if x > min_donor => {
-            info!("trying to get Donor round");
-            info!(
-                "minimum second to send = ((({x} - ({x} - {min_donor})) / {lhr}) * {}) ",
-                XVB_TIME_ALGO
-            );
-            min_donor
+        // the node only matters if the cycle was going to mine on it.
+        if self.time_donated > 0 && now.node != self.node {
+            return Some(if now.node == XvbNode::P2pool {
+                format!(
+                    "XvB node {} went offline and no other node is available",
+                    self.node
+                )
+            } else if now.node_offline {
+                format!(
+                    "XvB node {} went offline, {} is now used",
+                    self.node, now.node
+                )
+            } else {
+                format!(
+                    "Preferred XvB node changed from {} to {}",
+                    self.node, now.node
+                )
+            });
+        }
+        None
+    }
+}
+// An interrupted cycle still counts in the averages of the last hour, with the HR sent until the interruption.
+pub(crate) fn record_interrupted_cycle(
+    gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+    conditions: &CycleConditions,
+    elapsed: u32,
+) {
+    if elapsed == 0 {
+        return;
+    }
+    let elapsed = elapsed.min(XVB_TIME_ALGO);
+    let p2pool = elapsed.min(XVB_TIME_ALGO - conditions.time_donated);
+    let xvb = elapsed - p2pool;
+    let mut api = lock!(gui_api_xvb);
+    api.p2pool_sent_last_hour_samples
+        .0
+        .push_back(conditions.hashrate * p2pool as f32 / elapsed as f32);
+    api.xvb_sent_last_hour_samples
+        .0
+        .push_back(conditions.hashrate * xvb as f32 / elapsed as f32);
+}
+pub(crate) fn calcul_donated_time(
+    lhr: f32,
+    gui_api_p2pool: &Arc<Mutex<PubP2poolApi>>,
+    gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+    state_p2pool: &crate::disk::state::P2pool,
+    state_xvb: &crate::disk::state::Xvb,
+) -> u32 {
+    let snapshot = {
+        let p2pool = lock!(gui_api_p2pool);
+        let xvb = lock!(gui_api_xvb);
+        XvbSnapshot {
+            lhr,
+            difficulty: p2pool.p2pool_difficulty_u64,
+            mini: state_p2pool.mini,
+            sidechain_ehr: p2pool.sidechain_ehr,
+            stratum_hr: p2pool.user_p2pool_hashrate_u64 as f32,
+            p2pool_sent: calc_last_hour_avg_hash_rate(&xvb.p2pool_sent_last_hour_samples),
+            xvb_sent: calc_last_hour_avg_hash_rate(&xvb.xvb_sent_last_hour_samples),
+            xvb_1h: xvb.stats_priv.donor_1hr_avg * 1000.0,
+            mode: xvb.stats_priv.runtime_mode,
+            config: state_xvb.clone(),
         }
-        _ => return 0,
     };
-
-    (((hr_for_xvb - (hr_for_xvb - min)) / lhr) * XVB_TIME_ALGO as f32).ceil() as u32
+    let allocation = decide(&snapshot);
+    for msg in &allocation.messages {
+        output_console(&mut lock!(gui_api_xvb).output, msg, ProcessName::Xvb);
+    }
+    allocation.xvb
 }
 #[allow(clippy::too_many_arguments)]
 async fn sleep_then_update_node_xmrig(
@@ -211,7 +174,7 @@ async fn sleep_then_update_node_xmrig(
     rig: &str,
     xp_alive: bool,
 ) {
-    let node = lock!(gui_api_xvb).stats_priv.node;
+    let node = lock!(gui_api_xvb).stats_priv.node.clone();
     debug!(
         "Xvb Process | algo sleep for {} while mining on P2pool",
         XVB_TIME_ALGO - spared_time
@@ -227,8 +190,16 @@ async fn sleep_then_update_node_xmrig(
                 .as_ref()
                 .is_some_and(|n| n == &XvbNode::P2pool)
         {
-            if let Err(err) =
-                update_xmrig_config(client, api_uri, token_xmrig, &node, address, rig).await
+            if let Err(err) = update_miners(
+                client,
+                gui_api_xvb,
+                api_uri,
+                token_xmrig,
+                &node,
+                address,
+                rig,
+            )
+            .await
             {
                 // show to console error about updating xmrig config
                 warn!("Xvb Process | Failed request HTTP API {msg_xmrig_or_xp}");
@@ -254,6 +225,189 @@ async fn sleep_then_update_node_xmrig(
         sleep(Duration::from_secs(spared_time.into())).await;
     }
 }
+// Split of the threads of XMRig for the time donated, if the thread split can be used for this cycle.
+pub fn thread_split(
+    xp_alive: bool,
+    gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+    img_xmrig: &Arc<Mutex<ImgXmrig>>,
+    time_donated: u32,
+) -> Option<ThreadSplit> {
+    let msg = if xp_alive || lock!(gui_api_xvb).switcher.is_some() {
+        "The thread split is only used with XMRig, without XMRig-Proxy or the stratum switcher."
+    } else if !lock!(gui_api_xvb).rigs.is_empty() {
+        // the time donated counts the hashrate of the rigs, which the threads of XMRig can not give.
+        "The thread split is only used without remote rigs."
+    } else if let Some(split) = lock!(img_xmrig)
+        .threads
+        .parse()
+        .ok()
+        .and_then(|threads| ThreadSplit::new(threads, time_donated))
+    {
+        return Some(split);
+    } else {
+        "The thread split needs XMRig to mine with at least two threads."
+    };
+    output_console(
+        &mut lock!(gui_api_xvb).output,
+        &format!("{msg} The ten minutes are split in time instead."),
+        ProcessName::Xvb,
+    );
+    None
+}
+// XMRig mines on P2pool for the whole cycle with a part of its threads, the second XMRig mines on XvB with the others.
+#[allow(clippy::too_many_arguments)]
+async fn mine_thread_split(
+    client: &Client,
+    gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+    gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
+    split_xmrig: &Arc<Mutex<SplitXmrig>>,
+    img_xmrig: &Arc<Mutex<ImgXmrig>>,
+    api_url: &str,
+    token_xmrig: &str,
+    address: &str,
+    rig: &str,
+    split: ThreadSplit,
+    hashrate: f32,
+    time_donated: &Arc<Mutex<u32>>,
+    history: &Arc<Mutex<History>>,
+) {
+    if lock!(gui_api_xvb).current_node != Some(XvbNode::P2pool) {
+        debug!("Xvb Process | request XMRig to mine on p2pool");
+        if let Err(err) = update_miners(
+            client,
+            gui_api_xvb,
+            api_url,
+            token_xmrig,
+            &XvbNode::P2pool,
+            address,
+            rig,
+        )
+        .await
+        {
+            warn!("Xvb Process | Failed request HTTP API XMRig");
+            output_console(
+                &mut lock!(gui_api_xvb).output,
+                &format!(
+                    "Failure to update XMRig config with HTTP API.\nError: {}",
+                    err
+                ),
+                ProcessName::Xvb,
+            );
+        } else {
+            lock!(gui_api_xmrig).node = XvbNode::P2pool.to_string();
+        }
+    }
+    let (node, endpoints) = {
+        let api = lock!(gui_api_xvb);
+        (api.stats_priv.node.clone(), api.endpoints.clone())
+    };
+    let split = match apply_thread_split(
+        client,
+        split_xmrig,
+        img_xmrig,
+        &endpoints,
+        api_url,
+        token_xmrig,
+        address,
+        rig,
+        split,
+        &node,
+    )
+    .await
+    {
+        Ok(split) => split,
+        Err(err) => {
+            warn!(
+                "Xvb Process | Failed to split the threads of XMRig: {}",
+                err
+            );
+            output_console(
+                &mut lock!(gui_api_xvb).output,
+                &format!("Failure to split the threads of XMRig.\nError: {}", err),
+                ProcessName::Xvb,
+            );
+            ThreadSplit {
+                p2pool: split.threads(),
+                xvb: 0,
+            }
+        }
+    };
+    *lock!(time_donated) = split.time_donated();
+    output_console(
+        &mut lock!(gui_api_xvb).output,
+        &format!(
+            "Mining on P2pool node with {} threads and on XvB with {} threads for the next ten minutes.",
+            split.p2pool, split.xvb
+        ),
+        ProcessName::Xvb,
+    );
+    record_decision(history, gui_api_xvb, split.time_donated());
+    sleep(Duration::from_secs(XVB_TIME_ALGO.into())).await;
+    let xvb = split.xvb as f32 / split.threads() as f32;
+    let mut api = lock!(gui_api_xvb);
+    api.p2pool_sent_last_hour_samples
+        .0
+        .push_back(hashrate * (1.0 - xvb));
+    api.xvb_sent_last_hour_samples.0.push_back(hashrate * xvb);
+}
+// The cycle is split in time, the second XMRig of a previous cycle gives its threads back.
+async fn give_back_split_threads(
+    client: &Client,
+    gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+    split_xmrig: &Arc<Mutex<SplitXmrig>>,
+    img_xmrig: &Arc<Mutex<ImgXmrig>>,
+    api_url: &str,
+    token_xmrig: &str,
+) {
+    let threads = lock!(img_xmrig).threads.clone();
+    if let Err(err) = stop_thread_split(client, split_xmrig, api_url, token_xmrig, &threads).await {
+        warn!(
+            "Xvb Process | Failed to give the threads back to XMRig: {}",
+            err
+        );
+        output_console(
+            &mut lock!(gui_api_xvb).output,
+            &format!(
+                "Failure to give the threads of the thread split back to XMRig.\nError: {}",
+                err
+            ),
+            ProcessName::Xvb,
+        );
+    }
+}
+// keep the decision in the history, to be able to check later how the algorithm behaved.
+fn record_decision(
+    history: &Arc<Mutex<History>>,
+    gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+    time_donated: u32,
+) {
+    let (node, round) = {
+        let api = lock!(gui_api_xvb);
+        let node = if time_donated > 0 {
+            api.stats_priv.node.clone()
+        } else {
+            XvbNode::P2pool
+        };
+        let round = api
+            .stats_priv
+            .round_participate
+            .as_ref()
+            .map(|r| r.to_string());
+        (node.to_string(), round)
+    };
+    let decision = XvbDecision {
+        time: unix_now(),
+        donated: time_donated,
+        node,
+        round,
+    };
+    if let Err(e) = lock!(history).push_xvb(decision) {
+        warn!(
+            "Xvb Process | Failed to write the decision to the history: {}",
+            e
+        );
+    }
+}
 // push new value into samples before executing this calcul
 fn calc_last_hour_avg_hash_rate(samples: &SamplesAverageHour) -> f32 {
     samples.0.iter().sum::<f32>() / samples.0.len() as f32
@@ -267,10 +421,14 @@ pub(crate) async fn algorithm(
     gui_api_p2pool: &Arc<Mutex<PubP2poolApi>>,
     token_xmrig: &str,
     state_p2pool: &crate::disk::state::P2pool,
+    state_xvb: &crate::disk::state::Xvb,
     share: u32,
     time_donated: &Arc<Mutex<u32>>,
     rig: &str,
     xp_alive: bool,
+    history: &Arc<Mutex<History>>,
+    img_xmrig: &Arc<Mutex<ImgXmrig>>,
+    split_xmrig: &Arc<Mutex<SplitXmrig>>,
 ) {
     debug!("Xvb Process | Algorithm is started");
     output_console(
@@ -284,7 +442,7 @@ pub(crate) async fn algorithm(
     // if share is in PW,
     let msg_xmrig_or_xp = if xp_alive { "XMRig-Proxy" } else { "XMRig" };
 
-    let api_url = api_url_xmrig(xp_alive, true);
+    let api_url = lock!(gui_api_xvb).endpoints.api_url(xp_alive, true);
     if share > 0 {
         debug!("Xvb Process | Algorithm share is in current window");
         // calcul minimum HR
@@ -294,9 +452,50 @@ pub(crate) async fn algorithm(
             "At least one share is in current PPLNS window.",
             ProcessName::Xvb,
         );
-        let hashrate_xmrig = current_controllable_hr(xp_alive, gui_api_xp, gui_api_xmrig);
-        *lock!(time_donated) =
-            calcul_donated_time(hashrate_xmrig, gui_api_p2pool, gui_api_xvb, state_p2pool);
+        let hashrate_xmrig =
+            current_controllable_hr(xp_alive, gui_api_xvb, gui_api_xp, gui_api_xmrig);
+        *lock!(time_donated) = calcul_donated_time(
+            hashrate_xmrig,
+            gui_api_p2pool,
+            gui_api_xvb,
+            state_p2pool,
+            state_xvb,
+        );
+        // the thread split keeps mining on both sides for the whole cycle.
+        let split = if state_xvb.thread_split {
+            thread_split(xp_alive, gui_api_xvb, img_xmrig, *lock!(time_donated))
+        } else {
+            None
+        };
+        if let Some(split) = split {
+            mine_thread_split(
+                client,
+                gui_api_xvb,
+                gui_api_xmrig,
+                split_xmrig,
+                img_xmrig,
+                &api_url,
+                token_xmrig,
+                address,
+                rig,
+                split,
+                hashrate_xmrig,
+                time_donated,
+                history,
+            )
+            .await;
+            output_console_without_time(&mut lock!(gui_api_xvb).output, "", ProcessName::Xvb);
+            return;
+        }
+        give_back_split_threads(
+            client,
+            gui_api_xvb,
+            split_xmrig,
+            img_xmrig,
+            &api_url,
+            token_xmrig,
+        )
+        .await;
         let time_donated = *lock!(time_donated);
         debug!("Xvb Process | Donated time {} ", time_donated);
         output_console(
@@ -308,13 +507,15 @@ pub(crate) async fn algorithm(
             ),
             ProcessName::Xvb,
         );
+        record_decision(history, gui_api_xvb, time_donated);
 
         // p2pool need to be mined if donated time is not equal to xvb_time_algo
         if time_donated != XVB_TIME_ALGO && lock!(gui_api_xvb).current_node != Some(XvbNode::P2pool)
         {
             debug!("Xvb Process | request {msg_xmrig_or_xp} to mine on p2pool");
-            if let Err(err) = update_xmrig_config(
+            if let Err(err) = update_miners(
                 client,
+                gui_api_xvb,
                 &api_url,
                 token_xmrig,
                 &XvbNode::P2pool,
@@ -365,12 +566,22 @@ pub(crate) async fn algorithm(
             .0
             .push_back(hashrate_xmrig * (time_donated as f32 / XVB_TIME_ALGO as f32));
     } else {
+        give_back_split_threads(
+            client,
+            gui_api_xvb,
+            split_xmrig,
+            img_xmrig,
+            &api_url,
+            token_xmrig,
+        )
+        .await;
         // no share, so we mine on p2pool. We update xmrig only if it was still mining on XvB.
         if lock!(gui_api_xvb).current_node != Some(XvbNode::P2pool) {
             info!("Xvb Process | request {msg_xmrig_or_xp}to mine on p2pool");
 
-            if let Err(err) = update_xmrig_config(
+            if let Err(err) = update_miners(
                 client,
+                gui_api_xvb,
                 &api_url,
                 token_xmrig,
                 &XvbNode::P2pool,
@@ -400,8 +611,9 @@ pub(crate) async fn algorithm(
             "Mining on P2pool for the next ten minutes.",
             ProcessName::Xvb,
         );
+        record_decision(history, gui_api_xvb, 0);
         sleep(Duration::from_secs(XVB_TIME_ALGO.into())).await;
-        let hr = current_controllable_hr(xp_alive, gui_api_xp, gui_api_xmrig);
+        let hr = current_controllable_hr(xp_alive, gui_api_xvb, gui_api_xp, gui_api_xmrig);
         lock!(gui_api_xvb)
             .p2pool_sent_last_hour_samples
             .0
@@ -0,0 +1,133 @@
+// Local addresses used by XvB to switch the pool of XMRig/XMRig-Proxy.
+// They are derived from the state the same way the processes build their arguments,
+// so custom ports and the command arguments of the advanced modes are followed.
+
+use crate::{
+    disk::state::{P2pool, Xmrig, XmrigProxy},
+    helper::args::{Args, Binary},
+    XMRIG_API_CONFIG_URI, XMRIG_API_SUMMARY_URI,
+};
+
+const P2POOL_STRATUM: &str = "127.0.0.1:3333";
+const XP_STRATUM_PORT: &str = "3355";
+const XMRIG_API_PORT: &str = "18088";
+const XP_API_PORT: &str = "18089";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalEndpoints {
+    pub p2pool: String,    // stratum of P2Pool
+    pub xp: String,        // stratum of XMRig-Proxy
+    pub xmrig_api: String, // HTTP API of XMRig
+    pub xp_api: String,    // HTTP API of XMRig-Proxy
+}
+impl Default for LocalEndpoints {
+    fn default() -> Self {
+        Self {
+            p2pool: P2POOL_STRATUM.to_string(),
+            xp: format!("127.0.0.1:{}", XP_STRATUM_PORT),
+            xmrig_api: format!("127.0.0.1:{}", XMRIG_API_PORT),
+            xp_api: format!("127.0.0.1:{}", XP_API_PORT),
+        }
+    }
+}
+impl LocalEndpoints {
+    pub fn new(p2pool: &P2pool, xmrig: &Xmrig, xp: &XmrigProxy) -> Self {
+        Self {
+            p2pool: Self::p2pool_stratum(p2pool),
+            xp: Self::xp_stratum(xp),
+            xmrig_api: Self::xmrig_api(xmrig),
+            xp_api: Self::xp_api(xp),
+        }
+    }
+    // url of the HTTP API of xmrig-proxy if xp is true, of xmrig otherwise.
+    pub fn api_url(&self, xp: bool, config: bool) -> String {
+        let address = if xp { &self.xp_api } else { &self.xmrig_api };
+        let uri = if config {
+            XMRIG_API_CONFIG_URI
+        } else {
+            XMRIG_API_SUMMARY_URI
+        };
+        format!("http://{}/{}", address, uri)
+    }
+    // P2Pool has no stratum option outside of the command arguments.
+    pub fn p2pool_stratum(state: &P2pool) -> String {
+        if state.simple || state.arguments.is_empty() {
+            return P2POOL_STRATUM.to_string();
+        }
+        // P2Pool can listen on multiple addresses separated by commas, the first one is used.
+        Args::parse_lossy(Binary::P2pool, &state.arguments)
+            .value("--stratum")
+            .and_then(|v| v.split(',').next().map(str::to_string))
+            .map_or(P2POOL_STRATUM.to_string(), |v| reachable(&v))
+    }
+    pub fn xmrig_api(state: &Xmrig) -> String {
+        let (ip, port) = if state.simple {
+            (String::new(), String::new())
+        } else if !state.arguments.is_empty() {
+            let args = Args::parse_lossy(Binary::Xmrig, &state.arguments);
+            (
+                args.value("--http-host").unwrap_or_default().to_string(),
+                args.value("--http-port").unwrap_or_default().to_string(),
+            )
+        } else {
+            (state.api_ip.clone(), state.api_port.clone())
+        };
+        reachable(&join(&ip, &port, XMRIG_API_PORT))
+    }
+    // pool XMRig was started with, local P2Pool unless the user pointed it elsewhere.
+    pub fn xmrig_pool(state: &Xmrig) -> String {
+        if state.simple {
+            return P2POOL_STRATUM.to_string();
+        }
+        if !state.arguments.is_empty() {
+            return Args::parse_lossy(Binary::Xmrig, &state.arguments)
+                .value("--url")
+                .map_or(P2POOL_STRATUM.to_string(), reachable);
+        }
+        reachable(&join(&state.ip, &state.port, "3333"))
+    }
+    pub fn xp_stratum(state: &XmrigProxy) -> String {
+        let bind = if state.simple {
+            None
+        } else if !state.arguments.is_empty() {
+            Args::parse_lossy(Binary::XmrigProxy, &state.arguments)
+                .value("--bind")
+                .map(str::to_string)
+        } else {
+            Some(join(&state.ip, &state.port, XP_STRATUM_PORT))
+        };
+        reachable(&bind.unwrap_or_else(|| join("", "", XP_STRATUM_PORT)))
+    }
+    pub fn xp_api(state: &XmrigProxy) -> String {
+        let (ip, port) = if state.simple {
+            (String::new(), String::new())
+        } else if !state.arguments.is_empty() {
+            let args = Args::parse_lossy(Binary::XmrigProxy, &state.arguments);
+            (
+                args.value("--http-host").unwrap_or_default().to_string(),
+                args.value("--http-port").unwrap_or_default().to_string(),
+            )
+        } else {
+            (state.api_ip.clone(), state.api_port.clone())
+        };
+        reachable(&join(&ip, &port, XP_API_PORT))
+    }
+}
+
+fn join(ip: &str, port: &str, default_port: &str) -> String {
+    let port = if port.is_empty() { default_port } else { port };
+    format!("{}:{}", ip, port)
+}
+
+// A process listening on every interface is reached on the loopback.
+fn reachable(address: &str) -> String {
+    let Some((host, port)) = address.rsplit_once(':') else {
+        return address.to_string();
+    };
+    let host = match host {
+        "" | "0.0.0.0" | "localhost" => "127.0.0.1",
+        "[::]" => "[::1]",
+        host => host,
+    };
+    format!("{}:{}", host, port)
+}
@@ -1,10 +1,10 @@
+use crate::disk::history::{unix_now, History};
 use crate::helper::xrig::update_xmrig_config;
-use crate::helper::xvb::algorithm::algorithm;
+use crate::helper::xvb::algorithm::{algorithm, record_interrupted_cycle, CycleConditions};
 use crate::helper::xvb::priv_stats::XvbPrivStats;
 use crate::helper::xvb::public_stats::XvbPubStats;
 use crate::helper::{sleep_end_loop, ProcessName};
 use crate::miscs::output_console;
-use crate::{XMRIG_CONFIG_URL, XMRIG_PROXY_CONFIG_URL, XMRIG_PROXY_SUMMARY_URL, XMRIG_SUMMARY_URL};
 use bounded_vec_deque::BoundedVecDeque;
 use enclose::enc;
 use log::{debug, info, warn};
@@ -20,25 +20,35 @@ use tokio::spawn;
 use tokio::task::JoinHandle;
 use tokio::time::{sleep, Instant};
 
-use crate::helper::xvb::rounds::round_type;
-use crate::utils::constants::{XVB_PUBLIC_ONLY, XVB_TIME_ALGO};
+use crate::helper::xvb::rounds::{round_type, RoundTracker};
+use crate::utils::constants::{XVB_NODE_PING_INTERVAL, XVB_PUBLIC_ONLY, XVB_TIME_ALGO};
 use crate::{
     helper::{ProcessSignal, ProcessState},
     utils::macros::{lock, lock2, sleep},
 };
 
-use self::nodes::XvbNode;
+use self::endpoints::LocalEndpoints;
+use self::nodes::{XvbNode, XvbNodeConfig};
+use self::rigs::{update_rigs, update_rigs_hashrate, XvbRig};
+use self::split::{restore_threads, update_split_hashrate, SplitStatus, SplitXmrig};
+use self::switcher::{StratumSwitcher, Upstream};
 
 use super::p2pool::PubP2poolApi;
-use super::xrig::xmrig::PubXmrigApi;
+use super::xrig::xmrig::{ImgXmrig, PubXmrigApi};
 use super::xrig::xmrig_proxy::PubXmrigProxyApi;
 use super::{Helper, Process};
 
 pub mod algorithm;
+pub mod endpoints;
 pub mod nodes;
 pub mod priv_stats;
 pub mod public_stats;
+pub mod rigs;
 pub mod rounds;
+pub mod simulate;
+pub mod split;
+pub mod strategy;
+pub mod switcher;
 
 impl Helper {
     // Just sets some signals for the watchdog thread to pick up on.
@@ -99,6 +109,9 @@ impl Helper {
         let pub_api_xmrig = Arc::clone(&lock!(helper).pub_api_xmrig);
         let gui_api_xp = Arc::clone(&lock!(helper).gui_api_xp);
         let pub_api_xp = Arc::clone(&lock!(helper).gui_api_xp);
+        let history = Arc::clone(&lock!(helper).history);
+        let img_xmrig = Arc::clone(&lock!(helper).img_xmrig);
+        let split_xmrig = Arc::clone(&lock!(helper).split_xmrig);
         // Reset before printing to output.
         // Need to reset because values of stats would stay otherwise which could bring confusion even if panel is with a disabled theme.
         // at the start of a process, values must be default.
@@ -106,6 +119,27 @@ impl Helper {
             "XvB | resetting pub and gui but keep current node as it is updated by xmrig console."
         );
         reset_data_xvb(&pub_api, &gui_api);
+        let nodes = if state_xvb.nodes.is_empty() {
+            XvbNodeConfig::defaults()
+        } else {
+            state_xvb.nodes.clone()
+        };
+        lock!(pub_api).nodes.clone_from(&nodes);
+        lock!(gui_api).nodes = nodes;
+        let endpoints = LocalEndpoints::new(state_p2pool, state_xmrig, state_xp);
+        lock!(pub_api).endpoints.clone_from(&endpoints);
+        lock!(gui_api).endpoints = endpoints;
+        let rigs = state_xvb
+            .rigs
+            .iter()
+            .cloned()
+            .map(XvbRig::new)
+            .collect::<Vec<_>>();
+        lock!(pub_api).rigs.clone_from(&rigs);
+        lock!(gui_api).rigs = rigs;
+        // the switcher of a previous run stopped with its watchdog.
+        lock!(pub_api).switcher = None;
+        lock!(gui_api).switcher = None;
         // we reset the console output because it is complete start.
         lock!(gui_api).output.clear();
         // 2. Set process state
@@ -141,6 +175,9 @@ impl Helper {
                     &gui_api_xp,
                     &pub_api_xp,
                     &process_xp,
+                    &history,
+                    &img_xmrig,
+                    &split_xmrig,
                 );
             }),
         );
@@ -164,6 +201,9 @@ impl Helper {
         gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
         pub_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
         process_xp: &Arc<Mutex<Process>>,
+        history: &Arc<Mutex<History>>,
+        img_xmrig: &Arc<Mutex<ImgXmrig>>,
+        split_xmrig: &Arc<Mutex<SplitXmrig>>,
     ) {
         // create uniq client that is going to be used for during the life of the thread.
         let client = reqwest::Client::new();
@@ -186,6 +226,11 @@ impl Helper {
             state_xvb,
         )
         .await;
+        let switcher = if state_xvb.stratum_switcher {
+            start_switcher(pub_api, gui_api, state_xvb, state_p2pool).await
+        } else {
+            None
+        };
         let xp_alive = lock!(process_xp).state == ProcessState::Alive;
         // uptime for log of signal check ?
         let start = lock!(process).start;
@@ -202,7 +247,13 @@ impl Helper {
         // let handles;
         let handle_algo = Arc::new(Mutex::new(None));
         let handle_request = Arc::new(Mutex::new(None));
+        // conditions at the start of the running cycle, to decide again if they change too much.
+        let cycle_conditions: Arc<Mutex<Option<CycleConditions>>> = Arc::new(Mutex::new(None));
+        // raffle rounds seen, to log the participation when they end.
+        let round_tracker = Arc::new(Mutex::new(RoundTracker::default()));
         let mut msg_retry_done = false;
+        // the nodes were just pinged by the start.
+        let mut last_node_ping = std::time::Instant::now();
         info!("XvB | Entering Process mode... ");
         loop {
             debug!("XvB Watchdog | ----------- Start of loop -----------");
@@ -245,6 +296,35 @@ impl Helper {
                 info!("XvB Watchdog | Signal has stopped the loop");
                 break;
             }
+            // the second XMRig is asked every loop after the threads moved, until it shows them.
+            if lock!(split_xmrig).settled_hashrate().is_none() {
+                spawn(enc!((client, split_xmrig) async move {
+                    update_split_hashrate(&client, &split_xmrig).await;
+                }));
+            }
+            // decide again now if the conditions of the running cycle changed too much.
+            check_reevaluation_triggers(
+                process,
+                gui_api,
+                gui_api_p2pool,
+                gui_api_xmrig,
+                gui_api_xp,
+                split_xmrig,
+                &handle_algo,
+                &cycle_conditions,
+                &last_algorithm,
+                &time_donated,
+                xp_alive,
+            );
+            // ping the nodes periodically to switch to a clearly faster one.
+            if lock!(process).state == ProcessState::Alive
+                && last_node_ping.elapsed() >= Duration::from_secs(XVB_NODE_PING_INTERVAL)
+            {
+                last_node_ping = std::time::Instant::now();
+                spawn(enc!((client, pub_api, gui_api) async move {
+                    XvbNode::refresh_preferred_node(&client, &pub_api, &gui_api).await;
+                }));
+            }
             // let handle_algo_c = lock!(handle_algo);
             let is_algo_started_once = lock!(handle_algo).is_some();
             let is_algo_finished = lock!(handle_algo)
@@ -273,7 +353,7 @@ impl Helper {
                 // first_loop is false here but could be changed to true under some conditions.
                 // will send a stop signal if public stats failed or update data with new one.
                 *lock!(handle_request) = Some(spawn(
-                    enc!((client, pub_api, gui_api, gui_api_p2pool, gui_api_xmrig, gui_api_xp, state_xvb, state_p2pool, state_xmrig, state_xp, process, last_algorithm, retry, handle_algo, time_donated, last_request) async move {
+                    enc!((client, pub_api, gui_api, gui_api_p2pool, gui_api_xmrig, gui_api_xp, state_xvb, state_p2pool, state_xmrig, state_xp, process, last_algorithm, retry, handle_algo, time_donated, last_request, history, cycle_conditions, round_tracker, img_xmrig, split_xmrig) async move {
                             // needs to wait here for public stats to get private stats.
                             if last_request_expired || first_loop || should_refresh_before_next_algo {
                             XvbPubStats::update_stats(&client, &gui_api, &pub_api, &process).await;
@@ -295,6 +375,9 @@ impl Helper {
                                 )
                                 .await;
                                 *lock!(last_request) = Instant::now();
+                                // the remote rigs are part of the hashrate given to the algorithm.
+                                update_rigs_hashrate(&client, &pub_api, &gui_api).await;
+                                update_split_hashrate(&client, &split_xmrig).await;
 
                                 // verify in which round type we are
                                 let round = round_type(share, &pub_api);
@@ -307,15 +390,51 @@ impl Helper {
                                 {
                                     lock!(pub_api).stats_priv.win_current = true
                                 }
+                                // log the round if the draw ending it just happened.
+                                let record = {
+                                    let api = lock!(pub_api);
+                                    let history = lock!(history);
+                                    lock!(round_tracker).update(unix_now(), &api.stats_pub, &api.stats_priv, &state_p2pool.address, &history.xvb)
+                                };
+                                if let Some(record) = record {
+                                    let participation = match &record.round {
+                                        Some(r) => format!("you participated in the {r} round"),
+                                        None => "you did not participate".to_string(),
+                                    };
+                                    let msg = if record.won {
+                                        format!("Round ended, {participation} and won the raffle !")
+                                    } else {
+                                        format!("Round ended, {participation}, the winner is {}.", record.winner)
+                                    };
+                                    output_console(&mut lock!(gui_api).output, &msg, ProcessName::Xvb);
+                                    if let Err(e) = lock!(history).push_round(record) {
+                                        warn!("XvB | Failed to write the round to the history: {}", e);
+                                    }
+                                }
                             }
-                            let hashrate = current_controllable_hr(xp_alive, &gui_api_xp, &gui_api_xmrig);
+                            let hashrate = current_controllable_hr(xp_alive, &gui_api, &gui_api_xp, &gui_api_xmrig);
                                 if (first_loop || *lock!(retry)|| is_algo_finished) && hashrate > 0.0 && lock!(process).state == ProcessState::Alive
                                 {
                                     // if algo was started, it must not retry next loop.
                                     *lock!(retry) = false;
                                     // reset instant because algo will start.
                                     *lock!(last_algorithm) = Instant::now();
-                                    *lock!(handle_algo) = Some(spawn(enc!((client, gui_api, gui_api_xmrig, gui_api_xp, state_xmrig, state_xp, time_donated) async move {
+                                    let (node, node_offline) = {
+                                        let api = lock!(gui_api);
+                                        (api.stats_priv.node.clone(), api.stats_priv.node_offline)
+                                    };
+                                    let difficulty = lock!(gui_api_p2pool).p2pool_difficulty_u64;
+                                    let hashrate = instant_controllable_hr(xp_alive, &gui_api, &gui_api_xp, &gui_api_xmrig, &split_xmrig);
+                                    *lock!(cycle_conditions) = Some(CycleConditions {
+                                        shares: share,
+                                        difficulty,
+                                        hashrate,
+                                        node,
+                                        node_offline,
+                                        // known once the algorithm has decided.
+                                        time_donated: 0,
+                                    });
+                                    *lock!(handle_algo) = Some(spawn(enc!((client, gui_api, gui_api_xmrig, gui_api_xp, state_xmrig, state_xp, time_donated, history, img_xmrig, split_xmrig) async move {
                     let token_xmrig = if xp_alive {
                         &state_xp.token
                     } else {
@@ -334,16 +453,20 @@ impl Helper {
                                             &gui_api_p2pool,
                                             token_xmrig,
                                             &state_p2pool,
+                                            &state_xvb,
                                             share,
                                             &time_donated,
                                             rig,
-                                            xp_alive
+                                            xp_alive,
+                                            &history,
+                                            &img_xmrig,
+                                            &split_xmrig,
                                         ).await;
                                     })));
                                 } else {
                                     // if xmrig is still at 0 HR but is alive and algorithm is skipped, recheck first 10s of xmrig inside algorithm next time (in one minute). Don't check if algo failed to start because state was not alive after getting private stats.
 
-                                    if current_controllable_hr(xp_alive, &gui_api_xp, &gui_api_xmrig) == 0.0 && lock!(process).state == ProcessState::Alive {
+                                    if current_controllable_hr(xp_alive, &gui_api, &gui_api_xp, &gui_api_xmrig) == 0.0 && lock!(process).state == ProcessState::Alive {
                                         *lock!(retry) = true
                                     }
                                 }
@@ -374,6 +497,24 @@ impl Helper {
             // verify given time set by algo and start time of current algo.
             // will run only if XvB is alive.
             // let algo time to start, so no countdown is shown.
+            lock!(pub_api).time_donated = *lock!(time_donated);
+            // the second XMRig of the thread split only mines while XvB is alive.
+            let split_exited = lock!(split_xmrig).take_exited();
+            if split_exited {
+                output_console(
+                    &mut lock!(gui_api).output,
+                    "The second XMRig of the thread split exited, its threads are given back to XMRig until the next decision.",
+                    ProcessName::Xvb,
+                );
+            }
+            if split_exited
+                || (lock!(process).state != ProcessState::Alive && lock!(split_xmrig).stop())
+            {
+                spawn(enc!((client, gui_api, img_xmrig, state_xmrig) async move {
+                    give_back_threads(&client, &gui_api, &img_xmrig, &state_xmrig.token).await;
+                }));
+            }
+            lock!(pub_api).thread_split = lock!(split_xmrig).status();
             update_indicator_algo(
                 is_algo_started_once,
                 is_algo_finished,
@@ -382,6 +523,14 @@ impl Helper {
                 *lock!(time_donated),
                 &last_algorithm,
             );
+            // miners of the switcher do not tell where they mine, the switcher does.
+            if let Some(switcher) = &switcher {
+                lock!(pub_api).current_node = Some(switcher.upstream().node);
+                if let Some(node) = switcher.take_failed() {
+                    warn!("XvB | the stratum switcher could not reach {}", node);
+                    lock!(process).signal = ProcessSignal::UpdateNodes(node);
+                }
+            }
             // first_loop is done, but maybe retry will allow the algorithm to retry again.
             if first_loop {
                 first_loop = false;
@@ -389,6 +538,114 @@ impl Helper {
             // Sleep (only if 900ms hasn't passed)
             sleep_end_loop(start_loop, ProcessName::Xvb).await;
         }
+        // the switcher stops with the runtime of the watchdog.
+        lock!(pub_api).switcher = None;
+        lock!(gui_api).switcher = None;
+        if lock!(split_xmrig).stop() {
+            give_back_threads(&client, gui_api, img_xmrig, &state_xmrig.token).await;
+        }
+        lock!(pub_api).thread_split = None;
+        lock!(gui_api).thread_split = None;
+    }
+}
+// XMRig gets back the threads of the second XMRig of the thread split, which must be stopped before.
+async fn give_back_threads(
+    client: &Client,
+    gui_api: &Arc<Mutex<PubXvbApi>>,
+    img_xmrig: &Arc<Mutex<ImgXmrig>>,
+    token: &str,
+) {
+    let api_uri = lock!(gui_api).endpoints.api_url(false, true);
+    let threads = lock!(img_xmrig).threads.clone();
+    if let Err(e) = restore_threads(client, &api_uri, token, &threads).await {
+        warn!("XvB | Failed to give the threads back to XMRig: {}", e);
+        output_console(
+            &mut lock!(gui_api).output,
+            &format!("Failure to give the threads of the thread split back to XMRig with HTTP API.\nError: {e}"),
+            ProcessName::Xvb,
+        );
+    }
+}
+// Miners connected to the switcher start on P2Pool, the algorithm moves them like it does with xmrig.
+async fn start_switcher(
+    pub_api: &Arc<Mutex<PubXvbApi>>,
+    gui_api: &Arc<Mutex<PubXvbApi>>,
+    state_xvb: &crate::disk::state::Xvb,
+    state_p2pool: &crate::disk::state::P2pool,
+) -> Option<StratumSwitcher> {
+    let upstream = Upstream::new(
+        &XvbNode::P2pool,
+        &lock!(pub_api).endpoints,
+        &state_p2pool.address,
+    );
+    match StratumSwitcher::bind(&state_xvb.switcher_bind, upstream).await {
+        Ok(switcher) => {
+            output_console(
+                &mut lock!(gui_api).output,
+                &format!(
+                    "Stratum switcher listening on {}, the miners connected to it follow the decisions of XvB.",
+                    switcher.address()
+                ),
+                ProcessName::Xvb,
+            );
+            lock!(pub_api).switcher = Some(switcher.clone());
+            lock!(gui_api).switcher = Some(switcher.clone());
+            Some(switcher)
+        }
+        Err(e) => {
+            warn!("XvB | could not start the stratum switcher: {}", e);
+            output_console(
+                &mut lock!(gui_api).output,
+                &format!(
+                    "Could not start the stratum switcher on {}: {}\nXMRig or XMRig-Proxy will be switched with their HTTP API.",
+                    state_xvb.switcher_bind, e
+                ),
+                ProcessName::Xvb,
+            );
+            None
+        }
+    }
+}
+// Point the miners to the node: with the stratum switcher if it runs, with the HTTP API of xmrig or xmrig-proxy otherwise.
+// The remote rigs are always switched with their HTTP API.
+pub(crate) async fn update_miners(
+    client: &Client,
+    gui_api: &Arc<Mutex<PubXvbApi>>,
+    api_uri: &str,
+    token: &str,
+    node: &XvbNode,
+    address: &str,
+    rig: &str,
+) -> anyhow::Result<()> {
+    let (switcher, upstream, endpoints, rigs) = {
+        let api = lock!(gui_api);
+        (
+            api.switcher.clone(),
+            Upstream::new(node, &api.endpoints, address),
+            api.endpoints.clone(),
+            api.rigs
+                .iter()
+                .map(|r| r.config.clone())
+                .collect::<Vec<_>>(),
+        )
+    };
+    // the remote rigs follow even if the local miners could not be switched.
+    for (name, e) in update_rigs(client, &rigs, node, &endpoints, address).await {
+        warn!("XvB | Failed to switch rig {} to {}: {}", name, node, e);
+        output_console(
+            &mut lock!(gui_api).output,
+            &format!("Failure to switch rig {name} to {node} with HTTP API.\nError: {e}"),
+            ProcessName::Xvb,
+        );
+    }
+    match switcher {
+        Some(switcher) => {
+            switcher.switch(upstream);
+            Ok(())
+        }
+        None => {
+            update_xmrig_config(client, api_uri, token, node, &upstream.pool, address, rig).await
+        }
     }
 }
 //---------------------------------------------------------------------------------------------------- Public XvB API
@@ -405,9 +662,21 @@ pub struct PubXvbApi {
     // will be updated by output of xmrig.
     // could also be retrieved by fetching current config.
     pub current_node: Option<XvbNode>,
+    // seconds given to XvB by the last decision of the algorithm.
+    pub time_donated: u32,
+    // XvB nodes of the state for this run, so the output of xmrig can be matched against them.
+    pub nodes: Vec<XvbNodeConfig>,
+    // local P2Pool, XMRig and XMRig-Proxy of the state for this run.
+    pub endpoints: LocalEndpoints,
+    // switches the miners instead of the HTTP API of xmrig if enabled.
+    pub switcher: Option<StratumSwitcher>,
+    // remote XMRig of the state for this run, with their last hashrate.
+    pub rigs: Vec<XvbRig>,
+    // threads mining on each side if the thread split is in place.
+    pub thread_split: Option<SplitStatus>,
 }
 #[derive(Debug, Clone)]
-pub struct SamplesAverageHour(BoundedVecDeque<f32>);
+pub struct SamplesAverageHour(pub(crate) BoundedVecDeque<f32>);
 impl Default for SamplesAverageHour {
     fn default() -> Self {
         let capacity = (3600 / XVB_TIME_ALGO) as usize;
@@ -423,6 +692,14 @@ impl PubXvbApi {
     pub fn new() -> Self {
         Self::default()
     }
+    // hashrate of the remote rigs that answered their last request.
+    pub fn rigs_hashrate(&self) -> f32 {
+        self.rigs.iter().map(|r| r.hashrate).sum()
+    }
+    // hashrate of the second XMRig of the thread split.
+    pub fn split_hashrate(&self) -> f32 {
+        self.thread_split.as_ref().map_or(0.0, |s| s.hashrate)
+    }
     // The issue with just doing [gui_api = pub_api] is that values get overwritten.
     // This doesn't matter for any of the values EXCEPT for the output,  so we must
     // manually append it instead of overwriting.
@@ -433,11 +710,11 @@ impl PubXvbApi {
         if !buf.is_empty() {
             output.push_str(&buf);
         }
-        let runtime_hero_mode = std::mem::take(&mut gui_api.stats_priv.runtime_hero_mode);
+        let runtime_mode = std::mem::take(&mut gui_api.stats_priv.runtime_mode);
         *gui_api = Self {
             output,
             stats_priv: XvbPrivStats {
-                runtime_hero_mode,
+                runtime_mode,
                 ..pub_api.stats_priv.clone()
             },
             p2pool_sent_last_hour_samples: std::mem::take(
@@ -557,9 +834,10 @@ async fn check_state_outcauses_xvb(
                 };
                 spawn(
                     enc!((client, pub_api_xmrig, gui_api,pub_api_xp) async move {
-                    let url_api = api_url_xmrig(xp_is_alive, true);
-                    if let Err(err) = update_xmrig_config(
+                    let url_api = lock!(gui_api).endpoints.api_url(xp_is_alive, true);
+                    if let Err(err) = update_miners(
                         &client,
+                        &gui_api,
                         &url_api,
                         &token_xmrig,
                         &XvbNode::P2pool,
@@ -660,7 +938,7 @@ fn signal_interrupt(
     // check if STOP or RESTART Signal is given.
     // if STOP, will put Signal to None, if Restart to Wait
     // in either case, will break from loop.
-    let signal = lock!(process).signal;
+    let signal = lock!(process).signal.clone();
     match signal {
         ProcessSignal::Stop => {
             debug!("P2Pool Watchdog | Stop SIGNAL caught");
@@ -722,7 +1000,7 @@ fn signal_interrupt(
                     enc!((node, process, client, gui_api, pub_api, was_alive, address, token_xmrig, gui_api_xmrig, gui_api_xp) async move {
                         warn!("in spawn of UpdateNodes");
                     match node {
-                        XvbNode::NorthAmerica|XvbNode::Europe if was_alive => {
+                        XvbNode::Xvb(_) if was_alive => {
                         warn!("current is XvB node ? update to see which is available");
                             // a node is failing. We need to first verify if a node is available
                         XvbNode::update_fastest_node(&client, &gui_api, &pub_api, &process).await;
@@ -741,7 +1019,7 @@ fn signal_interrupt(
 
                             
                         },
-                        XvbNode::NorthAmerica|XvbNode::Europe if !was_alive => {
+                        XvbNode::Xvb(_) if !was_alive => {
                         lock!(process).state = ProcessState::Syncing;
                             // Probably a start. We don't consider XMRig using XvB nodes without algo.
                                 // can update xmrig and check status of state in the same time.
@@ -749,10 +1027,11 @@ fn signal_interrupt(
 
                 if lock!(gui_api).current_node != Some(XvbNode::P2pool) {
                                 spawn(enc!((client, token_xmrig, address, gui_api_xmrig, gui_api_xp, gui_api) async move{
-                    let url_api = api_url_xmrig(xp_alive, true);
+                    let url_api = lock!(gui_api).endpoints.api_url(xp_alive, true);
                         warn!("update xmrig to use node ?");
-                    if let Err(err) = update_xmrig_config(
+                    if let Err(err) = update_miners(
                         &client,
+                        &gui_api,
                         &url_api,
                         &token_xmrig,
                         &XvbNode::P2pool,
@@ -795,14 +1074,27 @@ fn signal_interrupt(
 }
 fn reset_data_xvb(pub_api: &Arc<Mutex<PubXvbApi>>, gui_api: &Arc<Mutex<PubXvbApi>>) {
     let current_node = mem::take(&mut lock!(pub_api).current_node.clone());
-    let runtime_hero_mode = mem::take(&mut lock!(gui_api).stats_priv.runtime_hero_mode);
+    let runtime_mode = mem::take(&mut lock!(gui_api).stats_priv.runtime_mode);
+    let nodes = mem::take(&mut lock!(pub_api).nodes);
+    let endpoints = mem::take(&mut lock!(pub_api).endpoints);
+    let switcher = mem::take(&mut lock!(pub_api).switcher);
+    let rigs = mem::take(&mut lock!(pub_api).rigs);
     // let output = mem::take(&mut lock!(gui_api).output);
     *lock!(pub_api) = PubXvbApi::new();
     *lock!(gui_api) = PubXvbApi::new();
     // to keep the value modified by xmrig even if xvb is dead.
     lock!(pub_api).current_node = current_node;
-    // to not loose the information of runtime hero mode between restart
-    lock!(gui_api).stats_priv.runtime_hero_mode = runtime_hero_mode;
+    // nodes are only set at the start of the process.
+    lock!(gui_api).nodes.clone_from(&nodes);
+    lock!(pub_api).nodes = nodes;
+    lock!(gui_api).endpoints.clone_from(&endpoints);
+    lock!(pub_api).endpoints = endpoints;
+    lock!(gui_api).switcher.clone_from(&switcher);
+    lock!(pub_api).switcher = switcher;
+    lock!(gui_api).rigs.clone_from(&rigs);
+    lock!(pub_api).rigs = rigs;
+    // to not loose the information of runtime mode between restart
+    lock!(gui_api).stats_priv.runtime_mode = runtime_mode;
     // message while starting must be preserved.
     // lock!(pub_api).output = output;
 }
@@ -816,9 +1108,11 @@ fn update_indicator_algo(
     last_algorithm: &Arc<Mutex<Instant>>,
 ) {
     if is_algo_started_once && !is_algo_finished && lock!(process).state == ProcessState::Alive {
-        let node = lock!(pub_api).current_node;
+        let node = lock!(pub_api).current_node.clone();
+        let split = lock!(pub_api).thread_split.is_some();
         let msg_indicator = match node {
-            Some(XvbNode::P2pool) if time_donated > 0 => {
+            // with the thread split, both sides are mined for the whole cycle.
+            Some(XvbNode::P2pool) if time_donated > 0 && !split => {
                 // algo is mining on p2pool but will switch to XvB after
                 // show time remaining on p2pool
                 lock!(pub_api).stats_priv.time_switch_node = XVB_TIME_ALGO
@@ -844,25 +1138,104 @@ fn update_indicator_algo(
     }
 }
 
-// quick temporary function before refactor, but better than repeating this code
-// if xp is alive, put true
-// to get config url, true. False for summary
-pub fn api_url_xmrig(xp: bool, config: bool) -> String {
-    if xp {
-        if config {
-            XMRIG_PROXY_CONFIG_URL.to_string()
-        } else {
-            XMRIG_PROXY_SUMMARY_URL.to_string()
-        }
-    } else if config {
-        XMRIG_CONFIG_URL.to_string()
+// shortest average HR of xmrig or xmrig-proxy, to notice quickly when it collapses.
+// the second XMRig is read directly, the API only gets its hashrate at the end of the loop.
+fn instant_controllable_hr(
+    xp_alive: bool,
+    gui_api: &Arc<Mutex<PubXvbApi>>,
+    gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
+    gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
+    split_xmrig: &Arc<Mutex<SplitXmrig>>,
+) -> f32 {
+    let local = if xp_alive {
+        lock!(gui_api_xp).hashrate_1m
+    } else {
+        lock!(gui_api_xmrig).hashrate_raw
+    };
+    let split = lock!(split_xmrig).status().map_or(0.0, |s| s.hashrate);
+    local + lock!(gui_api).rigs_hashrate() + split
+}
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_reevaluation_triggers(
+    process: &Arc<Mutex<Process>>,
+    gui_api: &Arc<Mutex<PubXvbApi>>,
+    gui_api_p2pool: &Arc<Mutex<PubP2poolApi>>,
+    gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
+    gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
+    split_xmrig: &Arc<Mutex<SplitXmrig>>,
+    handle_algo: &Arc<Mutex<Option<JoinHandle<()>>>>,
+    cycle_conditions: &Arc<Mutex<Option<CycleConditions>>>,
+    last_algorithm: &Arc<Mutex<Instant>>,
+    time_donated: &Arc<Mutex<u32>>,
+    xp_alive: bool,
+) {
+    // only a running cycle of an alive process can be decided again, the other cases are managed by check_state_outcauses_xvb.
+    if lock!(process).state != ProcessState::Alive
+        || !lock!(handle_algo)
+            .as_ref()
+            .is_some_and(|algo| !algo.is_finished())
+    {
+        return;
+    }
+    let Some(mut start) = lock!(cycle_conditions).clone() else {
+        return;
+    };
+    start.time_donated = *lock!(time_donated);
+    // threads moved by the thread split take a while to show in the hashrates, so they can not be compared until then.
+    let hashrate = if lock!(split_xmrig).settled_hashrate().is_some() {
+        instant_controllable_hr(xp_alive, gui_api, gui_api_xp, gui_api_xmrig, split_xmrig)
     } else {
-        XMRIG_SUMMARY_URL.to_string()
+        start.hashrate
+    };
+    let (node, node_offline) = {
+        let api = lock!(gui_api);
+        (api.stats_priv.node.clone(), api.stats_priv.node_offline)
+    };
+    let (shares, difficulty) = {
+        let api = lock!(gui_api_p2pool);
+        (api.sidechain_shares, api.p2pool_difficulty_u64)
+    };
+    let now = CycleConditions {
+        shares,
+        difficulty,
+        hashrate,
+        node,
+        node_offline,
+        time_donated: start.time_donated,
+    };
+    if let Some(reason) = start.reevaluation_trigger(&now) {
+        info!("XvB | {reason}, the algorithm will decide again now.");
+        if let Some(handle) = lock!(handle_algo).as_ref() {
+            handle.abort();
+        }
+        record_interrupted_cycle(
+            gui_api,
+            &start,
+            lock!(last_algorithm).elapsed().as_secs() as u32,
+        );
+        // the algorithm is now finished, so the next loop will start it again without waiting for the end of the cycle.
+        *lock!(cycle_conditions) = None;
+        output_console(
+            &mut lock!(gui_api).output,
+            &format!("{reason}, stopping the current cycle to decide again."),
+            ProcessName::Xvb,
+        );
     }
 }
 // get the current HR of xmrig or xmrig-proxy
 // will get a longer average HR since it will be more accurate. Shorter timeframe can induce volatility.
 fn current_controllable_hr(
+    xp_alive: bool,
+    gui_api: &Arc<Mutex<PubXvbApi>>,
+    gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
+    gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
+) -> f32 {
+    // the local hashrate is read first, gui_api is locked after the APIs of XMRig like in the Helper loop.
+    let local = local_controllable_hr(xp_alive, gui_api_xp, gui_api_xmrig);
+    let api = lock!(gui_api);
+    local + api.rigs_hashrate() + api.split_hashrate()
+}
+fn local_controllable_hr(
     xp_alive: bool,
     gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
     gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
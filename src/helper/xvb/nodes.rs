@@ -3,67 +3,104 @@ use std::{
     time::{Duration, Instant},
 };
 
-use derive_more::Display;
 use log::{error, info, warn};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use tokio::spawn;
 
 use crate::{
     components::node::{GetInfo, TIMEOUT_NODE_PING},
     helper::{xvb::output_console, Process, ProcessName, ProcessState},
     macros::lock,
-    GUPAX_VERSION_UNDERSCORE, XVB_NODE_EU, XVB_NODE_NA, XVB_NODE_PORT, XVB_NODE_RPC,
+    GUPAX_VERSION_UNDERSCORE, XVB_NODE_EU, XVB_NODE_HYSTERESIS_MS, XVB_NODE_HYSTERESIS_RATIO,
+    XVB_NODE_NA, XVB_NODE_PORT, XVB_NODE_RPC,
 };
 
-use super::PubXvbApi;
-#[derive(Copy, Clone, Debug, Default, PartialEq, Display)]
+use super::{endpoints::LocalEndpoints, PubXvbApi};
+// An XvB node, the list of nodes used is in the state.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct XvbNodeConfig {
+    pub name: String, // Shown as "XvB [name] Node"
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub keepalive: bool,
+}
+impl XvbNodeConfig {
+    pub fn europe() -> Self {
+        Self {
+            name: "European".to_string(),
+            host: XVB_NODE_EU.to_string(),
+            port: XVB_NODE_PORT,
+            tls: true,
+            keepalive: true,
+        }
+    }
+    pub fn north_america() -> Self {
+        Self {
+            name: "North America".to_string(),
+            host: XVB_NODE_NA.to_string(),
+            port: XVB_NODE_PORT,
+            tls: true,
+            keepalive: true,
+        }
+    }
+    // Nodes of the default state, also used if the list of the state is empty.
+    pub fn defaults() -> Vec<Self> {
+        vec![Self::europe(), Self::north_america()]
+    }
+}
+impl Default for XvbNodeConfig {
+    fn default() -> Self {
+        Self::europe()
+    }
+}
+#[derive(Clone, Debug, PartialEq)]
 pub enum XvbNode {
-    #[display(fmt = "XvB North America Node")]
-    NorthAmerica,
-    #[default]
-    #[display(fmt = "XvB European Node")]
-    Europe,
-    #[display(fmt = "Local P2pool")]
+    Xvb(XvbNodeConfig),
     P2pool,
-    #[display(fmt = "Xmrig Proxy")]
     XmrigProxy,
 }
-impl XvbNode {
-    pub fn url(&self) -> String {
+impl Default for XvbNode {
+    fn default() -> Self {
+        Self::Xvb(XvbNodeConfig::default())
+    }
+}
+impl std::fmt::Display for XvbNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Self::NorthAmerica => String::from(XVB_NODE_NA),
-            Self::Europe => String::from(XVB_NODE_EU),
-            Self::P2pool => String::from("127.0.0.1"),
-            Self::XmrigProxy => String::from("127.0.0.1"),
+            Self::Xvb(node) => write!(f, "XvB {} Node", node.name),
+            Self::P2pool => write!(f, "Local P2pool"),
+            Self::XmrigProxy => write!(f, "Xmrig Proxy"),
         }
     }
-    pub fn port(&self) -> String {
+}
+impl XvbNode {
+    // [ip:port] to put in the pool of xmrig, local ones depend on the state of the processes.
+    pub fn pool(&self, endpoints: &LocalEndpoints) -> String {
         match self {
-            Self::NorthAmerica | Self::Europe => String::from(XVB_NODE_PORT),
-            Self::P2pool => String::from("3333"),
-            Self::XmrigProxy => String::from("3355"),
+            Self::Xvb(node) => format!("{}:{}", node.host, node.port),
+            Self::P2pool => endpoints.p2pool.clone(),
+            Self::XmrigProxy => endpoints.xp.clone(),
         }
     }
     pub fn user(&self, address: &str) -> String {
         match self {
-            Self::NorthAmerica => address.chars().take(8).collect(),
-            Self::Europe => address.chars().take(8).collect(),
+            Self::Xvb(_) => address.chars().take(8).collect(),
             Self::P2pool => GUPAX_VERSION_UNDERSCORE.to_string(),
             Self::XmrigProxy => GUPAX_VERSION_UNDERSCORE.to_string(),
         }
     }
     pub fn tls(&self) -> bool {
         match self {
-            Self::NorthAmerica => true,
-            Self::Europe => true,
+            Self::Xvb(node) => node.tls,
             Self::P2pool => false,
             Self::XmrigProxy => false,
         }
     }
     pub fn keepalive(&self) -> bool {
         match self {
-            Self::NorthAmerica => true,
-            Self::Europe => true,
+            Self::Xvb(node) => node.keepalive,
             Self::P2pool => false,
             Self::XmrigProxy => false,
         }
@@ -75,41 +112,13 @@ impl XvbNode {
         gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
         process_xvb: &Arc<Mutex<Process>>,
     ) {
-        let client_eu = client.clone();
-        let client_na = client.clone();
-        // two spawn to ping the two nodes in parallel and not one after the other.
-        let ms_eu = spawn(async move { XvbNode::ping(&XvbNode::Europe.url(), &client_eu).await });
-        let ms_na =
-            spawn(async move { XvbNode::ping(&XvbNode::NorthAmerica.url(), &client_na).await });
-        let node = if let Ok(ms_eu) = ms_eu.await {
-            if let Ok(ms_na) = ms_na.await {
-                // if two nodes are up, compare ping latency and return fastest.
-                if ms_na != TIMEOUT_NODE_PING && ms_eu != TIMEOUT_NODE_PING {
-                    if ms_na < ms_eu {
-                        XvbNode::NorthAmerica
-                    } else {
-                        XvbNode::Europe
-                    }
-                } else if ms_na != TIMEOUT_NODE_PING && ms_eu == TIMEOUT_NODE_PING {
-                    // if only na is online, return it.
-                    XvbNode::NorthAmerica
-                } else if ms_na == TIMEOUT_NODE_PING && ms_eu != TIMEOUT_NODE_PING {
-                    // if only eu is online, return it.
-                    XvbNode::Europe
-                } else {
-                    // if P2pool is returned, it means none of the two nodes are available.
-                    XvbNode::P2pool
-                }
-            } else {
-                error!("ping has failed !");
-                XvbNode::P2pool
-            }
-        } else {
-            error!("ping has failed !");
-            XvbNode::P2pool
-        };
+        let nodes = lock!(pub_api_xvb).nodes.clone();
+        let pings = XvbNode::ping_all(client, &nodes).await;
+        // if P2pool is returned, it means none of the nodes are available.
+        let node = select_node(None, &nodes, &pings)
+            .map_or(XvbNode::P2pool, |i| XvbNode::Xvb(nodes[i].clone()));
         if node == XvbNode::P2pool {
-            // if both nodes are dead, then the state of the process must be NodesOffline
+            // if all nodes are dead, then the state of the process must be NodesOffline
             info!("XvB node ping, all offline or ping failed, switching back to local p2pool",);
             output_console(
                 &mut lock!(gui_api_xvb).output,
@@ -119,7 +128,7 @@ impl XvbNode {
             lock!(process_xvb).state = ProcessState::OfflineNodesAll;
         } else {
             // if node is up and because update_fastest is used only if token/address is valid, it means XvB process is Alive.
-            info!("XvB node ping, both online and best is {}", node.url());
+            info!("XvB node ping, best is {}", node);
             output_console(
                 &mut lock!(gui_api_xvb).output,
                 &format!("XvB node ping, {} is selected as the fastest.", node),
@@ -135,7 +144,71 @@ impl XvbNode {
                 lock!(process_xvb).state = ProcessState::Syncing;
             }
         }
-        lock!(pub_api_xvb).stats_priv.node = node;
+        let stats = &mut lock!(pub_api_xvb).stats_priv;
+        stats.node = node;
+        // only used when a node fails, or at the start when there is no node to compare with.
+        stats.node_offline = true;
+    }
+    // Periodic ping while XvB is running, to switch to a clearly faster node.
+    // Nodes failing are already managed by the output of XMRig, so nothing is done if none answers.
+    pub async fn refresh_preferred_node(
+        client: &Client,
+        pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
+        gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+    ) {
+        let (nodes, current) = {
+            let api = lock!(pub_api_xvb);
+            let current = match &api.stats_priv.node {
+                XvbNode::Xvb(node) => Some(node.clone()),
+                _ => None,
+            };
+            (api.nodes.clone(), current)
+        };
+        let pings = XvbNode::ping_all(client, &nodes).await;
+        let Some(i) = select_node(current.as_ref(), &nodes, &pings) else {
+            warn!("XvB node ping, no node answered the periodic ping");
+            return;
+        };
+        if current.as_ref() == Some(&nodes[i]) {
+            return;
+        }
+        // the current node is kept as long as it answers, unless another one is clearly faster.
+        let offline = current
+            .as_ref()
+            .and_then(|c| nodes.iter().position(|n| n == c))
+            .map_or(true, |c| pings[c] == TIMEOUT_NODE_PING);
+        let node = XvbNode::Xvb(nodes[i].clone());
+        info!("XvB node ping, switching to {} ({} ms)", node, pings[i]);
+        output_console(
+            &mut lock!(gui_api_xvb).output,
+            &format!(
+                "XvB node ping, {} is now preferred ({} ms).",
+                node, pings[i]
+            ),
+            ProcessName::Xvb,
+        );
+        let stats = &mut lock!(pub_api_xvb).stats_priv;
+        stats.node = node;
+        stats.node_offline = offline;
+    }
+    // Ping every node in parallel and not one after the other, [TIMEOUT_NODE_PING] if offline.
+    async fn ping_all(client: &Client, nodes: &[XvbNodeConfig]) -> Vec<u128> {
+        let handles: Vec<_> = nodes
+            .iter()
+            .map(|node| {
+                let client = client.clone();
+                let host = node.host.clone();
+                spawn(async move { XvbNode::ping(&host, &client).await })
+            })
+            .collect();
+        let mut pings = Vec::with_capacity(handles.len());
+        for handle in handles {
+            pings.push(handle.await.unwrap_or_else(|_| {
+                error!("ping has failed !");
+                TIMEOUT_NODE_PING
+            }));
+        }
+        pings
     }
     async fn ping(ip: &str, client: &Client) -> u128 {
         let request = client
@@ -169,3 +242,27 @@ impl XvbNode {
         ms
     }
 }
+// Index of the node to use after a ping of every node, [None] if none answered.
+// The current node is kept unless another one answers clearly faster, so it does not switch at every ping.
+pub(crate) fn select_node(
+    current: Option<&XvbNodeConfig>,
+    nodes: &[XvbNodeConfig],
+    pings: &[u128],
+) -> Option<usize> {
+    let online = |i: &usize| pings.get(*i).is_some_and(|ms| *ms != TIMEOUT_NODE_PING);
+    let fastest = (0..nodes.len()).filter(online).min_by_key(|i| pings[*i])?;
+    let Some(current) = current
+        .and_then(|c| nodes.iter().position(|n| n == c))
+        .filter(online)
+    else {
+        return Some(fastest);
+    };
+    let (ms_current, ms_fastest) = (pings[current], pings[fastest]);
+    if (ms_fastest as f32) < ms_current as f32 * XVB_NODE_HYSTERESIS_RATIO
+        && ms_current - ms_fastest >= XVB_NODE_HYSTERESIS_MS
+    {
+        Some(fastest)
+    } else {
+        Some(current)
+    }
+}
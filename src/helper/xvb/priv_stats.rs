@@ -15,7 +15,7 @@ use crate::{
     XVB_URL,
 };
 
-use super::{nodes::XvbNode, rounds::XvbRound, PubXvbApi};
+use super::{nodes::XvbNode, rounds::XvbRound, strategy::XvbMode, PubXvbApi};
 
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct XvbPrivStats {
@@ -29,14 +29,17 @@ pub struct XvbPrivStats {
     #[serde(skip)]
     pub node: XvbNode,
     #[serde(skip)]
+    // the node was changed because the previous one stopped answering, not for a faster one.
+    pub node_offline: bool,
+    #[serde(skip)]
     // it is the time remaining before switching from P2pool to XvB or XvB to P2ool.
     // it is not the time remaining of the algo, even if it could be the same if never mining on XvB.
     pub time_switch_node: u32,
     #[serde(skip)]
     pub msg_indicator: String,
     #[serde(skip)]
-    // so the hero mode can change between two decision of algorithm without restarting XvB.
-    pub runtime_hero_mode: bool,
+    // so the mode can change between two decision of algorithm without restarting XvB.
+    pub runtime_mode: XvbMode,
 }
 
 impl XvbPrivStats {
@@ -83,7 +86,11 @@ impl XvbPrivStats {
         match XvbPrivStats::request_api(client, address, token).await {
             Ok(new_data) => {
                 debug!("XvB Watchdog | HTTP API request OK");
-                lock!(&pub_api).stats_priv = new_data;
+                // the other fields are not given by the API, they must not be reset (ex: preferred node).
+                let stats = &mut lock!(&pub_api).stats_priv;
+                stats.fails = new_data.fails;
+                stats.donor_1hr_avg = new_data.donor_1hr_avg;
+                stats.donor_24hr_avg = new_data.donor_24hr_avg;
                 // if last request failed, we are now ready to show stats again and maybe be alive next loop.
             }
             Err(err) => {
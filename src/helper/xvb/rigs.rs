@@ -0,0 +1,214 @@
+// Remote XMRig instances controlled by the algorithm through their HTTP API, in addition to the local XMRig or XMRig-Proxy.
+// Their hashrate is part of the controllable hashrate and they are switched with the local miners.
+
+use std::{
+    net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helper::{
+        xrig::{update_xmrig_config, xmrig::PrivXmrigApi},
+        xvb::output_console,
+        ProcessName,
+    },
+    macros::lock,
+    XMRIG_API_CONFIG_URI, XMRIG_API_SUMMARY_URI,
+};
+
+use super::{endpoints::LocalEndpoints, nodes::XvbNode, PubXvbApi};
+
+// A remote XMRig, the list is in the state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct XvbRigConfig {
+    pub name: String,   // Name shown in the console, also used as rig-id
+    pub api: String,    // [ip:port] of the HTTP API
+    pub token: String,  // Access token of the HTTP API
+    pub p2pool: String, // [ip:port] of P2Pool as reached by the rig, the local stratum on the LAN if empty
+}
+impl XvbRigConfig {
+    pub fn api_url(&self, config: bool) -> String {
+        let uri = if config {
+            XMRIG_API_CONFIG_URI
+        } else {
+            XMRIG_API_SUMMARY_URI
+        };
+        format!("http://{}/{}", self.api, uri)
+    }
+    // a remote rig can not reach the local addresses, and it is not behind the local XMRig-Proxy.
+    pub fn pool(&self, node: &XvbNode, endpoints: &LocalEndpoints) -> Result<String> {
+        match node {
+            XvbNode::Xvb(_) => Ok(node.pool(endpoints)),
+            XvbNode::P2pool | XvbNode::XmrigProxy if !self.p2pool.is_empty() => {
+                Ok(self.p2pool.clone())
+            }
+            XvbNode::P2pool | XvbNode::XmrigProxy => {
+                Ok(rig_stratum(&endpoints.p2pool, self.local_ip()?))
+            }
+        }
+    }
+    // Address of this host on the way to the rig, nothing is sent to know it.
+    fn local_ip(&self) -> Result<IpAddr> {
+        let rig = self
+            .api
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("{} has no address", self.api))?;
+        let any: SocketAddr = if rig.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(any)?;
+        socket.connect(rig)?;
+        Ok(socket.local_addr()?.ip())
+    }
+}
+
+// The local stratum is written as reached from this host, loopback for a P2Pool listening on every interface.
+// A rig on another host reaches it with [local_ip], the address of this host on its side.
+pub fn rig_stratum(stratum: &str, local_ip: IpAddr) -> String {
+    let Ok(address) = stratum.parse::<SocketAddr>() else {
+        return stratum.to_string();
+    };
+    let ip = address.ip();
+    if local_ip.is_loopback() || !(ip.is_loopback() || ip.is_unspecified()) {
+        return stratum.to_string();
+    }
+    SocketAddr::new(local_ip, address.port()).to_string()
+}
+
+// A remote rig of this run with the result of its last request.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XvbRig {
+    pub config: XvbRigConfig,
+    pub hashrate: f32,         // H/s, 0 if the rig could not be reached
+    pub error: Option<String>, // error of the last request of the summary
+}
+impl XvbRig {
+    pub fn new(config: XvbRigConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+}
+
+// Request the summary of every rig at the same time, the result is in the same order.
+pub async fn fetch_hashrates(client: &Client, rigs: &[XvbRigConfig]) -> Vec<Result<f32>> {
+    let handles = rigs
+        .iter()
+        .map(|rig| {
+            let (client, url, token) = (client.clone(), rig.api_url(false), rig.token.clone());
+            tokio::spawn(async move {
+                PrivXmrigApi::request_xmrig_api(&client, &url, &token)
+                    .await
+                    .map(|summary| summary.hashrate())
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut hashrates = Vec::with_capacity(handles.len());
+    for handle in handles {
+        hashrates.push(handle.await.unwrap_or_else(|e| Err(e.into())));
+    }
+    hashrates
+}
+
+// Refresh the hashrate of the rigs, a rig that stops or starts answering is written to the console.
+pub async fn update_rigs_hashrate(
+    client: &Client,
+    pub_api: &Arc<Mutex<PubXvbApi>>,
+    gui_api: &Arc<Mutex<PubXvbApi>>,
+) {
+    let configs = lock!(pub_api)
+        .rigs
+        .iter()
+        .map(|r| r.config.clone())
+        .collect::<Vec<_>>();
+    if configs.is_empty() {
+        return;
+    }
+    let hashrates = fetch_hashrates(client, &configs).await;
+    let mut rigs = lock!(pub_api).rigs.clone();
+    for (rig, hashrate) in rigs.iter_mut().zip(hashrates) {
+        let msg = match hashrate {
+            Ok(hashrate) => {
+                rig.hashrate = hashrate;
+                rig.error
+                    .take()
+                    .map(|_| format!("Rig {} is reachable again.", rig.config.name))
+            }
+            Err(e) => {
+                rig.hashrate = 0.0;
+                let new = rig.error.is_none();
+                rig.error = Some(e.to_string());
+                new.then(|| {
+                    format!(
+                        "Rig {} can not be reached on {}, its hashrate is not counted.\nError: {}",
+                        rig.config.name, rig.config.api, e
+                    )
+                })
+            }
+        };
+        if let Some(msg) = msg {
+            warn!("XvB | {}", msg);
+            output_console(&mut lock!(gui_api).output, &msg, ProcessName::Xvb);
+        }
+    }
+    lock!(gui_api).rigs.clone_from(&rigs);
+    lock!(pub_api).rigs = rigs;
+}
+
+// Point every rig to the node at the same time, a rig failing does not stop the others. Returns the rigs that failed.
+pub async fn update_rigs(
+    client: &Client,
+    rigs: &[XvbRigConfig],
+    node: &XvbNode,
+    endpoints: &LocalEndpoints,
+    address: &str,
+) -> Vec<(String, anyhow::Error)> {
+    let handles = rigs
+        .iter()
+        .map(|rig| {
+            let (client, rig, node, address) = (
+                client.clone(),
+                rig.clone(),
+                node.clone(),
+                address.to_string(),
+            );
+            let pool = rig.pool(&node, endpoints);
+            tokio::spawn(async move {
+                // without a pool it can reach, the rig is left where it is.
+                let pool = pool.map_err(|e| {
+                    anyhow!(
+                        "the address of P2Pool could not be found for the rig, set it in its settings: {}",
+                        e
+                    )
+                })?;
+                update_xmrig_config(
+                    &client,
+                    &rig.api_url(true),
+                    &rig.token,
+                    &node,
+                    &pool,
+                    &address,
+                    &rig.name,
+                )
+                .await
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut failed = vec![];
+    for (rig, handle) in rigs.iter().zip(handles) {
+        if let Err(e) = handle.await.unwrap_or_else(|e| Err(e.into())) {
+            failed.push((rig.name.clone(), e));
+        }
+    }
+    failed
+}
@@ -1,15 +1,19 @@
 use std::sync::{Arc, Mutex};
 
 use derive_more::Display;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    macros::lock, XVB_ROUND_DONOR_MEGA_MIN_HR, XVB_ROUND_DONOR_MIN_HR, XVB_ROUND_DONOR_VIP_MIN_HR,
+    disk::history::{XvbDecision, XvbRoundRecord},
+    helper::Helper,
+    macros::lock,
+    XVB_ROUND_DONOR_MEGA_MIN_HR, XVB_ROUND_DONOR_MIN_HR, XVB_ROUND_DONOR_VIP_MIN_HR,
     XVB_ROUND_DONOR_WHALE_MIN_HR, XVB_SIDE_MARGIN_1H,
 };
 
-use super::PubXvbApi;
-#[derive(Debug, Clone, Default, Display, Deserialize, PartialEq)]
+use super::{priv_stats::XvbPrivStats, public_stats::XvbPubStats, PubXvbApi};
+// Variants are ordered from the lowest to the highest round.
+#[derive(Debug, Clone, Default, Display, Deserialize, Serialize, PartialEq, Eq, PartialOrd)]
 pub enum XvbRound {
     #[default]
     #[display(fmt = "VIP")]
@@ -28,12 +32,31 @@ pub enum XvbRound {
     DonorMega,
 }
 
+impl XvbRound {
+    // Every round, from the lowest to the highest.
+    pub const ALL: [XvbRound; 5] = [
+        XvbRound::Vip,
+        XvbRound::Donor,
+        XvbRound::DonorVip,
+        XvbRound::DonorWhale,
+        XvbRound::DonorMega,
+    ];
+}
+
 pub(crate) fn round_type(share: u32, pub_api: &Arc<Mutex<PubXvbApi>>) -> Option<XvbRound> {
+    let stats_priv = &lock!(pub_api).stats_priv;
+    round_from_avg(share, stats_priv.donor_1hr_avg, stats_priv.donor_24hr_avg)
+}
+// Round for the donated averages in kH/s, as given by the XvB API.
+pub(crate) fn round_from_avg(
+    share: u32,
+    donor_1hr_avg: f32,
+    donor_24hr_avg: f32,
+) -> Option<XvbRound> {
     if share > 0 {
-        let stats_priv = &lock!(pub_api).stats_priv;
         match (
-            ((stats_priv.donor_1hr_avg * 1000.0) * XVB_SIDE_MARGIN_1H) as u32,
-            (stats_priv.donor_24hr_avg * 1000.0) as u32,
+            ((donor_1hr_avg * 1000.0) * XVB_SIDE_MARGIN_1H) as u32,
+            (donor_24hr_avg * 1000.0) as u32,
         ) {
             x if x.0 >= XVB_ROUND_DONOR_MEGA_MIN_HR && x.1 >= XVB_ROUND_DONOR_MEGA_MIN_HR => {
                 Some(XvbRound::DonorMega)
@@ -53,3 +76,71 @@ pub(crate) fn round_type(share: u32, pub_api: &Arc<Mutex<PubXvbApi>>) -> Option<
         None
     }
 }
+// Number of rounds of each type, [None] first, only the types present.
+pub(crate) fn count_rounds<'a>(
+    rounds: impl Iterator<Item = &'a Option<XvbRound>> + Clone,
+) -> Vec<(Option<XvbRound>, usize)> {
+    std::iter::once(None)
+        .chain(XvbRound::ALL.into_iter().map(Some))
+        .filter_map(|round| {
+            let count = rounds.clone().filter(|r| **r == round).count();
+            (count > 0).then_some((round, count))
+        })
+        .collect()
+}
+
+// Follows the draws of the public stats to log every raffle round ended while XvB is running.
+// A round ends when the winner and rolls of the last draw change.
+#[derive(Debug, Default)]
+pub(crate) struct RoundTracker {
+    draw: Option<(String, u64, u64)>, // winner, roll_winner and roll_round of the last draw seen
+    start: u64,                       // first time the current round was seen
+    round: Option<XvbRound>,          // last round qualified for during the current round
+    donor_1h: f32,
+    donor_24h: f32,
+}
+
+impl RoundTracker {
+    // Called after each refresh of the stats, returns the record of the round that just ended.
+    // The round seen first is only partially followed, its donated time starts when it was seen.
+    pub(crate) fn update(
+        &mut self,
+        now: u64,
+        stats_pub: &XvbPubStats,
+        stats_priv: &XvbPrivStats,
+        address: &str,
+        decisions: &[XvbDecision],
+    ) -> Option<XvbRoundRecord> {
+        let draw = (
+            stats_pub.winner.clone(),
+            stats_pub.roll_winner,
+            stats_pub.roll_round,
+        );
+        let mut record = None;
+        if self.draw.as_ref() != Some(&draw) {
+            if self.draw.is_some() {
+                record = Some(XvbRoundRecord {
+                    time: now,
+                    round: self.round.clone(),
+                    donor_1h: self.donor_1h,
+                    donor_24h: self.donor_24h,
+                    donated: decisions
+                        .iter()
+                        .filter(|d| d.time >= self.start && d.time < now)
+                        .map(|d| d.donated)
+                        .sum(),
+                    won: draw.0 == Helper::head_tail_of_monero_address(address),
+                    winner: draw.0.clone(),
+                    roll_winner: draw.1,
+                    roll_round: draw.2,
+                });
+            }
+            self.draw = Some(draw);
+            self.start = now;
+        }
+        self.round.clone_from(&stats_priv.round_participate);
+        self.donor_1h = stats_priv.donor_1hr_avg;
+        self.donor_24h = stats_priv.donor_24hr_avg;
+        record
+    }
+}
@@ -0,0 +1,219 @@
+// Replay of the XvB decisions on a timeline of ten minutes cycles, without mining.
+// Used by the [xvb-simulate] command to compare the modes before using them.
+
+use anyhow::{anyhow, Result};
+use bounded_vec_deque::BoundedVecDeque;
+use serde::{Deserialize, Serialize};
+
+use crate::{disk::state::Xvb, XVB_TIME_ALGO};
+
+use super::{
+    rounds::{count_rounds, round_from_avg, XvbRound},
+    strategy::{decide, pplns_window_cycles, share_probability, XvbMode, XvbSnapshot},
+    SamplesAverageHour,
+};
+
+// One cycle of the timeline. Hashrates are in H/s, donor averages in kH/s like the XvB API.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimelineCycle {
+    pub hashrate: f32,          // local HR controllable by the algorithm
+    pub difficulty: u64,        // p2pool sidechain difficulty
+    pub shares: u32,            // shares of the address in the PPLNS window
+    pub donor_1h: Option<f32>,  // recorded XvB average of the last hour, simulated if missing
+    pub donor_24h: Option<f32>, // recorded XvB average of the last 24 hours, simulated if missing
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimulatedCycle {
+    pub input: TimelineCycle,
+    pub p2pool: u32, // seconds mined on P2Pool
+    pub xvb: u32,    // seconds mined on XvB
+    pub donor_1h: f32,
+    pub donor_24h: f32,
+    pub round: Option<XvbRound>, // round of the donor averages at the end of the cycle
+    pub share_probability: f32, // probability to have a share in the PPLNS window at the end of the cycle
+    pub messages: Vec<String>,  // what the algorithm would have written in the console
+}
+
+// Timeline recorded as one JSON object per line, like the history files.
+// Unlike the history, an invalid line is an error, the result would be meaningless without it.
+pub fn parse_timeline(string: &str) -> Result<Vec<TimelineCycle>> {
+    string
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| serde_json::from_str(l).map_err(|e| anyhow!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+pub fn synthetic_timeline(
+    hashrate: f32,
+    difficulty: u64,
+    shares: u32,
+    cycles: usize,
+) -> Vec<TimelineCycle> {
+    vec![
+        TimelineCycle {
+            hashrate,
+            difficulty,
+            shares,
+            donor_1h: None,
+            donor_24h: None,
+        };
+        cycles
+    ]
+}
+
+pub fn simulate(
+    timeline: &[TimelineCycle],
+    mode: XvbMode,
+    config: &Xvb,
+    mini: bool,
+) -> Vec<SimulatedCycle> {
+    let mut p2pool_sent = SamplesAverageHour::default();
+    let mut xvb_sent = SamplesAverageHour::default();
+    let capacity_24h = (24 * 3600 / XVB_TIME_ALGO) as usize;
+    let mut xvb_sent_24h = BoundedVecDeque::from_iter(vec![0.0f32; capacity_24h], capacity_24h);
+    // before the timeline, the whole HR is considered to be mining on P2Pool.
+    let capacity_window = pplns_window_cycles(mini);
+    let first_hr = timeline.first().map_or(0.0, |c| c.hashrate);
+    let mut p2pool_window =
+        BoundedVecDeque::from_iter(vec![first_hr; capacity_window], capacity_window);
+    let (mut donor_1h, mut donor_24h) = (0.0, 0.0);
+    let mut simulated = Vec::with_capacity(timeline.len());
+    for cycle in timeline {
+        // recorded averages replace the simulated ones.
+        donor_1h = cycle.donor_1h.unwrap_or(donor_1h);
+        donor_24h = cycle.donor_24h.unwrap_or(donor_24h);
+        let p2pool_avg = average(p2pool_sent.0.iter().copied());
+        // without a share, everything stays on P2Pool like the algorithm does.
+        let (xvb, messages) = if cycle.shares > 0 {
+            let snapshot = XvbSnapshot {
+                lhr: cycle.hashrate,
+                difficulty: cycle.difficulty,
+                mini,
+                // only this instance is simulated, so the estimate is what it sent.
+                sidechain_ehr: p2pool_avg,
+                stratum_hr: p2pool_avg,
+                p2pool_sent: p2pool_avg,
+                xvb_sent: average(xvb_sent.0.iter().copied()),
+                xvb_1h: donor_1h * 1000.0,
+                mode,
+                config: config.clone(),
+            };
+            let allocation = decide(&snapshot);
+            (allocation.xvb, allocation.messages)
+        } else {
+            (
+                0,
+                vec!["No share in the current PPLNS Window !".to_string()],
+            )
+        };
+        let xvb_hr = cycle.hashrate * (xvb as f32 / XVB_TIME_ALGO as f32);
+        let p2pool_hr = cycle.hashrate - xvb_hr;
+        p2pool_sent.0.push_back(p2pool_hr);
+        xvb_sent.0.push_back(xvb_hr);
+        xvb_sent_24h.push_back(xvb_hr);
+        p2pool_window.push_back(p2pool_hr);
+        if cycle.donor_1h.is_none() {
+            donor_1h = average(xvb_sent.0.iter().copied()) / 1000.0;
+        }
+        if cycle.donor_24h.is_none() {
+            donor_24h = average(xvb_sent_24h.iter().copied()) / 1000.0;
+        }
+        simulated.push(SimulatedCycle {
+            input: cycle.clone(),
+            p2pool: XVB_TIME_ALGO - xvb,
+            xvb,
+            donor_1h,
+            donor_24h,
+            round: round_from_avg(cycle.shares, donor_1h, donor_24h),
+            share_probability: share_probability(
+                average(p2pool_window.iter().copied()),
+                cycle.difficulty,
+                mini,
+            ),
+            messages,
+        });
+    }
+    simulated
+}
+
+fn average(samples: impl ExactSizeIterator<Item = f32>) -> f32 {
+    let len = samples.len();
+    if len == 0 {
+        return 0.0;
+    }
+    samples.sum::<f32>() / len as f32
+}
+
+// Table of the cycles followed by a summary, as printed by [xvb-simulate].
+pub fn report(simulated: &[SimulatedCycle], verbose: bool) -> String {
+    let mut out = format!(
+        "{:>5} {:>10} {:>12} {:>6} {:>8} {:>8} {:>12} {:>12} {:<11} {:>7}\n",
+        "cycle",
+        "HR kH/s",
+        "difficulty",
+        "shares",
+        "P2Pool s",
+        "XvB s",
+        "donor 1h",
+        "donor 24h",
+        "round",
+        "share %"
+    );
+    for (i, cycle) in simulated.iter().enumerate() {
+        let round = cycle
+            .round
+            .as_ref()
+            .map_or("None".to_string(), |r| r.to_string());
+        out.push_str(&format!(
+            "{:>5} {:>10.3} {:>12} {:>6} {:>8} {:>8} {:>12.3} {:>12.3} {:<11} {:>7.1}\n",
+            i + 1,
+            cycle.input.hashrate / 1000.0,
+            cycle.input.difficulty,
+            cycle.input.shares,
+            cycle.p2pool,
+            cycle.xvb,
+            cycle.donor_1h,
+            cycle.donor_24h,
+            round,
+            cycle.share_probability * 100.0
+        ));
+        if verbose {
+            for msg in &cycle.messages {
+                out.push_str(&format!("      | {}\n", msg));
+            }
+        }
+    }
+    let Some(last) = simulated.last() else {
+        out.push_str("\nEmpty timeline, nothing to simulate.\n");
+        return out;
+    };
+    let xvb: u32 = simulated.iter().map(|c| c.xvb).sum();
+    let total = simulated.len() as u32 * XVB_TIME_ALGO;
+    out.push_str(&format!(
+        "\n{} cycles, {} seconds on XvB ({:.1}%), last donor averages: 1h {:.3} kH/s, 24h {:.3} kH/s\n",
+        simulated.len(),
+        xvb,
+        xvb as f32 * 100.0 / total as f32,
+        last.donor_1h,
+        last.donor_24h
+    ));
+    let rounds = count_rounds(simulated.iter().map(|c| &c.round))
+        .into_iter()
+        .map(|(round, count)| {
+            let name = round.map_or("None".to_string(), |r| r.to_string());
+            format!("{} {}", name, count)
+        })
+        .collect::<Vec<_>>();
+    out.push_str(&format!("Rounds: {}\n", rounds.join(", ")));
+    let probabilities = simulated.iter().map(|c| c.share_probability);
+    out.push_str(&format!(
+        "Share in the PPLNS window: {:.1}% on average, {:.1}% at worst\n",
+        average(probabilities.clone()) * 100.0,
+        probabilities.fold(1.0f32, f32::min) * 100.0
+    ));
+    out
+}
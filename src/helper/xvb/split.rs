@@ -0,0 +1,341 @@
+// Thread split mode of XvB: instead of sending the whole hashrate to one side for a part of the cycle,
+// a second XMRig mines on XvB with a part of the threads for the whole cycle while XMRig keeps the others on P2Pool.
+// The part of the threads comes from the time the algorithm would have donated in the cycle.
+
+use std::{
+    io::Read,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize};
+use reqwest::Client;
+
+use crate::{
+    helper::xrig::{
+        update_xmrig_threads,
+        xmrig::{ImgXmrig, PrivXmrigApi},
+    },
+    macros::lock,
+    XMRIG_API_SUMMARY_URI, XVB_SPLIT_API, XVB_SPLIT_SETTLE, XVB_TIME_ALGO,
+};
+
+use super::{endpoints::LocalEndpoints, nodes::XvbNode};
+
+// Threads mining on each side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThreadSplit {
+    pub p2pool: u16,
+    pub xvb: u16,
+}
+impl ThreadSplit {
+    // The threads given to XvB are rounded down so the share in the PPLNS window is not put at risk.
+    // P2Pool always keeps a thread, XMRig can not run without one.
+    pub fn new(threads: u16, time_donated: u32) -> Option<Self> {
+        if threads < 2 {
+            return None;
+        }
+        let xvb = (threads as u32 * time_donated.min(XVB_TIME_ALGO) / XVB_TIME_ALGO) as u16;
+        let xvb = xvb.min(threads - 1);
+        Some(Self {
+            p2pool: threads - xvb,
+            xvb,
+        })
+    }
+    pub fn threads(&self) -> u16 {
+        self.p2pool + self.xvb
+    }
+    // Time of a cycle that would send the same hashrate to XvB.
+    pub fn time_donated(&self) -> u32 {
+        XVB_TIME_ALGO * self.xvb as u32 / self.threads() as u32
+    }
+}
+
+// Split in place, shown in the Status tab.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitStatus {
+    pub split: ThreadSplit,
+    pub node: XvbNode,
+    pub hashrate: f32, // H/s of the second XMRig, from its last summary
+}
+
+// The second XMRig, owned by [Helper] so it lives as long as Gupaxx.
+pub struct SplitXmrig {
+    process: Option<SplitProcess>,
+    status: Option<SplitStatus>,
+    exited: bool,
+    token: String, // access token of the HTTP API of the second XMRig
+    // last time the threads moved, until the hashrates show it.
+    changed: Option<Instant>,
+}
+struct SplitProcess {
+    args: Vec<String>,
+    child: Box<dyn Child + Send + Sync>,
+    // like the other processes, the child gets a hangup if Gupaxx quits without stopping it.
+    _master: Box<dyn MasterPty + Send>,
+}
+impl Default for SplitXmrig {
+    fn default() -> Self {
+        Self {
+            process: None,
+            status: None,
+            exited: false,
+            token: format!("{:016x}", rand::random::<u64>()),
+            changed: None,
+        }
+    }
+}
+impl std::fmt::Debug for SplitXmrig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("SplitXmrig")
+            .field("running", &self.process.is_some())
+            .field("status", &self.status)
+            .finish()
+    }
+}
+impl Drop for SplitXmrig {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+impl SplitXmrig {
+    // Arguments of the second XMRig, its HTTP API is only reachable locally.
+    pub fn args(
+        threads: u16,
+        node: &XvbNode,
+        endpoints: &LocalEndpoints,
+        address: &str,
+        rig: &str,
+        token: &str,
+    ) -> Vec<String> {
+        let (host, port) = XVB_SPLIT_API.rsplit_once(':').unwrap_or_default();
+        let mut args = vec![
+            "--url".to_string(),
+            node.pool(endpoints),
+            "--user".to_string(),
+            node.user(address),
+            "--threads".to_string(),
+            threads.to_string(),
+            "--no-color".to_string(),
+            "--http-host".to_string(),
+            host.to_string(),
+            "--http-port".to_string(),
+            port.to_string(),
+            format!("--http-access-token={}", token),
+        ];
+        if !rig.is_empty() {
+            args.push("--rig-id".to_string());
+            args.push(rig.to_string());
+        }
+        if node.tls() {
+            args.push("--tls".to_string());
+        }
+        if node.keepalive() {
+            args.push("--keepalive".to_string());
+        }
+        args
+    }
+    // Mine on [node] with the XvB threads of [split], the running XMRig is only restarted if something changed.
+    pub fn start(
+        &mut self,
+        path: &Path,
+        split: ThreadSplit,
+        node: &XvbNode,
+        endpoints: &LocalEndpoints,
+        address: &str,
+        rig: &str,
+    ) -> Result<()> {
+        let args = Self::args(split.xvb, node, endpoints, address, rig, &self.token);
+        let hashrate = self.status.as_ref().map_or(0.0, |s| s.hashrate);
+        if self.is_running() && self.process.as_ref().is_some_and(|p| p.args == args) {
+            self.status = Some(SplitStatus {
+                split,
+                node: node.clone(),
+                hashrate,
+            });
+            return Ok(());
+        }
+        self.stop();
+        let pair = portable_pty::native_pty_system().openpty(PtySize {
+            rows: 100,
+            cols: 1000,
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+        let mut cmd = CommandBuilder::new(path);
+        cmd.args(&args);
+        if let Some(dir) = path.parent() {
+            cmd.cwd(dir);
+        }
+        let child = pair.slave.spawn_command(cmd)?;
+        drop(pair.slave);
+        // the output is not shown, but must be read so XMRig does not block on a full PTY.
+        let mut reader = pair.master.try_clone_reader()?;
+        thread::spawn(move || {
+            let mut buf = [0; 1024];
+            while reader.read(&mut buf).is_ok_and(|n| n > 0) {}
+        });
+        info!(
+            "XvB | thread split: second XMRig mining on {} with {} threads",
+            node, split.xvb
+        );
+        self.process = Some(SplitProcess {
+            args,
+            child,
+            _master: pair.master,
+        });
+        self.status = Some(SplitStatus {
+            split,
+            node: node.clone(),
+            hashrate: 0.0,
+        });
+        // an exit noticed until now is replaced by this start.
+        self.exited = false;
+        self.changed = Some(Instant::now());
+        Ok(())
+    }
+    // Returns true if the second XMRig was running.
+    pub fn stop(&mut self) -> bool {
+        self.status = None;
+        let Some(mut process) = self.process.take() else {
+            return false;
+        };
+        if let Err(e) = process.child.kill() {
+            warn!("XvB | could not kill the second XMRig: {}", e);
+        }
+        let _ = process.child.wait();
+        self.changed = Some(Instant::now());
+        true
+    }
+    pub fn is_running(&mut self) -> bool {
+        let Some(process) = &mut self.process else {
+            return false;
+        };
+        if matches!(process.child.try_wait(), Ok(None)) {
+            return true;
+        }
+        warn!("XvB | the second XMRig of the thread split exited");
+        self.process = None;
+        self.status = None;
+        self.exited = true;
+        false
+    }
+    // True once if the second XMRig exited without being stopped.
+    pub fn take_exited(&mut self) -> bool {
+        self.is_running();
+        std::mem::take(&mut self.exited)
+    }
+    pub fn status(&mut self) -> Option<SplitStatus> {
+        self.is_running();
+        self.status.clone()
+    }
+    // Hashrate of the second XMRig, [None] while the threads that moved do not show in the hashrates yet:
+    // the second XMRig has not reported since its start, or XMRig did not have the time to use the threads given back.
+    pub fn settled_hashrate(&mut self) -> Option<f32> {
+        let hashrate = self.status().map_or(0.0, |s| s.hashrate);
+        let settling = self.changed.is_some_and(|changed| {
+            if self.process.is_some() {
+                hashrate == 0.0
+            } else {
+                changed.elapsed() < Duration::from_secs(XVB_SPLIT_SETTLE)
+            }
+        });
+        if settling {
+            return None;
+        }
+        self.changed = None;
+        Some(hashrate)
+    }
+}
+
+// Refresh the hashrate of the second XMRig, it is part of the hashrate controlled by the algorithm.
+pub async fn update_split_hashrate(client: &Client, split_xmrig: &Arc<Mutex<SplitXmrig>>) {
+    let token = {
+        let mut split_xmrig = lock!(split_xmrig);
+        if !split_xmrig.is_running() {
+            return;
+        }
+        split_xmrig.token.clone()
+    };
+    let url = format!("http://{}/{}", XVB_SPLIT_API, XMRIG_API_SUMMARY_URI);
+    match PrivXmrigApi::request_xmrig_api(client, &url, &token).await {
+        Ok(summary) => {
+            if let Some(status) = &mut lock!(split_xmrig).status {
+                status.hashrate = summary.hashrate();
+            }
+        }
+        // XMRig needs a few seconds before its API answers, it is asked again meanwhile.
+        Err(e) => debug!(
+            "XvB | could not get the hashrate of the second XMRig: {}",
+            e
+        ),
+    }
+}
+
+// XMRig keeps the P2Pool threads of [split] and the second XMRig mines on [node] with the others.
+// Returns the split in place, all the threads stay on P2Pool if there is nothing to give or no XvB node.
+#[allow(clippy::too_many_arguments)]
+pub async fn apply_thread_split(
+    client: &Client,
+    split_xmrig: &Arc<Mutex<SplitXmrig>>,
+    img_xmrig: &Arc<Mutex<ImgXmrig>>,
+    endpoints: &LocalEndpoints,
+    api_uri: &str,
+    token: &str,
+    address: &str,
+    rig: &str,
+    split: ThreadSplit,
+    node: &XvbNode,
+) -> Result<ThreadSplit> {
+    let threads = lock!(img_xmrig).threads.clone();
+    if split.xvb == 0 || node == &XvbNode::P2pool {
+        stop_thread_split(client, split_xmrig, api_uri, token, &threads).await?;
+        return Ok(ThreadSplit {
+            p2pool: split.threads(),
+            xvb: 0,
+        });
+    }
+    // XMRig restarts its threads when they change, so only if needed.
+    let current = lock!(split_xmrig).status().map(|s| s.split.p2pool);
+    if current != Some(split.p2pool) {
+        update_xmrig_threads(client, api_uri, token, split.p2pool).await?;
+    }
+    let path = lock!(img_xmrig).path.clone();
+    let started = lock!(split_xmrig).start(&path, split, node, endpoints, address, rig);
+    if let Err(e) = started {
+        restore_threads(client, api_uri, token, &threads).await?;
+        return Err(e);
+    }
+    Ok(split)
+}
+
+// Stop the second XMRig if it runs and give its threads back to XMRig.
+pub async fn stop_thread_split(
+    client: &Client,
+    split_xmrig: &Arc<Mutex<SplitXmrig>>,
+    api_uri: &str,
+    token: &str,
+    threads: &str,
+) -> Result<()> {
+    if !lock!(split_xmrig).stop() {
+        return Ok(());
+    }
+    restore_threads(client, api_uri, token, threads).await
+}
+
+// Give all the threads back to XMRig after the second XMRig stopped.
+pub async fn restore_threads(
+    client: &Client,
+    api_uri: &str,
+    token: &str,
+    threads: &str,
+) -> Result<()> {
+    let threads = threads
+        .parse()
+        .map_err(|_| anyhow!("unknown number of threads of XMRig: {}", threads))?;
+    update_xmrig_threads(client, api_uri, token, threads).await
+}
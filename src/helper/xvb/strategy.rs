@@ -0,0 +1,389 @@
+// Decision step of the XvB algorithm: how the next cycle is split between P2Pool and XvB.
+// A strategy only sees a snapshot of the stats and settings, so it can be tested without any process running.
+// The switching of XMRig and the requests are done by [algorithm].
+
+use std::fmt::Display;
+
+use log::{info, warn};
+use readable::num::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    disk::state::Xvb, helper::xvb::rounds::XvbRound, BLOCK_PPLNS_WINDOW_MAIN,
+    BLOCK_PPLNS_WINDOW_MINI, SECOND_PER_BLOCK_P2POOL, XVB_BUFFER, XVB_ROUND_DONOR_MEGA_MIN_HR,
+    XVB_ROUND_DONOR_MIN_HR, XVB_ROUND_DONOR_VIP_MIN_HR, XVB_ROUND_DONOR_WHALE_MIN_HR,
+    XVB_STRATUM_DIVERGENCE, XVB_TIME_ALGO,
+};
+
+//---------------------------------------------------------------------------------------------------- [XvbMode]
+// Strategy selected in the XvB tab.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Deserialize, Serialize, clap::ValueEnum)]
+pub enum XvbMode {
+    #[default]
+    Default,
+    Hero,
+    Probabilistic,
+}
+
+impl XvbMode {
+    pub fn strategy(&self, config: &Xvb) -> Box<dyn XvbStrategy> {
+        match self {
+            Self::Default => Box::new(DefaultStrategy),
+            Self::Hero => Box::new(HeroStrategy),
+            Self::Probabilistic => Box::new(ProbabilisticStrategy {
+                confidence: f32::from(config.share_confidence) / 100.0,
+            }),
+        }
+    }
+}
+
+impl Display for XvbMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Snapshot/Allocation
+// Everything a strategy can decide from, taken at the start of a cycle.
+// Hashrates are in H/s.
+#[derive(Clone, Debug, Default)]
+pub struct XvbSnapshot {
+    pub lhr: f32,           // local HR controllable by the algorithm (XMRig or XMRig-Proxy)
+    pub difficulty: u64,    // p2pool sidechain difficulty
+    pub mini: bool,         // p2pool mini or main, for the size of the PPLNS window
+    pub sidechain_ehr: f32, // estimated HR sent the last hour for the address on p2pool
+    pub stratum_hr: f32,    // last hour HR of the miners connected to the p2pool stratum
+    pub p2pool_sent: f32,   // last hour average of the local HR sent to p2pool
+    pub xvb_sent: f32,      // last hour average of the local HR sent to XvB
+    pub xvb_1h: f32,        // last hour average HR donated to XvB for the address
+    pub mode: XvbMode,      // mode at runtime, can be changed between two decisions
+    pub config: Xvb,
+}
+
+// Seconds of the cycle given to each side, and what to show in the console about the decision.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Allocation {
+    pub p2pool: u32,
+    pub xvb: u32,
+    pub share_probability: f32, // probability to keep a share in the PPLNS window with this split
+    pub messages: Vec<String>,
+}
+
+impl Allocation {
+    // [ohr] is the outside HR the decision was taken with.
+    fn new(snapshot: &XvbSnapshot, ohr: f32, xvb: u32, mut messages: Vec<String>) -> Self {
+        let xvb = xvb.min(XVB_TIME_ALGO);
+        let p2pool = XVB_TIME_ALGO - xvb;
+        let p2pool_hr = ohr + snapshot.lhr * (p2pool as f32 / XVB_TIME_ALGO as f32);
+        let share_probability = share_probability(p2pool_hr, snapshot.difficulty, snapshot.mini);
+        messages.push(format!(
+            "{}% probability to keep a share in the PPLNS window with {} kH/s on P2Pool",
+            Float::from_1((share_probability * 100.0).into()),
+            Float::from_3((p2pool_hr / 1000.0).into())
+        ));
+        Self {
+            p2pool,
+            xvb,
+            share_probability,
+            messages,
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Strategies
+pub trait XvbStrategy {
+    fn decide(&self, snapshot: &XvbSnapshot) -> Allocation;
+}
+
+// The fixed split override replaces any strategy.
+pub fn decide(snapshot: &XvbSnapshot) -> Allocation {
+    if snapshot.config.manual_split {
+        let time = snapshot.config.split_unit.seconds(snapshot.config.split);
+        let mut messages = vec![];
+        log_override(
+            &mut messages,
+            format!("Override: fixed split of {time} seconds on XvB, estimates are not used"),
+        );
+        // only to show the probability to keep a share.
+        let ohr = outside_hashrate(snapshot, &mut messages);
+        return Allocation::new(snapshot, ohr, time, messages);
+    }
+    snapshot.mode.strategy(&snapshot.config).decide(snapshot)
+}
+
+// Donate the time needed for the highest round reachable, keeping a share in the PPLNS window.
+pub struct DefaultStrategy;
+
+impl XvbStrategy for DefaultStrategy {
+    fn decide(&self, snapshot: &XvbSnapshot) -> Allocation {
+        let mut messages = vec![];
+        let ohr = outside_hashrate(snapshot, &mut messages);
+        let min_hr = minimum_hashrate_share(snapshot.difficulty, snapshot.mini, ohr);
+        let spared_time = spared_time(snapshot, min_hr, &mut messages);
+        let time = highest_round_time(snapshot, spared_time, &mut messages);
+        Allocation::new(snapshot, ohr, time, messages)
+    }
+}
+
+// Donate all the time that can be spared while keeping a share in the PPLNS window.
+// A round cap still limits it.
+pub struct HeroStrategy;
+
+impl XvbStrategy for HeroStrategy {
+    fn decide(&self, snapshot: &XvbSnapshot) -> Allocation {
+        let mut messages = vec![];
+        let ohr = outside_hashrate(snapshot, &mut messages);
+        let min_hr = minimum_hashrate_share(snapshot.difficulty, snapshot.mini, ohr);
+        let spared_time = spared_time(snapshot, min_hr, &mut messages);
+        let time = if snapshot.config.manual_round {
+            highest_round_time(snapshot, spared_time, &mut messages)
+        } else {
+            spared_time
+        };
+        messages.push("Hero mode is enabled for this decision".to_string());
+        Allocation::new(snapshot, ohr, time, messages)
+    }
+}
+
+// Like the default strategy, but the minimum HR keeps a share in the PPLNS window with the [confidence] probability
+// instead of padding the average requirement with [XVB_BUFFER].
+pub struct ProbabilisticStrategy {
+    pub confidence: f32,
+}
+
+impl XvbStrategy for ProbabilisticStrategy {
+    fn decide(&self, snapshot: &XvbSnapshot) -> Allocation {
+        let mut messages = vec![];
+        let ohr = outside_hashrate(snapshot, &mut messages);
+        let min_hr =
+            minimum_hashrate_confidence(snapshot.difficulty, snapshot.mini, ohr, self.confidence);
+        let spared_time = spared_time(snapshot, min_hr, &mut messages);
+        let time = highest_round_time(snapshot, spared_time, &mut messages);
+        messages.push(format!(
+            "Probabilistic mode is enabled for this decision, a share is kept in the PPLNS window with {}% probability",
+            Float::from_0((self.confidence * 100.0).into())
+        ));
+        Allocation::new(snapshot, ohr, time, messages)
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Steps
+// HR mining to the address outside of this instance.
+fn outside_hashrate(snapshot: &XvbSnapshot, messages: &mut Vec<String>) -> f32 {
+    if snapshot.config.manual_outside_hr {
+        let ohr = snapshot.config.outside_hr as f32;
+        log_override(
+            messages,
+            format!(
+                "Override: fixed outside HR of {} kH/s used instead of the estimate",
+                Float::from_3((ohr / 1000.0).into())
+            ),
+        );
+        return ohr;
+    }
+    // In stratum mode, the exact last hour HR of the miners pointed to this P2Pool is used instead of the estimate.
+    let (baseline, source) = if snapshot.config.stratum_hashrate {
+        if let Some(msg) = divergence_warning(snapshot.stratum_hr, snapshot.sidechain_ehr) {
+            warn!("XvB Process | {}", msg);
+            messages.push(msg);
+        }
+        (snapshot.stratum_hr, "p2pool stratum HR")
+    } else {
+        (snapshot.sidechain_ehr, "p2pool sidechain HR")
+    };
+    // what if ehr stay still for the next ten minutes ? mHR will augment every ten minutes because it thinks that oHR is decreasing.
+    //
+    let avg_hr = snapshot.p2pool_sent;
+    let mut p2pool_ohr = baseline - avg_hr;
+    if p2pool_ohr < 0.0 {
+        p2pool_ohr = 0.0;
+    }
+    info!("XvB Process | {source} - last hour average HR = estimated outside HR\n{baseline} - {avg_hr} = {p2pool_ohr}");
+    p2pool_ohr
+}
+// Relative difference between the stratum HR and the sidechain estimate, if above [XVB_STRATUM_DIVERGENCE].
+// Without any share in the window, the estimate is 0 and can not be compared.
+pub(crate) fn hashrate_divergence(stratum_hr: f32, ehr: f32) -> Option<f32> {
+    if ehr <= 0.0 {
+        return None;
+    }
+    let divergence = (stratum_hr - ehr).abs() / stratum_hr.max(ehr);
+    (divergence > XVB_STRATUM_DIVERGENCE).then_some(divergence)
+}
+fn divergence_warning(stratum_hr: f32, ehr: f32) -> Option<String> {
+    let divergence = hashrate_divergence(stratum_hr, ehr)?;
+    let hint = if ehr > stratum_hr {
+        "a rig mining to your address may not be pointed at this p2pool"
+    } else {
+        "the estimate can lag behind a change of hashrate or be off by luck"
+    };
+    Some(format!(
+        "Warning: p2pool stratum HR of {} kH/s and sidechain estimate of {} kH/s differ by {}%, {}",
+        Float::from_3((stratum_hr / 1000.0).into()),
+        Float::from_3((ehr / 1000.0).into()),
+        Float::from_0((divergence * 100.0).into()),
+        hint
+    ))
+}
+fn pplns_window_seconds(mini: bool) -> u64 {
+    let pws = if mini {
+        BLOCK_PPLNS_WINDOW_MINI
+    } else {
+        BLOCK_PPLNS_WINDOW_MAIN
+    };
+    pws * SECOND_PER_BLOCK_P2POOL
+}
+fn minimum_hashrate_share(difficulty: u64, mini: bool, ohr: f32) -> f32 {
+    let window = pplns_window_seconds(mini);
+    let minimum_hr = ((difficulty / window) as f32 * XVB_BUFFER) - ohr;
+    info!("XvB Process | (difficulty / (window pplns blocks * seconds per p2pool block) * BUFFER) - outside HR = minimum HR to keep a share\n({difficulty} / {window} * {XVB_BUFFER}) - {ohr} = {minimum_hr}");
+    minimum_hr
+}
+// Shares are found following a Poisson process, so the probability to find at least one share in the window is
+// 1 - e^(-HR * window / difficulty).
+// The HR needed for a given probability is then -ln(1 - probability) * difficulty / window.
+pub(crate) fn minimum_hashrate_confidence(
+    difficulty: u64,
+    mini: bool,
+    ohr: f32,
+    confidence: f32,
+) -> f32 {
+    let window = pplns_window_seconds(mini);
+    let shares = -(1.0 - confidence.clamp(0.0, 0.9999)).ln();
+    let minimum_hr = (shares * difficulty as f32 / window as f32) - ohr;
+    info!("XvB Process | (-ln(1 - confidence) * difficulty / (window pplns blocks * seconds per p2pool block)) - outside HR = minimum HR to keep a share\n(-ln(1 - {confidence}) * {difficulty} / {window}) - {ohr} = {minimum_hr}");
+    minimum_hr
+}
+// Probability to have at least one share in the PPLNS window when [hr] is mining on P2Pool.
+pub(crate) fn share_probability(hr: f32, difficulty: u64, mini: bool) -> f32 {
+    if difficulty == 0 {
+        return 0.0;
+    }
+    1.0 - (-(hr * pplns_window_seconds(mini) as f32 / difficulty as f32)).exp()
+}
+pub(crate) fn pplns_window_cycles(mini: bool) -> usize {
+    pplns_window_seconds(mini).div_ceil(XVB_TIME_ALGO.into()) as usize
+}
+// Time that can be given to XvB while keeping [min_hr] on P2Pool.
+fn spared_time(snapshot: &XvbSnapshot, min_hr: f32, messages: &mut Vec<String>) -> u32 {
+    let lhr = snapshot.lhr;
+    let mut min_hr = min_hr;
+    if min_hr.is_sign_negative() {
+        info!("XvB Process | if minimum HR is negative, it is 0.");
+        min_hr = 0.0;
+    }
+    info!("Xvb Process | hr {}, min_hr: {} ", lhr, min_hr);
+    // numbers are divided by a thousands to print kH/s and not H/s
+    messages.push(format!(
+        "{} kH/s local HR from Xmrig",
+        Float::from_3((lhr / 1000.0).into())
+    ));
+    messages.push(format!(
+        "{} kH/s minimum required local HR to keep a share in PPLNS window",
+        Float::from_3((min_hr / 1000.0).into())
+    ));
+    messages.push(format!(
+        "{} kH/s estimated sent the last hour for your address on p2pool, including this instance",
+        Float::from_3((snapshot.sidechain_ehr / 1000.0).into())
+    ));
+    if snapshot.config.stratum_hashrate && !snapshot.config.manual_outside_hr {
+        messages.push(format!(
+            "{} kH/s sent the last hour by the miners connected to the p2pool stratum, including this instance",
+            Float::from_3((snapshot.stratum_hr / 1000.0).into())
+        ));
+    }
+    time_that_could_be_spared(lhr, min_hr)
+}
+fn time_that_could_be_spared(hr: f32, min_hr: f32) -> u32 {
+    // percent of time minimum
+    let minimum_time_required_on_p2pool = XVB_TIME_ALGO as f32 / (hr / min_hr);
+    info!("XvB Process | Time of algo / local hashrate / minimum hashrate = minimum time required on p2pool\n{XVB_TIME_ALGO} / ({hr} / {min_hr}) = {minimum_time_required_on_p2pool}");
+    let spared_time = XVB_TIME_ALGO as f32 - minimum_time_required_on_p2pool;
+    info!("XvB Process | Time of algo - minimum time required on p2pool = time that can be spared.\n{XVB_TIME_ALGO} - {minimum_time_required_on_p2pool} = {spared_time}");
+    // if less than 6 seconds, XMRig could hardly have the time to mine anything.
+    if spared_time >= 6.0 {
+        return spared_time as u32;
+    }
+    info!(
+        "XvB Process | sparted time is equal or less than 6 seconds, so everything goes to p2pool."
+    );
+    0
+}
+// Time needed out of the spared time to be in the highest round reachable, below the round cap if any.
+fn highest_round_time(snapshot: &XvbSnapshot, spared_time: u32, messages: &mut Vec<String>) -> u32 {
+    if spared_time == 0 {
+        return 0;
+    }
+    info!("current HR on XvB (last hour): {}", snapshot.xvb_1h);
+    let config = &snapshot.config;
+    let cap = config.manual_round.then_some(&config.round_cap);
+    // calculate how much time needed to be spared to be in most round type minimum HR + buffer
+    let (time, capped) = minimum_time_for_highest_accessible_round(
+        spared_time,
+        snapshot.lhr,
+        snapshot.xvb_1h,
+        snapshot.xvb_sent,
+        cap,
+    );
+    if let Some(capped) = capped {
+        log_override(
+            messages,
+            format!(
+                "Override: aiming for {} round at most, {} round is reachable",
+                config.round_cap, capped
+            ),
+        );
+    }
+    time
+}
+// spared time, local hr, current 1h average hr already mining on XvB, 1h average local HR sent on XvB, highest round allowed.
+// Also returns the highest round that was reachable if the cap prevented aiming for it.
+fn minimum_time_for_highest_accessible_round(
+    st: u32,
+    lhr: f32,
+    chr: f32,
+    shr: f32,
+    cap: Option<&XvbRound>,
+) -> (u32, Option<XvbRound>) {
+    // we remove one second that could possibly be sent, because if the time needed is a float, it will be rounded up.
+    // this subtraction can not fail because mnimum spared time is >= 6.
+    let hr_for_xvb = ((st - 1) as f32 / XVB_TIME_ALGO as f32) * lhr;
+    info!(
+        "hr for xvb is: ({st} / {}) * {lhr} = {hr_for_xvb}H/s",
+        XVB_TIME_ALGO
+    );
+    let ohr = chr - shr;
+    info!("ohr is: {chr} - {shr} = {ohr}H/s");
+    let rounds = [
+        (XvbRound::DonorMega, XVB_ROUND_DONOR_MEGA_MIN_HR),
+        (XvbRound::DonorWhale, XVB_ROUND_DONOR_WHALE_MIN_HR),
+        (XvbRound::DonorVip, XVB_ROUND_DONOR_VIP_MIN_HR),
+        (XvbRound::Donor, XVB_ROUND_DONOR_MIN_HR),
+    ];
+    let mut capped = None;
+    for (round, round_min_hr) in rounds {
+        let min = round_min_hr as f32 - ohr;
+        info!("minimum required HR for {round} round is: {round_min_hr} - {ohr} = {min}H/s");
+        if hr_for_xvb <= min {
+            continue;
+        }
+        if cap.is_some_and(|cap| &round > cap) {
+            info!("{round} round is reachable but above the cap");
+            capped.get_or_insert(round);
+            continue;
+        }
+        info!("trying to get {round} round");
+        info!(
+            "minimum second to send = ((({hr_for_xvb} - ({hr_for_xvb} - {min})) / {lhr}) * {}) ",
+            XVB_TIME_ALGO
+        );
+        let time = (((hr_for_xvb - (hr_for_xvb - min)) / lhr) * XVB_TIME_ALGO as f32).ceil() as u32;
+        return (time, capped);
+    }
+    (0, capped)
+}
+// Overrides are shown in the console to know what drove the decision.
+fn log_override(messages: &mut Vec<String>, msg: String) {
+    info!("XvB Process | {}", msg);
+    messages.push(msg);
+}
@@ -0,0 +1,339 @@
+// Local stratum endpoint forwarding the miners to P2Pool or a XvB node.
+// Miners connect once and the algorithm switches the upstream of all of them at once,
+// without the HTTP API of XMRig/XMRig-Proxy and without touching the config of the rigs.
+// Each miner has its own connection to the upstream, logged in with the user of the node.
+// The session id given by the upstream changes with it, so the miner only sees a stable one.
+
+use std::{
+    fmt,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use tokio::{
+    io::{
+        split, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines, ReadHalf,
+        WriteHalf,
+    },
+    net::{TcpListener, TcpStream},
+    sync::watch,
+    time::timeout,
+};
+use tokio_rustls::{
+    rustls::{
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        ClientConfig, DigitallySignedStruct, SignatureScheme,
+    },
+    TlsConnector,
+};
+
+use crate::{macros::lock, XVB_SWITCHER_TIMEOUT};
+
+use super::{endpoints::LocalEndpoints, nodes::XvbNode};
+
+// Where the miners are sent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Upstream {
+    pub node: XvbNode,
+    pub pool: String, // [host:port]
+    pub user: String,
+    pub tls: bool,
+}
+impl Upstream {
+    pub fn new(node: &XvbNode, endpoints: &LocalEndpoints, address: &str) -> Self {
+        Self {
+            node: node.clone(),
+            pool: node.pool(endpoints),
+            user: node.user(address),
+            tls: node.tls(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StratumSwitcher {
+    address: SocketAddr,
+    upstream: Arc<watch::Sender<Upstream>>,
+    miners: Arc<AtomicUsize>,
+    // node a miner could not be switched to, taken by the XvB watchdog.
+    failed: Arc<Mutex<Option<XvbNode>>>,
+}
+impl fmt::Debug for StratumSwitcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StratumSwitcher")
+            .field("address", &self.address)
+            .field("upstream", &*self.upstream.borrow())
+            .field("miners", &self.miners())
+            .finish()
+    }
+}
+impl StratumSwitcher {
+    // Listen for miners on [bind], they are sent to [upstream] until it is switched.
+    // The switcher stops with the runtime it was started on.
+    pub async fn bind(bind: &str, upstream: Upstream) -> Result<Self> {
+        let listener = TcpListener::bind(bind).await?;
+        let switcher = Self {
+            address: listener.local_addr()?,
+            upstream: Arc::new(watch::channel(upstream).0),
+            miners: Arc::new(AtomicUsize::new(0)),
+            failed: Arc::new(Mutex::new(None)),
+        };
+        info!("Stratum switcher | listening on {}", switcher.address);
+        tokio::spawn(switcher.clone().accept(listener));
+        Ok(switcher)
+    }
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+    pub fn upstream(&self) -> Upstream {
+        self.upstream.borrow().clone()
+    }
+    pub fn miners(&self) -> usize {
+        self.miners.load(Ordering::Relaxed)
+    }
+    // Move every connected miner to [upstream], new miners will also use it.
+    pub fn switch(&self, upstream: Upstream) {
+        self.upstream.send_if_modified(|current| {
+            if *current == upstream {
+                return false;
+            }
+            info!(
+                "Stratum switcher | switching {} miners to {}",
+                self.miners(),
+                upstream.pool
+            );
+            *current = upstream;
+            true
+        });
+    }
+    pub fn take_failed(&self) -> Option<XvbNode> {
+        lock!(self.failed).take()
+    }
+    async fn accept(self, listener: TcpListener) {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    let switcher = self.clone();
+                    tokio::spawn(async move {
+                        switcher.miners.fetch_add(1, Ordering::Relaxed);
+                        debug!("Stratum switcher | miner {} connected", peer);
+                        if let Err(e) = switcher.session(stream).await {
+                            info!("Stratum switcher | miner {} disconnected: {}", peer, e);
+                        }
+                        switcher.miners.fetch_sub(1, Ordering::Relaxed);
+                    });
+                }
+                Err(e) => {
+                    warn!("Stratum switcher | failed to accept a miner: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+    async fn session(&self, stream: TcpStream) -> Result<()> {
+        let (reader, mut miner) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let line = lines
+            .next_line()
+            .await?
+            .ok_or_else(|| anyhow!("closed before login"))?;
+        let login: Value = serde_json::from_str(&line)?;
+        if login["method"] != "login" || !login["params"].is_object() {
+            bail!("the first request is not a login");
+        }
+        let session_id = format!("gupaxx-{:016x}", rand::random::<u64>());
+        let mut receiver = self.upstream.subscribe();
+        let upstream = receiver.borrow_and_update().clone();
+        let (mut pool, mut result) = Pool::login(&upstream, &login["params"]).await?;
+        result["id"] = session_id.clone().into();
+        replace_session_id(result.get_mut("job"), &session_id);
+        let response =
+            json!({"id": login["id"], "jsonrpc": "2.0", "error": null, "result": result});
+        send(&mut miner, &response).await?;
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(line) = line? else {
+                        return Ok(());
+                    };
+                    // submits and keepalives carry the session id of the upstream.
+                    let mut request: Value = serde_json::from_str(&line)?;
+                    replace_session_id(request.get_mut("params"), &pool.id);
+                    send(&mut pool.writer, &request).await?;
+                }
+                line = pool.lines.next_line() => {
+                    // the miner will reconnect and get the current upstream.
+                    let line = line?.ok_or_else(|| anyhow!("{} closed the connection", pool.address))?;
+                    let mut message: Value = serde_json::from_str(&line)?;
+                    if message["method"] == "job" {
+                        replace_session_id(message.get_mut("params"), &session_id);
+                    }
+                    send(&mut miner, &message).await?;
+                }
+                changed = receiver.changed() => {
+                    if changed.is_err() {
+                        return Ok(());
+                    }
+                    let upstream = receiver.borrow_and_update().clone();
+                    match Pool::login(&upstream, &login["params"]).await {
+                        Ok((new, mut result)) => {
+                            pool = new;
+                            // the miner drops its job for the one of the new upstream.
+                            let mut job = result["job"].take();
+                            replace_session_id(Some(&mut job), &session_id);
+                            send(&mut miner, &json!({"jsonrpc": "2.0", "method": "job", "params": job})).await?;
+                        }
+                        Err(e) => {
+                            warn!("Stratum switcher | could not switch a miner to {}: {}", upstream.pool, e);
+                            *lock!(self.failed) = Some(upstream.node);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Connection to the upstream on behalf of one miner.
+struct Pool {
+    address: String,
+    id: String, // session id given by the upstream at login
+    lines: Lines<BufReader<ReadHalf<Box<dyn Stream>>>>,
+    writer: WriteHalf<Box<dyn Stream>>,
+}
+impl Pool {
+    // Log in with the login of the miner but the user of the upstream, returns the result of the login.
+    async fn login(upstream: &Upstream, params: &Value) -> Result<(Self, Value)> {
+        timeout(Duration::from_secs(XVB_SWITCHER_TIMEOUT), async {
+            let (reader, writer) = split(connect(upstream).await?);
+            let mut pool = Self {
+                address: upstream.pool.clone(),
+                id: String::new(),
+                lines: BufReader::new(reader).lines(),
+                writer,
+            };
+            let mut params = params.clone();
+            params["login"] = upstream.user.clone().into();
+            let login = json!({"id": 1, "jsonrpc": "2.0", "method": "login", "params": params});
+            send(&mut pool.writer, &login).await?;
+            let line = pool
+                .lines
+                .next_line()
+                .await?
+                .ok_or_else(|| anyhow!("{} closed the connection", upstream.pool))?;
+            let mut response: Value = serde_json::from_str(&line)?;
+            if !response["error"].is_null() {
+                bail!("login refused: {}", response["error"]);
+            }
+            pool.id = response["result"]["id"]
+                .as_str()
+                .ok_or_else(|| anyhow!("no session id in the login of {}", upstream.pool))?
+                .to_string();
+            Ok((pool, response["result"].take()))
+        })
+        .await
+        .map_err(|_| anyhow!("{} did not answer the login", upstream.pool))?
+    }
+}
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+async fn connect(upstream: &Upstream) -> Result<Box<dyn Stream>> {
+    let tcp = TcpStream::connect(&upstream.pool).await?;
+    if !upstream.tls {
+        return Ok(Box::new(tcp));
+    }
+    let host = upstream
+        .pool
+        .rsplit_once(':')
+        .map_or(upstream.pool.as_str(), |(host, _)| host)
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let name = ServerName::try_from(host.to_string())?;
+    Ok(Box::new(tls_connector().connect(name, tcp).await?))
+}
+
+// Like XMRig, the certificate of the node is not verified, only the connection is encrypted.
+fn tls_connector() -> TlsConnector {
+    static CONFIG: Lazy<Arc<ClientConfig>> = Lazy::new(|| {
+        Arc::new(
+            ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(
+                    AnyCertificate(ring::default_provider()),
+                ))
+                .with_no_client_auth(),
+        )
+    });
+    TlsConnector::from(Arc::clone(&CONFIG))
+}
+
+#[derive(Debug)]
+struct AnyCertificate(CryptoProvider);
+impl ServerCertVerifier for AnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+// Jobs and requests of the miner carry the session id in [id] when there is one.
+fn replace_session_id(object: Option<&mut Value>, id: &str) {
+    if let Some(current) = object.and_then(|o| o.get_mut("id")) {
+        *current = id.into();
+    }
+}
+
+async fn send(writer: &mut (impl AsyncWrite + Unpin), message: &Value) -> Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::components::update::Update;
+use crate::helper::args::{override_error, Binary};
 use crate::helper::{Helper, ProcessSignal};
 use crate::utils::constants::{
     APP_MAX_HEIGHT, APP_MAX_WIDTH, APP_MIN_HEIGHT, APP_MIN_WIDTH, BYTES_ICON,
@@ -15,7 +16,7 @@ use egui::TextStyle::Small;
 use egui::TextStyle::{Body, Button, Heading, Monospace, Name};
 use egui::*;
 use env_logger::fmt::style::Style;
-use env_logger::{Builder, WriteStyle};
+use env_logger::{Builder, Target, WriteStyle};
 use log::LevelFilter;
 use std::sync::Arc;
 use std::time::Instant;
@@ -74,7 +75,7 @@ pub fn init_text_styles(ctx: &egui::Context, width: f32, pixels_per_point: f32)
 
 #[cold]
 #[inline(never)]
-pub fn init_logger(now: Instant) {
+pub fn init_logger(now: Instant, target: Target) {
     let filter_env = std::env::var("RUST_LOG").unwrap_or_else(|_| "INFO".to_string());
     let filter = match filter_env.as_str() {
         "error" | "Error" | "ERROR" => LevelFilter::Error,
@@ -84,6 +85,11 @@ pub fn init_logger(now: Instant) {
         _ => LevelFilter::Info,
     };
     std::env::set_var("RUST_LOG", format!("off,gupax={}", filter_env));
+    // No colors in a log file.
+    let write_style = match target {
+        Target::Pipe(_) => WriteStyle::Never,
+        _ => WriteStyle::Always,
+    };
 
     Builder::new()
         .format(move |buf, record| {
@@ -101,7 +107,8 @@ pub fn init_logger(now: Instant) {
             )
         })
         .filter_level(filter)
-        .write_style(WriteStyle::Always)
+        .write_style(write_style)
+        .target(target)
         .parse_default_env()
         .format_timestamp_millis()
         .init();
@@ -152,8 +159,12 @@ pub fn init_auto(app: &mut App) {
         .expect("could not get the current path");
 
     // [Auto-Update]
+    // An update waits for the user to confirm it on the [Gupax] tab,
+    // which does not exist in daemon mode.
     #[cfg(not(feature = "distro"))]
-    if app.state.gupax.auto_update {
+    if app.state.gupax.auto_update && app.daemon {
+        warn!("Skipping auto-update: there is no GUI to confirm it in daemon mode");
+    } else if app.state.gupax.auto_update {
         Update::spawn_thread(
             &app.og,
             &app.state.gupax,
@@ -181,6 +192,15 @@ pub fn init_auto(app: &mut App) {
             warn!("Gupaxx | P2Pool path is not a file! Skipping auto-p2pool...");
         } else if !crate::components::update::check_p2pool_path(&app.state.gupax.p2pool_path) {
             warn!("Gupaxx | P2Pool path is not valid! Skipping auto-p2pool...");
+        } else if let Some(e) = override_error(
+            Binary::P2pool,
+            app.state.p2pool.simple,
+            &app.state.p2pool.arguments,
+        ) {
+            warn!(
+                "Gupaxx | P2Pool arguments are not valid ({})! Skipping auto-p2pool...",
+                e
+            );
         } else {
             let backup_hosts = app.gather_backup_hosts();
             Helper::start_p2pool(
@@ -200,7 +220,18 @@ pub fn init_auto(app: &mut App) {
             warn!("Gupaxx | XMRig path is not an executable! Skipping auto-xmrig...");
         } else if !crate::components::update::check_xmrig_path(&app.state.gupax.xmrig_path) {
             warn!("Gupaxx | XMRig path is not valid! Skipping auto-xmrig...");
-        } else if cfg!(windows) {
+        } else if let Some(e) = override_error(
+            Binary::Xmrig,
+            app.state.xmrig.simple,
+            &app.state.xmrig.arguments,
+        ) {
+            warn!(
+                "Gupaxx | XMRig arguments are not valid ({})! Skipping auto-xmrig...",
+                e
+            );
+        } else if cfg!(windows) || app.daemon {
+            // There is no GUI to ask for the [sudo] password in daemon mode,
+            // so [sudo] must be allowed to start XMRig without one (NOPASSWD).
             Helper::start_xmrig(
                 &app.helper,
                 &app.state.xmrig,
@@ -220,6 +251,15 @@ pub fn init_auto(app: &mut App) {
             warn!("Gupaxx | Xmrig-Proxy path is not a file! Skipping auto-xmrig_proxy...");
         } else if !crate::components::update::check_xp_path(&app.state.gupax.xmrig_proxy_path) {
             warn!("Gupaxx | Xmrig-Proxy path is not valid! Skipping auto-xmrig_proxy...");
+        } else if let Some(e) = override_error(
+            Binary::XmrigProxy,
+            app.state.xmrig_proxy.simple,
+            &app.state.xmrig_proxy.arguments,
+        ) {
+            warn!(
+                "Gupaxx | Xmrig-Proxy arguments are not valid ({})! Skipping auto-xmrig_proxy...",
+                e
+            );
         } else {
             Helper::start_xp(
                 &app.helper,
@@ -43,6 +43,7 @@ use std::time::Instant;
 mod app;
 mod cli;
 mod components;
+mod daemon;
 mod disk;
 mod helper;
 mod inits;
@@ -62,8 +63,13 @@ fn main() {
     crate::panic::set_panic_hook(now);
 
     // Init logger.
-    init_logger(now);
+    init_logger(now, args.log_target());
     let mut app = App::new(now, args);
+
+    // Headless, never returns.
+    if app.daemon {
+        daemon::run(app);
+    }
     init_auto(&mut app);
 
     // Init GUI stuff.
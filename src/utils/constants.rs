@@ -87,12 +87,7 @@ pub const P2POOL_API_PATH_NETWORK: &str = "network/stats";
 #[cfg(target_family = "unix")]
 pub const P2POOL_API_PATH_POOL: &str = "pool/stats";
 pub const XMRIG_API_SUMMARY_URI: &str = "1/summary"; // The default relative URI of XMRig's API summary
-                                                     // pub const XMRIG_API_CONFIG_URI: &str = "1/config"; // The default relative URI of XMRig's API config
-                                                     // todo allow user to change the port of the http api for xmrig and xmrig-proxy
-pub const XMRIG_CONFIG_URL: &str = "http://127.0.0.1:18088/1/config"; // The default relative URI of XMRig's API config
-pub const XMRIG_PROXY_CONFIG_URL: &str = "http://127.0.0.1:18089/1/config"; // The default relative URI of XMRig Proxy's API config
-pub const XMRIG_SUMMARY_URL: &str = "http://127.0.0.1:18088/1/summary"; // The default relative URI of XMRig's API config
-pub const XMRIG_PROXY_SUMMARY_URL: &str = "http://127.0.0.1:18089/1/summary"; // The default relative URI of XMRig Proxy's API config
+pub const XMRIG_API_CONFIG_URI: &str = "1/config"; // The default relative URI of XMRig's API config
 
 // Process state tooltips (online, offline, etc)
 pub const P2POOL_ALIVE: &str = "P2Pool is online and fully synchronized";
@@ -241,6 +236,26 @@ pub const STATUS_SUBMENU_PROCESSES: &str =
     "View the status of process related data for [Gupaxx|P2Pool|XMRig]";
 pub const STATUS_SUBMENU_P2POOL: &str = "View P2Pool specific data";
 pub const STATUS_SUBMENU_HASHRATE: &str = "Compare your CPU hashrate with others";
+pub const STATUS_SUBMENU_CHARTS: &str =
+    "View the history of the hashrate, effort and XvB decisions, kept across restarts";
+pub const STATUS_SUBMENU_WORKERS: &str =
+    "View the miners connected to the stratum of your P2Pool node, and the ones that left";
+//-- Workers
+pub const STATUS_WORKERS_MIN_HASHRATE: &str =
+    "Flag the workers with a hashrate under this value (H/s). 0 disables the check";
+pub const STATUS_WORKERS_SORT: &str = "Sort the workers by this column, click again to reverse";
+pub const STATUS_WORKERS_MISSING: &str =
+    "This worker was connected to P2Pool but is not anymore. It stays listed until it comes back or P2Pool is restarted";
+pub const STATUS_WORKERS_LOW: &str = "The hashrate of this worker is under the minimum";
+pub const STATUS_WORKERS_OK: &str = "This worker is connected";
+//-- Charts
+pub const STATUS_CHARTS_HOUR: &str = "Show the last hour";
+pub const STATUS_CHARTS_DAY: &str = "Show the last 24 hours";
+pub const STATUS_CHARTS_WEEK: &str = "Show the last 7 days";
+pub const STATUS_CHARTS_HASHRATE: &str = "Hashrate of XMRig/XMRig-Proxy (1 minute average) and of your P2Pool node, sampled every minute. Older than 2 days is averaged per hour";
+pub const STATUS_CHARTS_EFFORT: &str = "Current effort of P2Pool to find a block";
+pub const STATUS_CHARTS_SPLIT: &str =
+    "Percentage of time given to XvB and to P2Pool by each decision of the XvB algorithm";
 //-- P2Pool
 pub const STATUS_SUBMENU_PAYOUT:    &str = "The total amount of payouts received via P2Pool across all time. This includes all payouts you have ever received using Gupaxx and P2Pool.";
 pub const STATUS_SUBMENU_XMR:       &str = "The total of XMR mined via P2Pool across all time. This includes all the XMR you have ever mined using Gupaxx and P2Pool.";
@@ -299,9 +314,27 @@ pub const STATUS_SUBMENU_OTHER_BENCHMARKS: &str =
 // Gupaxx
 pub const GUPAX_UPDATE: &str =
     "Check for updates on Gupaxx and bundled versions of P2Pool and XMRig via GitHub's API and upgrade automatically";
-pub const GUPAX_AUTO_UPDATE: &str = "Automatically check for updates at startup";
+pub const GUPAX_AUTO_UPDATE: &str = "Automatically check for updates at startup. The release notes are shown and the update waits for your confirmation, so it is skipped in daemon mode";
 pub const GUPAX_BUNDLED_UPDATE: &str =
     "Update XMRig and P2Pool with bundled versions of latest Gupaxx. It will replace any present xmrig and p2pool binary in their specified path.";
+pub const GUPAX_UPDATE_APPLY: &str = "Download and install this release";
+pub const GUPAX_UPDATE_CANCEL: &str = "Do not update, nothing is downloaded";
+pub const GUPAX_CHANNEL_STABLE: &str = "Update to the latest stable release of Gupaxx";
+pub const GUPAX_CHANNEL_PRERELEASE: &str =
+    "Update to the newest release of Gupaxx, including pre-releases. Pre-releases can be unstable, use them on a test machine";
+pub const GUPAX_PIN_GUPAX: &str =
+    "Update to this version of Gupaxx (e.g. v1.2.0) instead of following the channel. It can be older than the current version. Leave empty to follow the channel";
+pub const GUPAX_PIN_P2POOL: &str =
+    "Hold P2Pool at this version (e.g. v4.1): it is only replaced by a bundled version matching it, another version is never downloaded. Leave empty to always take the bundled version";
+pub const GUPAX_PIN_XMRIG: &str =
+    "Hold XMRig at this version (e.g. 6.21 matches 6.21.0 and 6.21.1): it is only replaced by a bundled version matching it, another version is never downloaded. Leave empty to always take the bundled version";
+pub const GUPAX_PIN_XMRIG_PROXY: &str =
+    "Hold XMRig-Proxy at this version (e.g. 6.21.0): it is only replaced by a bundled version matching it, another version is never downloaded. Leave empty to always take the bundled version";
+pub const GUPAX_ROLLBACK: &str =
+    "Put back the binaries of Gupaxx, P2Pool and XMRig replaced by the last update. Restart Gupaxx and the processes afterwards to use them";
+pub const GUPAX_NO_ROLLBACK: &str = "No binaries kept by an update to roll back to";
+pub const GUPAX_AUTO_ROLLBACK: &str =
+    "Automatically roll back the last update if P2Pool, XMRig or XMRig-Proxy crashes on its first start after being updated";
 pub const GUPAX_SHOULD_RESTART: &str =
     "Gupaxx was updated. A restart is recommended but not required";
 // #[cfg(not(target_os = "macos"))]
@@ -341,8 +374,21 @@ pub const GUPAX_ADVANCED: &str = r#"Use advanced Gupaxx settings:
   - Basic toggles
   - P2Pool/XMRig binary path selector
   - Gupaxx resolution sliders
-  - Gupaxx start-up tab selector"#;
+  - Gupaxx start-up tab selector
+  - Local HTTP API settings
+  - Prometheus metrics settings"#;
 pub const GUPAX_SELECT: &str = "Open a file explorer to select a file";
+pub const GUPAX_HTTP_API: &str = "Serve the stats of P2Pool/XMRig/XMRig-Proxy/XvB as JSON on [/api/status] and allow to start/stop/restart them with POST requests on [/api/<process>/<action>]. Requires a restart of Gupaxx";
+pub const GUPAX_HTTP_API_IP: &str =
+    "Specify which IP to bind to for the local HTTP API; Default: [127.0.0.1]";
+pub const GUPAX_HTTP_API_PORT: &str =
+    "Specify which port to bind to for the local HTTP API; Default: [18090]";
+pub const GUPAX_HTTP_API_TOKEN: &str = "Token required in the header [Authorization: Bearer <token>] of POST requests. If empty, POST requests are refused";
+pub const GUPAX_METRICS: &str = "Expose the stats of Gupaxx/P2Pool/XMRig/XMRig-Proxy/XvB on [/metrics] in the Prometheus text format, to be scraped by Prometheus/Grafana. Requires a restart of Gupaxx";
+pub const GUPAX_METRICS_IP: &str =
+    "Specify which IP to bind to for the Prometheus metrics; Default: [127.0.0.1]";
+pub const GUPAX_METRICS_PORT: &str =
+    "Specify which port to bind to for the Prometheus metrics; Default: [18091]";
 pub const GUPAX_PATH_P2POOL: &str = "The location of the P2Pool binary: Both absolute and relative paths are accepted; A red [X] will appear if there is no file found at the given path";
 pub const GUPAX_PATH_XMRIG: &str = "The location of the XMRig binary: Both absolute and relative paths are accepted; A red [X] will appear if there is no file found at the given path";
 pub const GUPAX_PATH_XMRIG_PROXY: &str = "The location of the XMRig-Proxy binary: Both absolute and relative paths are accepted; A red [X] will appear if there is no file found at the given path";
@@ -361,6 +407,7 @@ pub const P2POOL_BACKUP_HOST_SIMPLE: &str = r#"Automatically switch to the other
 Note: you must ping the remote nodes or this feature will default to only using the currently selected node."#;
 pub const P2POOL_BACKUP_HOST_ADVANCED: &str =
     "Automatically switch to the other nodes in your list if the current one is down.";
+pub const P2POOL_FAILOVER: &str = "Keep checking the Monero node while P2Pool is running, and restart P2Pool on the next healthy node if it stops responding, is not synchronized or falls behind the other nodes. The saved settings are not changed";
 pub const P2POOL_SELECT_FASTEST: &str = "Select the fastest remote Monero node";
 pub const P2POOL_SELECT_RANDOM: &str = "Select a random remote Monero node";
 pub const P2POOL_SELECT_LAST: &str = "Select the previous remote Monero node";
@@ -402,6 +449,14 @@ pub const P2POOL_PATH_NOT_VALID: &str = "P2Pool binary at the given PATH in the
 pub const P2POOL_PATH_OK: &str = "P2Pool was found at the given PATH";
 pub const P2POOL_PATH_EMPTY: &str = "P2Pool PATH is empty! To fix: goto the [Gupaxx Advanced] tab, select [Open] and specify where P2Pool is located.";
 
+// Supervisor
+pub const SUPERVISOR_ENABLED: &str = "Automatically restart the process if it crashes. A process stopped with the [Stop] button is never restarted. Uses the last saved settings";
+pub const SUPERVISOR_MAX_RESTARTS: &str = "Maximum amount of restarts in a row before giving up";
+pub const SUPERVISOR_BACKOFF: &str =
+    "Seconds to wait before the first restart, doubled after each restart (up to 5 minutes)";
+pub const SUPERVISOR_CRASH_LOOP_WINDOW: &str =
+    "Seconds the process must stay alive for the restarts in a row to be reset";
+
 // Node/Pool list
 pub const LIST_ADD: &str = "Add the current values to the list";
 pub const LIST_SAVE: &str = "Save the current values to the already existing entry";
@@ -452,18 +507,64 @@ pub const XVB_HELP: &str = "You need to register an account by clicking on the l
 pub const XVB_URL: &str = "https://xmrvsbeast.com";
 
 pub const XVB_URL_PUBLIC_API: &str = "https://xmrvsbeast.com/p2pool/stats";
-pub const XVB_NODE_PORT: &str = "4247";
+pub const XVB_NODE_PORT: u16 = 4247;
 pub const XVB_NODE_EU: &str = "eu.xmrvsbeast.com";
 pub const XVB_NODE_NA: &str = "na.xmrvsbeast.com";
 pub const XVB_NODE_RPC: &str = "18089";
+// Seconds between two pings of the XvB nodes while XvB is running.
+pub const XVB_NODE_PING_INTERVAL: u64 = 300;
+// Another node replaces the current one only if it answers under this part of the current ping and at least this many ms faster.
+pub const XVB_NODE_HYSTERESIS_RATIO: f32 = 0.75;
+pub const XVB_NODE_HYSTERESIS_MS: u128 = 20;
+// Default address of the stratum switcher, reachable by the rigs of the local network.
+pub const XVB_SWITCHER_BIND: &str = "0.0.0.0:3344";
+// Seconds to connect and log in to an upstream of the stratum switcher.
+pub const XVB_SWITCHER_TIMEOUT: u64 = 10;
+// HTTP API of the second XMRig of the thread split.
+pub const XVB_SPLIT_API: &str = "127.0.0.1:18090";
+// Seconds for the short average of XMRig to follow the threads given back by the thread split.
+pub const XVB_SPLIT_SETTLE: u64 = 20;
 pub const XVB_URL_RULES: &str = "https://xmrvsbeast.com/p2pool/rules.html";
 // buffer in percentage of HR to have plus the requirement.
 pub const XVB_SIDE_MARGIN_1H: f32 = 1.20;
 pub const XVB_BUFFER: f32 = 1.05;
 pub const XVB_TIME_ALGO: u32 = 600;
 pub const XVB_TOKEN_LEN: usize = 9;
+pub const XVB_DEFAULT_SELECT: &str =
+    "This mode will donate the hashrate needed for the highest round reachable while keeping a share in the p2pool PPLNS window.\nWhen modified, the algorithm will use the new choice at the next decision.";
+pub const XVB_PROBABILISTIC_SELECT: &str =
+    "This mode is like the default mode, but keeps a share in the p2pool PPLNS window with the probability chosen in the advanced settings (95% by default) instead of adding a 5% buffer to the average hashrate needed. With a high probability, it donates less but the share is less likely to leave the window.\nWhen modified, the algorithm will use the new choice at the next decision.";
 pub const XVB_HERO_SELECT: &str =
     "This mode will donate all available hashrate while keeping a share in the  p2pool PPLNS window.\nWhen modified, the algorithm will use the new choice at the next decision.";
+pub const XVB_STRATUM_SELECT: &str =
+    "Use the hashrate of the miners connected to the stratum of the P2Pool of Gupaxx instead of the sidechain estimate to know the hashrate outside of Gupaxx. It is exact, but only counts the miners pointed to this P2Pool.\nA warning is shown in the console if it diverges from the sidechain estimate.\nWhen modified, XvB must be restarted.";
+pub const XVB_SIMPLE: &str = r#"Use simple XvB settings:
+  - Token
+  - Mode (Default, Hero, Probabilistic)
+  - Stratum hashrate"#;
+pub const XVB_ADVANCED: &str = r#"Use advanced XvB settings:
+  - Manual outside hashrate
+  - Manual split of the cycle
+  - Round cap
+  - Share confidence of the probabilistic mode
+  - Stratum switcher
+  - Remote rigs
+  - Thread split
+  - Round history"#;
+pub const XVB_MANUAL_OUTSIDE_HR: &str = "Use this hashrate as the hashrate mining to your address outside of Gupaxx, instead of the estimate of the last hour.\nUseful when rigs were just added or removed.\nWhen modified, XvB must be restarted.";
+pub const XVB_MANUAL_SPLIT: &str = "Always give this part of the ten minutes cycle to XvB, in percent or in seconds, as long as a share is in the PPLNS window.\nThe algorithm will not check if enough hashrate is left to keep the share.\nWhen modified, XvB must be restarted.";
+pub const XVB_SHARE_CONFIDENCE_SELECT: &str = "Probability to keep at least one share in the PPLNS window, used by the probabilistic mode to decide the hashrate kept on P2Pool.\nShares are found following a Poisson law, from the hashrate on P2Pool, the sidechain difficulty and the size of the window (mini or main).\nThe probability of each decision is shown in the console, whatever the mode.\nWhen modified, XvB must be restarted.";
+pub const XVB_STRATUM_SWITCHER: &str = "Run a stratum endpoint in Gupaxx on this address and switch the miners connected to it between P2Pool and XvB, instead of changing the pool of XMRig or XMRig-Proxy with their HTTP API.\nEvery rig pointed to it follows the decisions of the algorithm without reconnecting, no HTTP token is needed.\nWhen modified, XvB must be restarted.";
+pub const XVB_THREAD_SPLIT: &str = "Instead of mining on XvB with all the threads for a part of the ten minutes cycle, start a second XMRig mining on XvB with a part of the threads for the whole cycle. XMRig keeps the other threads on P2Pool.\nThe part of the threads is rounded down and P2Pool always keeps one. Only used with XMRig, not with XMRig-Proxy, the stratum switcher or remote rigs.\nThe second XMRig needs the memory of its own RandomX dataset (around 2 GB).\nWhen modified, XvB must be restarted.";
+pub const XVB_REMOTE_RIGS: &str = "XMRig instances of the [[xvb.rigs]] list of the state file, switched between P2Pool and XvB with their HTTP API.\nTheir hashrate is added to the hashrate controlled by the algorithm, an unreachable rig is not counted.\nWhen modified, XvB must be restarted.";
+pub const XVB_MANUAL_ROUND: &str = "Never aim for a round higher than this one, even if it is reachable. Also limits the hero mode.\nWhen modified, XvB must be restarted.";
+// Relative difference between the stratum hashrate and the sidechain estimate to warn about.
+pub const XVB_STRATUM_DIVERGENCE: f32 = 0.30;
+// Relative change of the sidechain difficulty that makes the algorithm decide again before the end of the cycle.
+pub const XVB_TRIGGER_DIFFICULTY: f32 = 0.25;
+// Part of the local hashrate of the start of the cycle under which it is considered collapsed.
+pub const XVB_TRIGGER_HASHRATE: f32 = 0.5;
+pub const XVB_ROUND_HISTORY: &str = "Every raffle round that ended while XvB was running: the round qualified for, the averages donated and the time given to XvB during the round, and the draw.\nThe first round after a start only counts the time donated since the start.";
 pub const XVB_TOKEN_FIELD: &str = "Token";
 pub const XVB_FAILURE_FIELD: &str = "Failures";
 pub const XVB_DONATED_1H_FIELD: &str = "Donated last hour";
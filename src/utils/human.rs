@@ -51,6 +51,11 @@ impl HumanTime {
         HumanTime(Duration::from_secs(u))
     }
 
+    #[inline]
+    pub const fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+
     fn plural(
         f: &mut std::fmt::Formatter,
         started: &mut bool,
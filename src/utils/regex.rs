@@ -17,11 +17,11 @@
 
 // Some regexes used throughout Gupax.
 
-use log::{error, warn};
+use log::warn;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::helper::xvb::nodes::XvbNode;
+use crate::helper::xvb::nodes::{XvbNode, XvbNodeConfig};
 
 //---------------------------------------------------------------------------------------------------- Lazy
 pub static REGEXES: Lazy<Regexes> = Lazy::new(Regexes::new);
@@ -141,85 +141,26 @@ pub fn num_lines(s: &str) -> usize {
     static LINE_BREAKS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\r?\n").unwrap());
     LINE_BREAKS.captures_iter(s).count() + 1
 }
-// get the number of current shares
-pub fn nb_current_shares(s: &str) -> Option<u32> {
-    static CURRENT_SHARE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"Your shares               = (?P<nb>\d+) blocks").unwrap());
-    if let Some(c) = CURRENT_SHARE.captures(s) {
-        if let Some(m) = c.name("nb") {
-            return Some(m.as_str().parse::<u32>().unwrap_or_else(|_| {
-                panic!(
-                    "{}",
-                    [
-                        "the number of shares should have been a unit number but is :\n",
-                        m.as_str(),
-                    ]
-                    .concat()
-                )
-            }));
-        }
-    }
-    None
-}
-pub fn detect_new_node_xmrig(s: &str) -> Option<XvbNode> {
+pub fn detect_new_node_xmrig(s: &str, nodes: &[XvbNodeConfig], p2pool: &str) -> Option<XvbNode> {
     static CURRENT_SHARE: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"use pool (?P<pool>.*?) ").unwrap());
     if let Some(c) = CURRENT_SHARE.captures(s) {
         if let Some(m) = c.name("pool") {
-            match m.as_str() {
-                // if user change address of local p2pool, it could create issue
-                "127.0.0.1:3333" => {
-                    return Some(XvbNode::P2pool);
-                }
-                "eu.xmrvsbeast.com:4247" => {
-                    return Some(XvbNode::Europe);
-                }
-                "na.xmrvsbeast.com:4247" => {
-                    return Some(XvbNode::NorthAmerica);
-                }
-                _ => {}
+            let pool = m.as_str();
+            if pool == p2pool {
+                return Some(XvbNode::P2pool);
             }
-        }
-    }
-    warn!("a line on xmrig console was detected as using a new pool but the syntax was not recognized or it was not a pool useable for the algorithm.");
-    None
-}
-pub fn estimated_hr(s: &str) -> Option<f32> {
-    static CURRENT_SHARE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"(?P<nb>[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?) (?P<unit>.*)H/s").unwrap()
-    });
-    if let Some(c) = CURRENT_SHARE.captures(s) {
-        let coeff = if let Some(unit) = c.name("unit") {
-            match unit.as_str() {
-                "K" => 1000,
-                "M" => 1000 * 1000,
-                "G" => 1000 * 1000 * 1000,
-                _ => {
-                    error!("unit of your p2pool sidechain HR is not recognized.");
-                    1
-                }
+            if let Some(node) = nodes
+                .iter()
+                .find(|n| pool == format!("{}:{}", n.host, n.port))
+            {
+                return Some(XvbNode::Xvb(node.clone()));
             }
-        } else {
-            1
-        } as f32;
-        if let Some(m) = c.name("nb") {
-            return Some(
-                m.as_str().parse::<f32>().unwrap_or_else(|_| {
-                    panic!(
-                        "{}",
-                        [
-                            "the number of shares should have been a float number but is :\n",
-                            m.as_str(),
-                        ]
-                        .concat()
-                    )
-                }) * coeff,
-            );
         }
     }
+    warn!("a line on xmrig console was detected as using a new pool but the syntax was not recognized or it was not a pool useable for the algorithm.");
     None
 }
-
 pub fn contains_timeout(l: &str) -> bool {
     static LINE_SHARE: Lazy<Regex> = Lazy::new(|| Regex::new(r"timeout").unwrap());
     LINE_SHARE.is_match(l)
@@ -232,24 +173,6 @@ pub fn contains_usepool(l: &str) -> bool {
     static LINE_SHARE: Lazy<Regex> = Lazy::new(|| Regex::new(r"use pool").unwrap());
     LINE_SHARE.is_match(l)
 }
-pub fn contains_statuscommand(l: &str) -> bool {
-    static LINE_SHARE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^statusfromgupaxx").unwrap());
-    LINE_SHARE.is_match(l)
-}
-pub fn contains_yourshare(l: &str) -> bool {
-    static LINE_SHARE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^Your shares               = ").unwrap());
-    LINE_SHARE.is_match(l)
-}
-pub fn contains_yourhashrate(l: &str) -> bool {
-    static LINE_SHARE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^Your hashrate \(pool-side\) = ").unwrap());
-    LINE_SHARE.is_match(l)
-}
-pub fn contains_end_status(l: &str) -> bool {
-    static LINE_SHARE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Uptime         ").unwrap());
-    LINE_SHARE.is_match(l)
-}
 //---------------------------------------------------------------------------------------------------- TEST
 #[cfg(test)]
 mod test {